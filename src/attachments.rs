@@ -0,0 +1,65 @@
+use printpdf::{PdfDocumentReference, IndirectFontRef, Image, ImageTransform, Mm, Pt};
+use printpdf::image_crate;
+use anyhow::{Error, Result, anyhow};
+
+// Supporting photos (scale ticket scans, delivery photos) appended after the
+// document, one per page, scaled to fit between the margins with the caption
+// underneath.
+
+const PAGE_WIDTH: Pt = Pt(612.0);
+const PAGE_HEIGHT: Pt = Pt(792.0);
+const MARGIN: f64 = 54.0;
+const CAPTION_SIZE: f64 = 10.0;
+// Room below the image for the caption
+const CAPTION_HEIGHT: f64 = 20.0;
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Attachment {
+    pub caption: String,
+    // Encoded image file, e.g. JPEG or PNG
+    pub image: Vec<u8>,
+}
+
+// Photos larger than `max_pixels` on their longer side are scaled down first
+pub(crate) fn add_attachment_pages(doc: &PdfDocumentReference, attachments: &[Attachment], font: &IndirectFontRef, max_pixels: Option<u32>) -> Result<(), Error> {
+    for attachment in attachments {
+        let decoded = match image_crate::load_from_memory(&attachment.image) {
+            Ok(decoded) => decoded,
+            Err(e) => return Err(anyhow!(format!("Could not decode the attachment `{}`. Reason: `{e}`", attachment.caption))),
+        };
+        let decoded = match max_pixels {
+            Some(max) if decoded.width().max(decoded.height()) > max => decoded.thumbnail(max, max),
+            _ => decoded,
+        };
+        let width = decoded.width() as f64;
+        let height = decoded.height() as f64;
+        if width == 0.0 || height == 0.0 {
+            continue;
+        }
+
+        // At 72 dpi one pixel is one point, so the scale is the ratio of the
+        // available space to the pixel size
+        let available_width = PAGE_WIDTH.0 - MARGIN * 2.0;
+        let available_height = PAGE_HEIGHT.0 - MARGIN * 2.0 - CAPTION_HEIGHT;
+        let scale = (available_width / width).min(available_height / height);
+        let image_width = width * scale;
+        let image_height = height * scale;
+        let x = MARGIN + (available_width - image_width) / 2.0;
+        let y = PAGE_HEIGHT.0 - MARGIN - image_height;
+
+        let (page, layer) = doc.add_page(PAGE_WIDTH.into(), PAGE_HEIGHT.into(), "Layer 1");
+        let layer = doc.get_page(page).get_layer(layer);
+        Image::from_dynamic_image(&decoded).add_to_layer(layer.clone(), ImageTransform {
+            translate_x: Some(Pt(x).into()),
+            translate_y: Some(Pt(y).into()),
+            scale_x: Some(scale),
+            scale_y: Some(scale),
+            dpi: Some(72.0),
+            ..Default::default()
+        });
+        let caption_y: Mm = Pt(y - CAPTION_HEIGHT + 6.0).into();
+        layer.use_text(&attachment.caption, CAPTION_SIZE, Pt(x).into(), caption_y, font);
+    }
+    return Ok(());
+}
@@ -0,0 +1,85 @@
+use printpdf::lopdf::{Document, Object};
+use anyhow::{Error, Result, anyhow};
+
+// Output size limits, for e-invoicing gateways which reject large files.
+// When a document comes out over GenOptions::max_size it's generated again
+// with each of the reductions in turn until it fits. printpdf embeds whole
+// font files, so they are first subset to the glyphs drawn (see subset.rs)
+// and then cut down by leaving out the optional faces. A document still over
+// the limit is an error listing the largest resources, so whoever set it up
+// knows what to trim.
+
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Reduction {
+    // Flate compresses every stream
+    pub compress: bool,
+    // Empties the glyphs the document doesn't draw out of the fonts
+    pub subset_fonts: bool,
+    // Semibold and italic text use the regular and bold faces
+    pub required_fonts_only: bool,
+    // Longer side of the attachment photos, in pixels
+    pub max_image_pixels: Option<u32>,
+}
+
+// In order of what they cost the look of the document
+pub(crate) const REDUCTIONS: [Reduction; 5] = [
+    Reduction { compress: true, subset_fonts: false, required_fonts_only: false, max_image_pixels: None },
+    Reduction { compress: true, subset_fonts: true, required_fonts_only: false, max_image_pixels: None },
+    Reduction { compress: true, subset_fonts: true, required_fonts_only: true, max_image_pixels: None },
+    Reduction { compress: true, subset_fonts: true, required_fonts_only: true, max_image_pixels: Some(1600) },
+    Reduction { compress: true, subset_fonts: true, required_fonts_only: true, max_image_pixels: Some(800) },
+];
+
+pub(crate) fn compress(pdf: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut doc = Document::load_mem(pdf)?;
+    doc.compress();
+    let mut bytes = Vec::new();
+    doc.save_to(&mut bytes)?;
+    return Ok(bytes);
+}
+
+pub(crate) fn over_budget_error(pdf: &[u8], max_size: usize) -> Error {
+    let mut message = format!("The document is {} bytes, over the limit of {max_size} bytes", pdf.len());
+    if let Ok(doc) = Document::load_mem(pdf) {
+        let mut resources = largest_resources(&doc);
+        resources.truncate(5);
+        if !resources.is_empty() {
+            let list: Vec<String> = resources.iter().map(|(name, size)| format!("{name} ({size} bytes)")).collect();
+            message.push_str(&format!(". Largest resources: {}", list.join(", ")));
+        }
+    }
+    return anyhow!(message);
+}
+
+// Streams by size, named after what they are
+fn largest_resources(doc: &Document) -> Vec<(String, usize)> {
+    let mut resources = Vec::new();
+    for (id, object) in &doc.objects {
+        let Object::Stream(stream) = object else { continue };
+        let name = if stream.dict.get(b"Subtype").and_then(Object::as_name).ok() == Some(&b"Image"[..]) {
+            let dimension = |key: &[u8]| stream.dict.get(key).and_then(Object::as_i64).unwrap_or(0);
+            format!("image {}x{}", dimension(b"Width"), dimension(b"Height"))
+        } else if let Some(font) = font_name(doc, *id) {
+            format!("font {font}")
+        } else {
+            format!("stream {} {}", id.0, id.1)
+        };
+        resources.push((name, stream.content.len()));
+    }
+    resources.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+    return resources;
+}
+
+// Name of the font whose file is the stream `id`
+fn font_name(doc: &Document, id: (u32, u16)) -> Option<String> {
+    for object in doc.objects.values() {
+        let Ok(dict) = object.as_dict() else { continue };
+        let embeds = [&b"FontFile"[..], b"FontFile2", b"FontFile3"].iter()
+            .any(|key| dict.get(key).and_then(Object::as_reference).ok() == Some(id));
+        if embeds {
+            let name = dict.get(b"FontName").and_then(Object::as_name_str).unwrap_or("unnamed");
+            return Some(name.to_owned());
+        }
+    }
+    return None;
+}
@@ -0,0 +1,214 @@
+use printpdf::lopdf::{Dictionary, Document, Object, ObjectId, Stream};
+use printpdf::{PdfDocument, PdfLayerReference, IndirectFontRef, Mm, Pt};
+use anyhow::{Error, Result, anyhow};
+use crate::drawing::QuickShapes;
+use crate::stamp::{inherited, real, rect_from};
+use crate::PdfResources;
+
+// Printer calibration. Printers clip and shift the page differently, so the
+// whole page can be moved and scaled to line up with pre-printed stock. The
+// saved document is adjusted afterwards, like the stamp: every page's
+// content is wrapped in a transform and its annotations (links and form
+// fields) are moved along with it.
+
+// Layout of the calibration page, in points
+const PAGE_WIDTH: f64 = 612.0;
+const PAGE_HEIGHT: f64 = 792.0;
+const CONTENT_MARGIN: f64 = 54.0;
+const EDGE_MARGIN: f64 = 18.0;
+// Clear of the square and the instructions
+const HORIZONTAL_RULER: f64 = 100.0;
+const VERTICAL_RULER: f64 = 520.0;
+// Side of the square to measure the scale against, 4"
+const SCALE_SQUARE: f64 = 288.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Calibration {
+    // Moves the page right and up, in points
+    pub x_offset: f64,
+    pub y_offset: f64,
+    // About the center of the page, 1.0 leaves the size unchanged
+    pub scale: f64,
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        return Self {
+            x_offset: 0.0,
+            y_offset: 0.0,
+            scale: 1.0,
+        };
+    }
+}
+
+impl Calibration {
+    pub fn is_identity(&self) -> bool {
+        return *self == Self::default();
+    }
+
+    // The "cm" matrix for a page with the given media box
+    fn matrix(&self, media_box: [f64; 4]) -> [f64; 6] {
+        let [llx, lly, urx, ury] = media_box;
+        let center_x = (llx + urx) / 2.0;
+        let center_y = (lly + ury) / 2.0;
+        return [
+            self.scale, 0.0, 0.0, self.scale,
+            center_x * (1.0 - self.scale) + self.x_offset,
+            center_y * (1.0 - self.scale) + self.y_offset,
+        ];
+    }
+}
+
+// Applies the calibration to every page of a saved PDF
+pub fn calibrate_pdf(pdf: &[u8], calibration: &Calibration) -> Result<Vec<u8>, Error> {
+    if calibration.scale.is_nan() || calibration.scale <= 0.0 {
+        return Err(anyhow!(format!("Invalid calibration scale {}", calibration.scale)));
+    }
+    let mut doc = Document::load_mem(pdf)?;
+    for (_, page_id) in doc.get_pages() {
+        let media_box = inherited(&doc, page_id, b"MediaBox")
+            .and_then(|media_box| rect_from(&media_box))
+            .unwrap_or([0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT]);
+        transform_page(&mut doc, page_id, calibration.matrix(media_box))?;
+    }
+
+    let mut bytes = Vec::new();
+    doc.save_to(&mut bytes)?;
+    return Ok(bytes);
+}
+
+// Wraps the content of a page in the transform and moves its annotations
+// along with it
+pub(crate) fn transform_page(doc: &mut Document, page_id: ObjectId, matrix: [f64; 6]) -> Result<(), Error> {
+    let [a, b, c, d, e, f] = matrix;
+    let transform = format!("q {a} {b} {c} {d} {e} {f} cm\n");
+    let save = doc.add_object(Stream::new(Dictionary::new(), transform.into_bytes()));
+    let restore = doc.add_object(Stream::new(Dictionary::new(), b"\nQ".to_vec()));
+
+    let mut annotations = match doc.get_object(page_id)?.as_dict()?.get(b"Annots") {
+        Ok(Object::Array(annotations)) => annotations.clone(),
+        Ok(Object::Reference(id)) => doc.get_object(*id)?.as_array()?.clone(),
+        _ => Vec::new(),
+    };
+
+    let page = doc.get_object_mut(page_id)?.as_dict_mut()?;
+    let mut contents = vec![Object::Reference(save)];
+    match page.get(b"Contents") {
+        Ok(Object::Array(existing)) => contents.extend(existing.iter().cloned()),
+        Ok(existing) => contents.push(existing.clone()),
+        Err(_) => {},
+    }
+    contents.push(Object::Reference(restore));
+    page.set("Contents", contents);
+
+    let mut inline = false;
+    for annotation in annotations.iter_mut() {
+        match annotation {
+            Object::Reference(id) => move_rect(doc.get_object_mut(*id)?.as_dict_mut()?, matrix),
+            Object::Dictionary(annotation) => {
+                move_rect(annotation, matrix);
+                inline = true;
+            },
+            _ => {},
+        }
+    }
+    if inline {
+        doc.get_object_mut(page_id)?.as_dict_mut()?.set("Annots", annotations);
+    }
+    return Ok(());
+}
+
+// Annotations aren't affected by the content transform, so their position
+// is transformed the same way
+fn move_rect(annotation: &mut Dictionary, matrix: [f64; 6]) {
+    let [a, _, _, d, e, f] = matrix;
+    if let Some([llx, lly, urx, ury]) = annotation.get(b"Rect").ok().and_then(rect_from) {
+        annotation.set("Rect", vec![real(a * llx + e), real(d * lly + f), real(a * urx + e), real(d * ury + f)]);
+    }
+}
+
+// A test page with rulers, a square of known size and margin markers, printed
+// with the given calibration. Print it, measure, adjust and print again.
+pub fn gen_calibration_page(resources: &PdfResources, calibration: &Calibration) -> Result<Vec<u8>, Error> {
+    let (doc, page, layer) = PdfDocument::new("Printer Calibration", Pt(PAGE_WIDTH).into(), Pt(PAGE_HEIGHT).into(), "Layer 1");
+    let layer = doc.get_page(page).get_layer(layer);
+    let font_regular = doc.add_external_font(resources.font_regular.as_ref())?;
+    let font_bold = doc.add_external_font(resources.font_bold.as_ref())?;
+    let pt = |value: f64| -> Mm { Pt(value).into() };
+
+    // Anything the printer clips loses part of these
+    layer.add_box(pt(EDGE_MARGIN), pt(EDGE_MARGIN), pt(PAGE_WIDTH - EDGE_MARGIN), pt(PAGE_HEIGHT - EDGE_MARGIN));
+    // Where the document content starts
+    layer.add_dashed_line(pt(0.0), pt(CONTENT_MARGIN), pt(PAGE_WIDTH), pt(CONTENT_MARGIN));
+    layer.add_dashed_line(pt(0.0), pt(PAGE_HEIGHT - CONTENT_MARGIN), pt(PAGE_WIDTH), pt(PAGE_HEIGHT - CONTENT_MARGIN));
+    layer.add_dashed_line(pt(CONTENT_MARGIN), pt(0.0), pt(CONTENT_MARGIN), pt(PAGE_HEIGHT));
+    layer.add_dashed_line(pt(PAGE_WIDTH - CONTENT_MARGIN), pt(0.0), pt(PAGE_WIDTH - CONTENT_MARGIN), pt(PAGE_HEIGHT));
+
+    // Rulers from the left and bottom edges of the paper
+    draw_ruler(&layer, &font_regular, PAGE_WIDTH, HORIZONTAL_RULER, true);
+    draw_ruler(&layer, &font_regular, PAGE_HEIGHT, VERTICAL_RULER, false);
+
+    let square_x = (PAGE_WIDTH - SCALE_SQUARE) / 2.0;
+    let square_y = PAGE_HEIGHT - CONTENT_MARGIN - 60.0 - SCALE_SQUARE;
+    layer.add_box(pt(square_x), pt(square_y), pt(square_x + SCALE_SQUARE), pt(square_y + SCALE_SQUARE));
+
+    let text_x = CONTENT_MARGIN + 12.0;
+    let mut text_y = PAGE_HEIGHT - CONTENT_MARGIN - 20.0;
+    layer.use_text("Printer Calibration", 16.0, pt(text_x), pt(text_y), &font_bold);
+    text_y -= 18.0;
+    layer.use_text(
+        format!("Current: x offset {:.1} pt, y offset {:.1} pt, scale {:.3}", calibration.x_offset, calibration.y_offset, calibration.scale),
+        9.0, pt(text_x), pt(text_y), &font_regular,
+    );
+    let instructions = [
+        "The square should measure exactly 4 in (101.6 mm) on each side. If it doesn't, set the",
+        "scale to 4 in divided by the measured size, multiplied by the current scale.",
+        "The rulers are measured from the left and bottom edges of the paper. Where the ruler reads",
+        "1 in, it should be 1 in from the edge. Add the difference to the offsets (72 pt = 1 in).",
+        "The solid box is 1/4 in inside the paper and should print without being clipped. The dashed",
+        "lines mark the document margins.",
+    ];
+    let mut text_y = square_y - 24.0;
+    for line in instructions {
+        layer.use_text(line, 9.0, pt(text_x), pt(text_y), &font_regular);
+        text_y -= 12.0;
+    }
+
+    let bytes = doc.save_to_bytes()?;
+    if calibration.is_identity() {
+        return Ok(bytes);
+    }
+    return calibrate_pdf(&bytes, calibration);
+}
+
+// Ruler from one edge of the paper to the other at `position`, with ticks
+// every 1/8", longer ones every 1/2" and a label every inch
+fn draw_ruler(layer: &PdfLayerReference, font: &IndirectFontRef, length: f64, position: f64, horizontal: bool) {
+    let pt = |value: f64| -> Mm { Pt(value).into() };
+    // Lines and text given as (distance along the ruler, distance across it)
+    let line = |along_1: f64, across_1: f64, along_2: f64, across_2: f64| {
+        if horizontal {
+            layer.add_line(pt(along_1), pt(across_1), pt(along_2), pt(across_2));
+        } else {
+            layer.add_line(pt(across_1), pt(along_1), pt(across_2), pt(along_2));
+        }
+    };
+    let label = |text: &str, along: f64, across: f64| {
+        if horizontal {
+            layer.use_text(text, 7.0, pt(along), pt(across), font);
+        } else {
+            layer.use_text(text, 7.0, pt(across), pt(along), font);
+        }
+    };
+    line(0.0, position, length, position);
+    let mut tick = 1;
+    while tick as f64 * 9.0 < length {
+        let along = tick as f64 * 9.0;
+        let size = if tick % 8 == 0 { 12.0 } else if tick % 4 == 0 { 8.0 } else { 4.0 };
+        line(along, position, along, position + size);
+        if tick % 8 == 0 {
+            label(&(tick / 8).to_string(), along + 2.0, position + 6.0);
+        }
+        tick += 1;
+    }
+}
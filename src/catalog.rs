@@ -0,0 +1,149 @@
+use std::fs;
+use anyhow::{Error, Result, anyhow};
+use crate::{money, ReceiptInfo};
+
+// Product catalog merged into the item lines by pre_pass, for what the POS
+// doesn't know or abbreviates: a readable description, the unit of measure,
+// whether the product is taxable, its category, a picture for the item
+// table and quantity price breaks for quotes. Anything left empty in the
+// catalog keeps what the POS sent.
+//
+// The catalog file has one product per line, with tab separated columns:
+//
+//     code	description	U/M	taxable	category	image	price breaks
+//     34CG	3/4" Crushed Gravel	TON	yes	Aggregates	34cg.jpg	1:12.00;10:10.50
+//
+// Price breaks are minimum quantity and unit price pairs separated by ";".
+// Trailing columns may be left out. Blank lines and lines starting with "#"
+// are skipped.
+
+#[derive(Debug, Clone, Default)]
+pub struct Product {
+    pub code: String,
+    pub description: String,
+    // Used when the POS didn't send a unit
+    pub uom: String,
+    // Overrides the POS, which doesn't always know
+    pub taxable: Option<bool>,
+    pub category: String,
+    // File in data_dir/products, printed with GenOptions::product_images
+    pub image: String,
+    // In increasing order of quantity
+    pub price_breaks: Vec<PriceBreak>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PriceBreak {
+    pub min_quantity: f64,
+    pub unit_price: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ProductCatalog {
+    products: Vec<Product>,
+}
+
+impl ProductCatalog {
+    pub fn load(file: &str) -> Result<Self, Error> {
+        let text = match fs::read_to_string(file) {
+            Ok(text) => text,
+            Err(e) => return Err(anyhow!(format!("Could not read the product catalog from the file: `{file}`. Reason: `{e}`"))),
+        };
+        return Self::parse(&text);
+    }
+
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let mut catalog = Self::default();
+        for (number, line) in text.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut columns = line.split('\t').map(str::trim);
+            let mut column = || columns.next().unwrap_or("").to_owned();
+            let code = column();
+            if code.is_empty() {
+                return Err(anyhow!(format!("Line {} of the product catalog has no item code", number + 1)));
+            }
+            let (description, uom, taxable, category, image, breaks) = (column(), column(), column(), column(), column(), column());
+            let taxable = match taxable.to_lowercase().as_str() {
+                "" => None,
+                "yes" | "y" | "true" => Some(true),
+                "no" | "n" | "false" => Some(false),
+                _ => return Err(anyhow!(format!("`{taxable}` on line {} of the product catalog isn't yes or no", number + 1))),
+            };
+            let mut price_breaks = Vec::new();
+            for price_break in breaks.split(';').map(str::trim).filter(|price_break| !price_break.is_empty()) {
+                let invalid = || anyhow!(format!("`{price_break}` on line {} of the product catalog isn't a quantity:price break", number + 1));
+                let (quantity, price) = price_break.split_once(':').ok_or_else(invalid)?;
+                price_breaks.push(PriceBreak {
+                    min_quantity: money::parse_amount(quantity).map_err(|_| invalid())?,
+                    unit_price: money::cleanup_amount(price).map_err(|_| invalid())?,
+                });
+            }
+            price_breaks.sort_by(|a, b| a.min_quantity.total_cmp(&b.min_quantity));
+            catalog = catalog.with_product(Product { code, description, uom, taxable, category, image, price_breaks });
+        }
+        return Ok(catalog);
+    }
+
+    // Adds a product, or replaces the one with the same code
+    pub fn with_product(mut self, product: Product) -> Self {
+        self.products.retain(|existing| existing.code != product.code);
+        self.products.push(product);
+        return self;
+    }
+
+    pub fn get(&self, code: &str) -> Option<&Product> {
+        let code = code.trim();
+        if code.is_empty() {
+            return None;
+        }
+        return self.products.iter().find(|product| product.code == code);
+    }
+}
+
+impl ReceiptInfo {
+    pub(crate) fn apply_catalog(&mut self, catalog: &ProductCatalog) {
+        for line in &mut self.item_lines {
+            let Some(product) = catalog.get(&line.code) else { continue };
+            if !product.description.is_empty() {
+                line.description = product.description.clone();
+            }
+            if line.uom.trim().is_empty() {
+                line.uom = product.uom.clone();
+            }
+            if let Some(taxable) = product.taxable {
+                line.taxable = taxable;
+            }
+            if line.category.is_empty() {
+                line.category = product.category.clone();
+            }
+            if line.image.is_empty() {
+                line.image = product.image.clone();
+            }
+            if line.price_breaks.is_empty() {
+                line.price_breaks = product.price_breaks.clone();
+            }
+        }
+    }
+}
+
+// e.g. "1-9: $12.00, 10+: $10.50". Ranges are only given for whole
+// quantities, otherwise every break reads "from this quantity up".
+pub(crate) fn describe_price_breaks(breaks: &[PriceBreak], format: &money::AmountFormat, currency: &money::CurrencyFormat) -> String {
+    let whole = breaks.iter().all(|price_break| price_break.min_quantity.fract() == 0.0);
+    let mut parts = Vec::new();
+    for (i, price_break) in breaks.iter().enumerate() {
+        let price = format.display(&money::normalize_unit_price(&price_break.unit_price));
+        let quantities = match breaks.get(i + 1) {
+            Some(next) if whole && next.min_quantity - 1.0 > price_break.min_quantity => {
+                format!("{}-{}", price_break.min_quantity, next.min_quantity - 1.0)
+            },
+            Some(_) if whole => format!("{}", price_break.min_quantity),
+            _ => format!("{}+", price_break.min_quantity),
+        };
+        parts.push(format!("{quantities}: {}", currency.with_symbol(&price)));
+    }
+    return parts.join(", ");
+}
@@ -0,0 +1,228 @@
+use sha2::{Digest, Sha256};
+use crate::{Amount, Money, ReceiptInfo, SymbolPosition};
+
+// Content checksum printed in the footer, so a reprinted or altered document
+// can be checked against the record it was generated from. The hash covers
+// everything printed on the document, after pre_pass, in a fixed encoding:
+// every field is length-prefixed, so moving text from one field to the next
+// changes the hash, and numbers are hashed by their bits. Amounts are hashed
+// as they print, with two decimals, and blank when they're left out.
+//
+// It isn't serde's JSON: serde is an optional feature, and a checksum printed
+// on a document years ago has to verify against its record as long as it's
+// kept, whatever the serde_json version or the Debug output of a type.
+
+// Hex digits printed on the document
+const PRINTED_LENGTH: usize = 8;
+
+struct Canonical {
+    hasher: Sha256,
+}
+
+impl Canonical {
+    fn text(&mut self, text: &str) {
+        self.bytes(text.as_bytes());
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.hasher.update((bytes.len() as u64).to_le_bytes());
+        self.hasher.update(bytes);
+    }
+
+    // Number of items in a list, or whether an optional part is present
+    fn count(&mut self, count: usize) {
+        self.hasher.update((count as u64).to_le_bytes());
+    }
+
+    fn number(&mut self, number: f64) {
+        self.hasher.update(number.to_bits().to_le_bytes());
+    }
+
+    fn character(&mut self, character: char) {
+        self.hasher.update(u32::from(character).to_le_bytes());
+    }
+
+    fn amount(&mut self, amount: Option<Money>) {
+        self.text(&amount.map(|amount| amount.to_string()).unwrap_or_default());
+    }
+
+    fn amounts(&mut self, amounts: &[Amount]) {
+        self.count(amounts.len());
+        for amount in amounts {
+            self.text(&amount.name);
+            self.amount(Some(amount.value));
+        }
+    }
+}
+
+impl ReceiptInfo {
+    // SHA-256 of the document content, as hex
+    pub fn checksum(&self) -> String {
+        let mut canonical = Canonical { hasher: Sha256::new() };
+        for field in [
+            &self.title, &self.date, &self.company_name, &self.company_info_line, &self.customer_info,
+            &self.transaction_number, &self.order_id, &self.vat_number, &self.doc_number,
+        ] {
+            canonical.text(field);
+        }
+        canonical.text(self.doc_type.slug());
+        canonical.count(self.item_lines.len());
+        for line in &self.item_lines {
+            for field in [&line.code, &line.description, &line.quantity, &line.unit_price] {
+                canonical.text(field);
+            }
+            canonical.amount(line.amount);
+            canonical.text(&line.uom);
+            canonical.text(&line.image);
+            canonical.count(line.price_breaks.len());
+            for price_break in &line.price_breaks {
+                canonical.number(price_break.min_quantity);
+                canonical.text(&price_break.unit_price);
+            }
+            canonical.count(line.discount.is_some() as usize);
+            canonical.text(&line.discount.as_ref().map(ToString::to_string).unwrap_or_default());
+            canonical.count(line.taxable as usize);
+            canonical.count(line.price_override as usize);
+            canonical.count(line.rental.is_some() as usize);
+            if let Some(rental) = &line.rental {
+                for field in [&rental.start, &rental.end, &rental.rate] {
+                    canonical.text(field);
+                }
+                canonical.count(rental.period as usize);
+            }
+        }
+        for field in [&self.delivery_tickets, &self.weigh_tickets] {
+            canonical.text(field);
+        }
+        canonical.amounts(&self.totals);
+        canonical.amounts(&self.payments);
+        canonical.amount(self.amount_due);
+        for field in [&self.employee, &self.slogan, &self.notes] {
+            canonical.text(field);
+        }
+
+        canonical.count(self.statement.is_some() as usize);
+        if let Some(statement) = &self.statement {
+            canonical.amount(statement.opening_balance);
+            canonical.amount(statement.closing_balance);
+            canonical.count(statement.transactions.len());
+            for line in &statement.transactions {
+                for field in [&line.date, &line.reference, &line.description] {
+                    canonical.text(field);
+                }
+                canonical.amount(Some(line.amount));
+                canonical.amount(line.balance);
+                canonical.text(&line.due_date);
+            }
+            canonical.count(statement.aging.is_some() as usize);
+            if let Some(aging) = &statement.aging {
+                for amount in [aging.current, aging.days_31_60, aging.days_61_90, aging.over_90, aging.total] {
+                    canonical.amount(Some(amount));
+                }
+            }
+        }
+        canonical.count(self.quote_reference.is_some() as usize);
+        if let Some(quote) = &self.quote_reference {
+            canonical.text(&quote.quote_number);
+            canonical.amount(Some(quote.quoted_total));
+        }
+        canonical.count(self.progress_billing.is_some() as usize);
+        if let Some(progress) = &self.progress_billing {
+            for amount in [progress.contract_total, progress.previously_billed, progress.this_invoice] {
+                canonical.amount(Some(amount));
+            }
+            canonical.text(&progress.percent_complete);
+        }
+        canonical.count(self.z_report.is_some() as usize);
+        if let Some(z_report) = &self.z_report {
+            canonical.count(z_report.tenders.len());
+            for tender in &z_report.tenders {
+                canonical.text(&tender.name);
+                canonical.amount(Some(tender.expected));
+                canonical.amount(Some(tender.counted));
+            }
+            canonical.count(z_report.transaction_counts.len());
+            for count in &z_report.transaction_counts {
+                canonical.text(&count.name);
+                canonical.text(&count.value);
+            }
+            canonical.count(z_report.clerks.len());
+            for clerk in &z_report.clerks {
+                canonical.text(&clerk.clerk);
+                canonical.text(&clerk.transactions);
+                canonical.amount(Some(clerk.sales));
+            }
+        }
+        canonical.count(self.attachments.len());
+        for attachment in &self.attachments {
+            canonical.text(&attachment.caption);
+            canonical.bytes(&attachment.image);
+        }
+        canonical.count(self.draft as usize);
+        canonical.count(self.revision as usize);
+        canonical.text(&self.supersedes);
+        canonical.amount(self.change_due);
+        canonical.count(self.schedule.is_some() as usize);
+        if let Some(schedule) = &self.schedule {
+            for field in [&schedule.requested_date, &schedule.window, &schedule.truck, &schedule.driver] {
+                canonical.text(field);
+            }
+        }
+        canonical.count(self.delivery_site.is_some() as usize);
+        if let Some(site) = &self.delivery_site {
+            canonical.text(&site.address);
+            canonical.count(site.coordinates.is_some() as usize);
+            if let Some((latitude, longitude)) = site.coordinates {
+                canonical.number(latitude);
+                canonical.number(longitude);
+            }
+        }
+        canonical.count(self.related_documents.len());
+        for related in &self.related_documents {
+            canonical.text(related.doc_type.slug());
+            canonical.text(&related.doc_number);
+        }
+        canonical.text(self.qr_payload.as_deref().unwrap_or(""));
+        canonical.count(self.currency.is_some() as usize);
+        if let Some(currency) = &self.currency {
+            canonical.text(&currency.symbol);
+            canonical.text(&currency.code);
+            canonical.count(match currency.symbol_position {
+                SymbolPosition::Before => 0,
+                SymbolPosition::After => 1,
+            });
+            canonical.count(currency.thousands_separator.is_some() as usize);
+            if let Some(separator) = currency.thousands_separator {
+                canonical.character(separator);
+            }
+            canonical.character(currency.decimal_separator);
+        }
+        canonical.count(self.fiscal.is_some() as usize);
+        if let Some(fiscal) = &self.fiscal {
+            for field in [&fiscal.fiscal_number, &fiscal.signature, &fiscal.qr_payload] {
+                canonical.text(field);
+            }
+            canonical.count(fiscal.lines.len());
+            for line in &fiscal.lines {
+                canonical.text(line);
+            }
+        }
+
+        let digest = canonical.hasher.finalize();
+        return digest.iter().map(|byte| format!("{byte:02x}")).collect();
+    }
+
+    // The checksum as printed on the document
+    pub fn short_checksum(&self) -> String {
+        let mut checksum = self.checksum();
+        checksum.truncate(PRINTED_LENGTH);
+        return checksum;
+    }
+
+    // Whether a checksum read off a document, in full or as printed, matches
+    // this record. Run pre_pass on the record first, as the document was.
+    pub fn verify_checksum(&self, checksum: &str) -> bool {
+        let checksum = checksum.trim().to_ascii_lowercase();
+        return checksum.len() >= PRINTED_LENGTH && self.checksum().starts_with(&checksum);
+    }
+}
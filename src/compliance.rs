@@ -0,0 +1,91 @@
+use std::fmt;
+use std::sync::Mutex;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use anyhow::{Error, Result, anyhow};
+use crate::{DocType, ReceiptInfo};
+
+// Hooks for jurisdictions where receipts have to carry elements from a fiscal
+// device or a certified signing scheme: a sequential fiscal number, the
+// signature string and a QR code the tax authority's app can check. pre_pass
+// runs the hooks last, once the totals are final, and the document prints
+// what they put in ReceiptInfo::fiscal at the bottom of the last page.
+
+// What a hook adds to the document
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FiscalInfo {
+    pub fiscal_number: String,
+    pub signature: String,
+    // Encoded in the QR code, none is printed when empty
+    pub qr_payload: String,
+    // Anything else the jurisdiction requires, e.g. the device serial number
+    pub lines: Vec<String>,
+}
+
+pub trait ComplianceHook: fmt::Debug + Send + Sync {
+    // May change anything on the document, not only the fiscal info
+    fn apply(&self, receipt: &mut ReceiptInfo) -> Result<(), Error>;
+}
+
+// Example hook for schemes where each receipt is signed together with the
+// signature of the one before it, so a missing or altered receipt breaks the
+// chain. Only final invoices and receipts are numbered. The caller persists
+// state() after every document and restores it with new().
+pub struct ChainedSignature {
+    prefix: String,
+    key: Vec<u8>,
+    state: Mutex<(u64, String)>,
+}
+
+impl fmt::Debug for ChainedSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return f.debug_struct("ChainedSignature")
+            .field("prefix", &self.prefix)
+            .field("key", &"<redacted>")
+            .field("state", &self.state())
+            .finish();
+    }
+}
+
+impl ChainedSignature {
+    // `previous_signature` is empty for the first receipt
+    pub fn new(prefix: &str, key: &[u8], next_number: u64, previous_signature: &str) -> Self {
+        return Self {
+            prefix: prefix.to_owned(),
+            key: key.to_vec(),
+            state: Mutex::new((next_number, previous_signature.to_owned())),
+        };
+    }
+
+    // The next fiscal number and the last signature
+    pub fn state(&self) -> (u64, String) {
+        return self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone();
+    }
+}
+
+impl ComplianceHook for ChainedSignature {
+    fn apply(&self, receipt: &mut ReceiptInfo) -> Result<(), Error> {
+        if receipt.draft || !matches!(receipt.doc_type, DocType::Invoice | DocType::Receipt) {
+            return Ok(());
+        }
+        let total = receipt.total().map(|total| total.to_string()).unwrap_or_default();
+        // Held across the signing so two documents can't get the same number
+        let mut state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let fiscal_number = format!("{}{:08}", self.prefix, state.0);
+        let mut mac = match Hmac::<Sha256>::new_from_slice(&self.key) {
+            Ok(mac) => mac,
+            Err(e) => return Err(anyhow!(format!("Invalid fiscal signing key. Reason: `{e}`"))),
+        };
+        mac.update(format!("{};{fiscal_number};{};{};{total}", state.1, receipt.date, receipt.doc_number).as_bytes());
+        let signature: String = mac.finalize().into_bytes().iter().map(|byte| format!("{byte:02x}")).collect();
+        receipt.fiscal = Some(FiscalInfo {
+            qr_payload: format!("{fiscal_number};{};{total};{signature}", receipt.date),
+            fiscal_number,
+            signature: signature.clone(),
+            lines: Vec::new(),
+        });
+        *state = (state.0 + 1, signature);
+        return Ok(());
+    }
+}
@@ -0,0 +1,103 @@
+// Minimal calendar date handling. The POS only gives us dates as text, either
+// "MM/DD/YYYY" or "YYYY-MM-DD", optionally followed by a time which is ignored
+// except by parse_minutes.
+
+// Days since 1970-01-01 for the date at the start of `text`
+pub(crate) fn parse_days(text: &str) -> Option<i64> {
+    let (year, month, day) = parse_ymd(text)?;
+    return Some(days_from_civil(year, month, day));
+}
+
+// Minutes since 1970-01-01 00:00 for a date and time, e.g. "2026-03-02 08:30",
+// "03/02/2026 8:30 AM" or "2026-03-02T08:30:00". A date alone is midnight.
+pub(crate) fn parse_minutes(text: &str) -> Option<i64> {
+    let days = parse_days(text)?;
+    let text = text.trim();
+    let time = match text.split_once(|c: char| c == 'T' || c.is_whitespace()) {
+        Some((_, time)) => time.trim().to_uppercase(),
+        None => return Some(days * 1440),
+    };
+    let (time, offset) = match (time.strip_suffix("AM"), time.strip_suffix("PM")) {
+        (Some(time), _) => (time.trim().to_owned(), Some(0)),
+        (_, Some(time)) => (time.trim().to_owned(), Some(12)),
+        _ => (time, None),
+    };
+    let mut parts = time.split(':');
+    let hour: i64 = parts.next()?.parse().ok()?;
+    let minute: i64 = parts.next().unwrap_or("0").parse().ok()?;
+    let hour = match offset {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some(offset) => hour % 12 + offset,
+        None => hour,
+    };
+    if !(0..24).contains(&hour) || !(0..60).contains(&minute) {
+        return None;
+    }
+    return Some(days * 1440 + hour * 60 + minute);
+}
+
+// Year and month of the date at the start of `text`
+pub(crate) fn parse_year_month(text: &str) -> Option<(i64, i64)> {
+    let (year, month, _) = parse_ymd(text)?;
+    return Some((year, month));
+}
+
+// The date at the start of `text` as YYYY-MM-DD
+pub(crate) fn to_iso(text: &str) -> Option<String> {
+    let (year, month, day) = parse_ymd(text)?;
+    return Some(format!("{year:04}-{month:02}-{day:02}"));
+}
+
+// The date at the start of `text` as MM/DD/YYYY
+pub(crate) fn to_us(text: &str) -> Option<String> {
+    let (year, month, day) = parse_ymd(text)?;
+    return Some(format!("{month:02}/{day:02}/{year:04}"));
+}
+
+fn parse_ymd(text: &str) -> Option<(i64, i64, i64)> {
+    let date = text.split_whitespace().next()?.split('T').next()?;
+    let (year, month, day) = if let Some((year, rest)) = date.split_once('-') {
+        let (month, day) = rest.split_once('-')?;
+        (year.parse().ok()?, month.parse().ok()?, day.parse().ok()?)
+    } else {
+        let mut parts = date.split('/');
+        let month = parts.next()?.parse().ok()?;
+        let day = parts.next()?.parse().ok()?;
+        let year = parts.next()?.parse().ok()?;
+        (year, month, day)
+    };
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    return Some((year, month, day));
+}
+
+// YYYY-MM-DD for a number of days since 1970-01-01
+pub(crate) fn iso_from_days(days: i64) -> String {
+    let (year, month, day) = civil_from_days(days);
+    return format!("{year:04}-{month:02}-{day:02}");
+}
+
+// Howard Hinnant's civil_from_days
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = if days >= 0 { days } else { days - 146096 } / 146097;
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    return (year, month, day);
+}
+
+// Howard Hinnant's days_from_civil
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    return era * 146097 + day_of_era - 719468;
+}
@@ -0,0 +1,59 @@
+use anyhow::{Error, Result};
+use crate::{money, ItemLine, Money, ReceiptInfo, Rounding};
+
+// Container deposits (bottle and can deposits) under the provincial deposit
+// rules: every unit of a configured item carries a refundable deposit, which
+// is billed as its own line and isn't taxed.
+
+#[derive(Debug, Clone)]
+pub struct ContainerDeposit {
+    // Code of the item which carries the deposit
+    pub item_code: String,
+    // Code and description of the deposit line, e.g. ("DEP10", "Container deposit")
+    pub deposit_code: String,
+    pub description: String,
+    // Per unit of the item
+    pub amount: f64,
+}
+
+impl ReceiptInfo {
+    // Adds the deposit line after every item which carries one, unless the
+    // POS already sent it
+    pub(crate) fn add_container_deposits(&mut self, deposits: &[ContainerDeposit], rounding: &Rounding) -> Result<(), Error> {
+        if deposits.is_empty() {
+            return Ok(());
+        }
+        let mut added = Money::ZERO;
+        let mut i = 0;
+        while i < self.item_lines.len() {
+            let line = &self.item_lines[i];
+            i += 1;
+            let deposit = match deposits.iter().find(|deposit| deposit.item_code.eq(&line.code)) {
+                Some(deposit) => deposit,
+                None => continue,
+            };
+            let already_billed = self.item_lines.get(i).is_some_and(|next| next.code.eq(&deposit.deposit_code));
+            if already_billed || line.quantity.is_empty() {
+                continue;
+            }
+            let amount = Money::from_f64(deposit.amount * money::parse_amount(&line.quantity)?, rounding);
+            let deposit_line = ItemLine {
+                code: deposit.deposit_code.clone(),
+                description: deposit.description.clone(),
+                quantity: line.quantity.clone(),
+                unit_price: money::format_unit_price(deposit.amount),
+                amount: Some(amount),
+                uom: line.uom.clone(),
+                taxable: false,
+                ..ItemLine::default()
+            };
+            self.item_lines.insert(i, deposit_line);
+            added = added.checked_add(amount)?;
+            i += 1;
+        }
+        if added != Money::ZERO {
+            self.add_to_totals(added, true)?;
+        }
+        return Ok(());
+    }
+}
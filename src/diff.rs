@@ -0,0 +1,191 @@
+use crate::{money, Amount, ItemLine, Money, ReceiptInfo};
+
+// What changed between two versions of a document, e.g. when a corrected
+// invoice replaces an earlier one. Item lines are matched by their code, or
+// their description when they have no code, in order, so a line without a
+// code whose description was edited shows as removed and added. Unit prices
+// are compared by value, so "12.5" and "12.50" are the same whether or not
+// pre_pass ran on both versions.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    // A header field, e.g. "Date" or "Sold to"
+    Field { name: &'static str, before: String, after: String },
+    LineAdded { code: String, description: String, amount: Option<Money> },
+    LineRemoved { code: String, description: String, amount: Option<Money> },
+    // One field of a line present in both versions
+    LineChanged { code: String, description: String, field: &'static str, before: String, after: String },
+    // A total or tender, None where it's missing from one of the versions
+    Total { name: String, before: Option<Money>, after: Option<Money> },
+    Payment { name: String, before: Option<Money>, after: Option<Money> },
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DocumentDiff {
+    pub changes: Vec<Change>,
+}
+
+impl DocumentDiff {
+    pub fn is_empty(&self) -> bool {
+        return self.changes.is_empty();
+    }
+
+    // One Markdown bullet per change, for the changes page
+    pub(crate) fn to_markdown(&self) -> String {
+        let mut text = String::new();
+        for change in &self.changes {
+            text.push_str("- ");
+            text.push_str(&change.describe());
+            text.push('\n');
+        }
+        return text;
+    }
+}
+
+impl Change {
+    pub fn describe(&self) -> String {
+        let either = |value: &Option<Money>| value.map_or_else(|| String::from("none"), |value| value.to_string());
+        return match self {
+            Change::Field { name, before, after } => format!("{name} changed from \"{before}\" to \"{after}\""),
+            Change::LineAdded { code, description, amount: Some(amount) } => format!("Added {} for {amount}", line_name(code, description)),
+            Change::LineAdded { code, description, amount: None } => format!("Added {}", line_name(code, description)),
+            Change::LineRemoved { code, description, amount: Some(amount) } => format!("Removed {} ({amount})", line_name(code, description)),
+            Change::LineRemoved { code, description, amount: None } => format!("Removed {}", line_name(code, description)),
+            Change::LineChanged { code, description, field, before, after } => {
+                format!("{}: {field} changed from {before} to {after}", line_name(code, description))
+            },
+            Change::Total { name, before, after } => {
+                format!("{} changed from {} to {}", name.trim_end_matches(':'), either(before), either(after))
+            },
+            Change::Payment { name, before, after } => {
+                format!("Payment {name} changed from {} to {}", either(before), either(after))
+            },
+        };
+    }
+}
+
+fn line_name(code: &str, description: &str) -> String {
+    if code.is_empty() {
+        return description.to_owned();
+    }
+    return format!("{code} {description}");
+}
+
+fn line_key(line: &ItemLine) -> &str {
+    return if line.code.is_empty() { &line.description } else { &line.code };
+}
+
+pub fn diff(before: &ReceiptInfo, after: &ReceiptInfo) -> DocumentDiff {
+    let mut changes = Vec::new();
+    let fields = [
+        ("Title", &before.title, &after.title),
+        ("Date", &before.date, &after.date),
+        ("Sold to", &before.customer_info, &after.customer_info),
+        ("Transaction number", &before.transaction_number, &after.transaction_number),
+        ("Order ID", &before.order_id, &after.order_id),
+        ("VAT number", &before.vat_number, &after.vat_number),
+        ("Document number", &before.doc_number, &after.doc_number),
+        ("Delivery tickets", &before.delivery_tickets, &after.delivery_tickets),
+        ("Weigh tickets", &before.weigh_tickets, &after.weigh_tickets),
+        ("Clerk", &before.employee, &after.employee),
+        ("Notes", &before.notes, &after.notes),
+    ];
+    for (name, old, new) in fields {
+        if old != new {
+            changes.push(Change::Field { name, before: old.clone(), after: new.clone() });
+        }
+    }
+    if before.amount_due != after.amount_due {
+        changes.push(Change::Field { name: "Amount due", before: text(before.amount_due), after: text(after.amount_due) });
+    }
+
+    let mut matched = vec![false; before.item_lines.len()];
+    for new in &after.item_lines {
+        let old = (0..before.item_lines.len())
+            .find(|i| !matched[*i] && line_key(&before.item_lines[*i]) == line_key(new));
+        let old = match old {
+            Some(i) => {
+                matched[i] = true;
+                &before.item_lines[i]
+            },
+            None => {
+                changes.push(Change::LineAdded {
+                    code: new.code.clone(),
+                    description: new.description.clone(),
+                    amount: new.amount,
+                });
+                continue;
+            },
+        };
+        let discount = |line: &ItemLine| line.discount.as_ref().map(ToString::to_string).unwrap_or_default();
+        let fields = [
+            ("description", old.description == new.description, old.description.clone(), new.description.clone()),
+            ("quantity", old.quantity == new.quantity, old.quantity.clone(), new.quantity.clone()),
+            ("U/M", old.uom == new.uom, old.uom.clone(), new.uom.clone()),
+            (
+                "unit price",
+                money::normalize_unit_price(&old.unit_price) == money::normalize_unit_price(&new.unit_price),
+                old.unit_price.clone(),
+                new.unit_price.clone(),
+            ),
+            ("discount", old.discount == new.discount, discount(old), discount(new)),
+            ("amount", old.amount == new.amount, text(old.amount), text(new.amount)),
+            ("taxable", old.taxable == new.taxable, yes_no(old.taxable), yes_no(new.taxable)),
+        ];
+        for (field, same, old_value, new_value) in fields {
+            if !same {
+                changes.push(Change::LineChanged {
+                    code: new.code.clone(),
+                    description: new.description.clone(),
+                    field,
+                    before: old_value,
+                    after: new_value,
+                });
+            }
+        }
+    }
+    for (i, old) in before.item_lines.iter().enumerate() {
+        if !matched[i] {
+            changes.push(Change::LineRemoved {
+                code: old.code.clone(),
+                description: old.description.clone(),
+                amount: old.amount,
+            });
+        }
+    }
+
+    for (name, old, new) in compare_amounts(&before.totals, &after.totals) {
+        changes.push(Change::Total { name, before: old, after: new });
+    }
+    for (name, old, new) in compare_amounts(&before.payments, &after.payments) {
+        changes.push(Change::Payment { name, before: old, after: new });
+    }
+    return DocumentDiff { changes };
+}
+
+// Blank when there's no amount
+fn text(amount: Option<Money>) -> String {
+    return amount.map(|amount| amount.to_string()).unwrap_or_default();
+}
+
+fn yes_no(value: bool) -> String {
+    return String::from(if value { "yes" } else { "no" });
+}
+
+// Amounts which differ by name, in the order of the new version and then
+// the ones which were removed
+fn compare_amounts(before: &[Amount], after: &[Amount]) -> Vec<(String, Option<Money>, Option<Money>)> {
+    let mut differences = Vec::new();
+    for new in after.iter().filter(|amount| !amount.name.is_empty()) {
+        let old = before.iter().find(|old| old.name == new.name);
+        if old.is_none_or(|old| old.value != new.value) {
+            differences.push((new.name.clone(), old.map(|old| old.value), Some(new.value)));
+        }
+    }
+    for old in before.iter().filter(|amount| !amount.name.is_empty()) {
+        if !after.iter().any(|new| new.name == old.name) {
+            differences.push((old.name.clone(), Some(old.value), None));
+        }
+    }
+    return differences;
+}
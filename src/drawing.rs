@@ -0,0 +1,184 @@
+use printpdf::{Mm, PdfLayerReference, Point, Line, Pt, LineDashPattern};
+use crate::theme::{BoxStyle, Edges, LineStyle, ThemeColor};
+
+// Distance of the bezier control points from a corner which best
+// approximates a quarter circle
+const KAPPA: f64 = 0.5523;
+// In points
+const DASH_LENGTH: i64 = 3;
+const DASH_GAP: i64 = 2;
+
+pub(crate) trait QuickShapes {
+    // fn add_closed_shape<T>(&mut self, points: Vec<T>);
+    fn add_box(&self, x1: Mm, y1: Mm, x2: Mm, y2: Mm);
+    fn add_line(&self, x1: Mm, y1: Mm, x2: Mm, y2: Mm);
+    // Filled rectangle without an outline. Draw it before anything which
+    // should appear on top of it.
+    fn add_filled_box(&self, x1: Mm, y1: Mm, x2: Mm, y2: Mm, color: ThemeColor);
+    // printpdf has no text decorations, so these draw a line relative to the
+    // baseline of text at (x, y) spanning `width`
+    fn add_underline(&self, x: Mm, y: Mm, width: Mm, font_size: f64);
+    fn add_strikethrough(&self, x: Mm, y: Mm, width: Mm, font_size: f64);
+    fn add_rounded_box(&self, x1: Mm, y1: Mm, x2: Mm, y2: Mm, radius: Mm);
+    fn add_dashed_line(&self, x1: Mm, y1: Mm, x2: Mm, y2: Mm);
+    fn add_styled_line(&self, x1: Mm, y1: Mm, x2: Mm, y2: Mm, style: LineStyle);
+    // Draws only the selected sides of a box
+    fn add_edges(&self, x1: Mm, y1: Mm, x2: Mm, y2: Mm, edges: Edges, style: LineStyle);
+    // Box outline following the theme: rounded when every edge is drawn and a
+    // radius is set, otherwise the selected edges as straight lines
+    fn add_styled_box(&self, x1: Mm, y1: Mm, x2: Mm, y2: Mm, style: &BoxStyle);
+}
+
+impl QuickShapes for PdfLayerReference {
+    fn add_box(&self, x1: Mm, y1: Mm, x2: Mm, y2: Mm) {
+        self.add_shape(Line {
+            points: vec![
+                (Point::new(x1, y1), false),
+                (Point::new(x2, y1), false),
+                (Point::new(x2, y2), false),
+                (Point::new(x1, y2), false),
+            ],
+            is_closed: true,
+            has_fill: false,
+            has_stroke: true,
+            is_clipping_path: false,
+        });
+    }
+
+    fn add_line(&self, x1: Mm, y1: Mm, x2: Mm, y2: Mm) {
+        self.add_shape(Line {
+            points: vec![
+                (Point::new(x1, y1), false),
+                (Point::new(x2, y2), false),
+            ],
+            is_closed: true,
+            has_fill: false,
+            has_stroke: true,
+            is_clipping_path: false,
+        });
+    }
+
+    fn add_filled_box(&self, x1: Mm, y1: Mm, x2: Mm, y2: Mm, color: ThemeColor) {
+        self.save_graphics_state();
+        self.set_fill_color(color.to_pdf());
+        self.add_shape(Line {
+            points: vec![
+                (Point::new(x1, y1), false),
+                (Point::new(x2, y1), false),
+                (Point::new(x2, y2), false),
+                (Point::new(x1, y2), false),
+            ],
+            is_closed: true,
+            has_fill: true,
+            has_stroke: false,
+            is_clipping_path: false,
+        });
+        self.restore_graphics_state();
+    }
+
+    fn add_underline(&self, x: Mm, y: Mm, width: Mm, font_size: f64) {
+        let y = y - Pt(font_size * 0.12).into();
+        add_decoration_line(self, x, y, width, font_size);
+    }
+
+    fn add_strikethrough(&self, x: Mm, y: Mm, width: Mm, font_size: f64) {
+        // Roughly half the x-height of Noto Sans
+        let y = y + Pt(font_size * 0.27).into();
+        add_decoration_line(self, x, y, width, font_size);
+    }
+
+    fn add_rounded_box(&self, x1: Mm, y1: Mm, x2: Mm, y2: Mm, radius: Mm) {
+        let (left, right) = (x1.0.min(x2.0), x1.0.max(x2.0));
+        let (bottom, top) = (y1.0.min(y2.0), y1.0.max(y2.0));
+        let r = radius.0.min((right - left) / 2.0).min((top - bottom) / 2.0);
+        let k = r * (1.0 - KAPPA);
+        let point = |x: f64, y: f64, is_control: bool| (Point::new(Mm(x), Mm(y)), is_control);
+        // Each corner is a cubic curve: two control points followed by the end point
+        self.add_shape(Line {
+            points: vec![
+                point(left + r, bottom, false),
+                point(right - r, bottom, false),
+                point(right - k, bottom, true),
+                point(right, bottom + k, true),
+                point(right, bottom + r, false),
+                point(right, top - r, false),
+                point(right, top - k, true),
+                point(right - k, top, true),
+                point(right - r, top, false),
+                point(left + r, top, false),
+                point(left + k, top, true),
+                point(left, top - k, true),
+                point(left, top - r, false),
+                point(left, bottom + r, false),
+                point(left, bottom + k, true),
+                point(left + k, bottom, true),
+                point(left + r, bottom, false),
+            ],
+            is_closed: true,
+            has_fill: false,
+            has_stroke: true,
+            is_clipping_path: false,
+        });
+    }
+
+    fn add_dashed_line(&self, x1: Mm, y1: Mm, x2: Mm, y2: Mm) {
+        self.save_graphics_state();
+        self.set_line_dash_pattern(dash_pattern());
+        self.add_line(x1, y1, x2, y2);
+        self.restore_graphics_state();
+    }
+
+    fn add_styled_line(&self, x1: Mm, y1: Mm, x2: Mm, y2: Mm, style: LineStyle) {
+        match style {
+            LineStyle::Solid => self.add_line(x1, y1, x2, y2),
+            LineStyle::Dashed => self.add_dashed_line(x1, y1, x2, y2),
+        }
+    }
+
+    fn add_edges(&self, x1: Mm, y1: Mm, x2: Mm, y2: Mm, edges: Edges, style: LineStyle) {
+        if edges.all() && style == LineStyle::Solid {
+            self.add_box(x1, y1, x2, y2);
+            return;
+        }
+        if edges.bottom {
+            self.add_styled_line(x1, y1, x2, y1, style);
+        }
+        if edges.right {
+            self.add_styled_line(x2, y1, x2, y2, style);
+        }
+        if edges.top {
+            self.add_styled_line(x1, y2, x2, y2, style);
+        }
+        if edges.left {
+            self.add_styled_line(x1, y1, x1, y2, style);
+        }
+    }
+
+    fn add_styled_box(&self, x1: Mm, y1: Mm, x2: Mm, y2: Mm, style: &BoxStyle) {
+        if style.corner_radius > 0.0 && style.edges.all() {
+            self.save_graphics_state();
+            if style.line == LineStyle::Dashed {
+                self.set_line_dash_pattern(dash_pattern());
+            }
+            self.add_rounded_box(x1, y1, x2, y2, Pt(style.corner_radius).into());
+            self.restore_graphics_state();
+            return;
+        }
+        self.add_edges(x1, y1, x2, y2, style.edges, style.line);
+    }
+}
+
+fn dash_pattern() -> LineDashPattern {
+    return LineDashPattern {
+        dash_1: Some(DASH_LENGTH),
+        gap_1: Some(DASH_GAP),
+        ..Default::default()
+    };
+}
+
+fn add_decoration_line(layer: &PdfLayerReference, x: Mm, y: Mm, width: Mm, font_size: f64) {
+    layer.save_graphics_state();
+    layer.set_outline_thickness(font_size * 0.06);
+    layer.add_line(x, y, x + width, y);
+    layer.restore_graphics_state();
+}
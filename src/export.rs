@@ -0,0 +1,323 @@
+use std::collections::BTreeMap;
+use anyhow::{Error, Result, anyhow};
+use crate::{date, is_cash, is_subtotal, totals, DocType, Money, ReceiptInfo};
+
+// Accounting exports: the document as double-entry postings, written either
+// as a QuickBooks Desktop IIF import or as a generic journal CSV.
+
+// General ledger accounts the postings are made to. The defaults are the
+// standard QuickBooks account names.
+#[derive(Debug, Clone)]
+pub struct GlAccounts {
+    pub accounts_receivable: String,
+    // Income account for the item lines
+    pub sales: String,
+    pub sales_tax_payable: String,
+    // Income account for the rows of the totals that are neither a tax nor
+    // a subtotal, e.g. surcharges the POS billed or pre_pass added, and
+    // delivery fees
+    pub surcharges: String,
+    // Where tenders go unless overridden in `tenders`
+    pub undeposited_funds: String,
+    // Tender name to account, e.g. ("Visa", "Visa Clearing")
+    pub tenders: Vec<(String, String)>,
+    // Tax total name to account, e.g. ("PST", "PST Payable"). Totals listed
+    // here are always treated as tax.
+    pub taxes: Vec<(String, String)>,
+}
+
+impl Default for GlAccounts {
+    fn default() -> Self {
+        return Self {
+            accounts_receivable: String::from("Accounts Receivable"),
+            sales: String::from("Sales"),
+            sales_tax_payable: String::from("Sales Tax Payable"),
+            surcharges: String::from("Other Income"),
+            undeposited_funds: String::from("Undeposited Funds"),
+            tenders: Vec::new(),
+            taxes: Vec::new(),
+        };
+    }
+}
+
+// Words in a total's name which mark it as a tax
+const TAX_WORDS: [&str; 6] = ["TAX", "GST", "HST", "PST", "QST", "VAT"];
+
+impl GlAccounts {
+    fn tender_account(&self, name: &str) -> &str {
+        return lookup(&self.tenders, name).unwrap_or(&self.undeposited_funds);
+    }
+
+    fn tax_account(&self, name: &str) -> Option<&str> {
+        if let Some(account) = lookup(&self.taxes, name) {
+            return Some(account);
+        }
+        return if is_tax_total(name) { Some(&self.sales_tax_payable) } else { None };
+    }
+}
+
+// Whether a total from the POS is a tax, judging by its name
+pub(crate) fn is_tax_total(name: &str) -> bool {
+    let name = name.trim_end_matches(':').to_uppercase();
+    if name.starts_with("TOTAL") || name.starts_with("SUBTOTAL") || name.starts_with("SUB TOTAL") {
+        return false;
+    }
+    return name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .any(|word| TAX_WORDS.contains(&word));
+}
+
+fn lookup<'a>(table: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    let name = name.trim().trim_end_matches(':');
+    return table
+        .iter()
+        .find(|(key, _)| key.trim().trim_end_matches(':').eq_ignore_ascii_case(name))
+        .map(|(_, account)| account.as_str());
+}
+
+struct Posting {
+    account: String,
+    // Debits are positive, credits negative
+    amount: Money,
+    memo: String,
+}
+
+struct Transaction {
+    // IIF transaction type
+    kind: &'static str,
+    postings: Vec<Posting>,
+}
+
+impl ReceiptInfo {
+    fn journal(&self, accounts: &GlAccounts) -> Result<Vec<Transaction>, Error> {
+        if self.draft {
+            return Err(anyhow!(format!("{} is a draft and doesn't post to the ledger", self.doc_number)));
+        }
+        let mut revenue = Vec::new();
+        for line in &self.item_lines {
+            let Some(amount) = line.amount else {
+                continue;
+            };
+            revenue.push(Posting {
+                account: accounts.sales.clone(),
+                amount: amount.checked_neg()?,
+                memo: line.description.clone(),
+            });
+        }
+        // The same rows the total is made of, see totals.rs. Discount rows
+        // are already taken off the line amounts.
+        for total in &self.totals {
+            if total.name.trim().is_empty() || is_subtotal(&total.name) || totals::is_discount(&total.name) || total.name.eq("Total:") {
+                continue;
+            }
+            let account = accounts.tax_account(&total.name).unwrap_or(&accounts.surcharges);
+            revenue.push(Posting {
+                account: account.to_owned(),
+                amount: total.value.checked_neg()?,
+                memo: total.name.trim_end_matches(':').to_owned(),
+            });
+        }
+        let mut tenders = Vec::new();
+        for tender in &self.payments {
+            tenders.push(Posting {
+                account: accounts.tender_account(&tender.name).to_owned(),
+                amount: tender.value.abs(),
+                memo: tender.name.clone(),
+            });
+        }
+        // Change comes out of the cash drawer, or the last tender
+        if let Some(change_due) = self.change_due {
+            let tender = self.payments
+                .iter()
+                .find(|tender| is_cash(&tender.name))
+                .or(self.payments.last());
+            if let Some(tender) = tender {
+                tenders.push(Posting {
+                    account: accounts.tender_account(&tender.name).to_owned(),
+                    amount: change_due.checked_neg()?,
+                    memo: String::from("Change"),
+                });
+            }
+        }
+        let revenue_total = Money::checked_sum(revenue.iter().map(|posting| posting.amount))?.checked_neg()?;
+
+        let mut transactions = Vec::new();
+        match self.doc_type {
+            DocType::Invoice => {
+                let mut postings = vec![Posting {
+                    account: accounts.accounts_receivable.clone(),
+                    amount: revenue_total,
+                    memo: String::new(),
+                }];
+                postings.extend(revenue);
+                transactions.push(Transaction { kind: "INVOICE", postings });
+                // Anything paid at the counter is a payment against the invoice
+                for tender in tenders {
+                    let amount = tender.amount;
+                    transactions.push(Transaction {
+                        kind: "PAYMENT",
+                        postings: vec![tender, Posting {
+                            account: accounts.accounts_receivable.clone(),
+                            amount: amount.checked_neg()?,
+                            memo: String::new(),
+                        }],
+                    });
+                }
+            },
+            DocType::Receipt => {
+                let tendered = Money::checked_sum(tenders.iter().map(|posting| posting.amount))?;
+                if tendered != revenue_total {
+                    return Err(anyhow!(format!(
+                        "Receipt {} doesn't balance: {tendered} tendered against {revenue_total} of sales and tax",
+                        self.doc_number
+                    )));
+                }
+                let mut postings = tenders;
+                postings.extend(revenue);
+                transactions.push(Transaction { kind: "CASH SALE", postings });
+            },
+            DocType::Quote | DocType::Statement | DocType::ZReport | DocType::DeliveryTicket => {
+                return Err(anyhow!(format!("{:?} {} doesn't post to the ledger", self.doc_type, self.doc_number)));
+            },
+        }
+        return Ok(transactions);
+    }
+
+    // QuickBooks Desktop import. Tenders on an invoice become separate
+    // payment transactions.
+    pub fn to_quickbooks_iif(&self, accounts: &GlAccounts) -> Result<String, Error> {
+        let date = match date::to_us(&self.date) {
+            Some(date) => date,
+            None => return Err(anyhow!(format!("Could not read the date `{}` of {}", self.date, self.doc_number))),
+        };
+        let customer = iif_field(self.customer_info.lines().next().unwrap_or(""));
+        let doc_number = iif_field(&self.doc_number);
+
+        let mut iif = String::new();
+        iif.push_str("!TRNS\tTRNSTYPE\tDATE\tACCNT\tNAME\tAMOUNT\tDOCNUM\tMEMO\n");
+        iif.push_str("!SPL\tTRNSTYPE\tDATE\tACCNT\tNAME\tAMOUNT\tDOCNUM\tMEMO\n");
+        iif.push_str("!ENDTRNS\n");
+        for transaction in self.journal(accounts)? {
+            for (i, posting) in transaction.postings.iter().enumerate() {
+                let row = if i == 0 { "TRNS" } else { "SPL" };
+                iif.push_str(&format!(
+                    "{row}\t{}\t{date}\t{}\t{customer}\t{}\t{doc_number}\t{}\n",
+                    transaction.kind, iif_field(&posting.account), posting.amount, iif_field(&posting.memo),
+                ));
+            }
+            iif.push_str("ENDTRNS\n");
+        }
+        return Ok(iif);
+    }
+
+    // One row per posting, for ledgers other than QuickBooks
+    pub fn to_journal_csv(&self, accounts: &GlAccounts) -> Result<String, Error> {
+        let date = date::to_iso(&self.date).unwrap_or_else(|| self.date.clone());
+        let mut csv = String::from("date,doc_number,doc_type,account,memo,debit,credit\n");
+        for transaction in self.journal(accounts)? {
+            for posting in &transaction.postings {
+                let (debit, credit) = if posting.amount.is_negative() {
+                    (String::new(), posting.amount.abs().to_string())
+                } else {
+                    (posting.amount.to_string(), String::new())
+                };
+                csv.push_str(&[
+                    csv_field(&date),
+                    csv_field(&self.doc_number),
+                    csv_field(transaction.kind),
+                    csv_field(&posting.account),
+                    csv_field(&posting.memo),
+                    debit,
+                    credit,
+                ].join(","));
+                csv.push('\n');
+            }
+        }
+        return Ok(csv);
+    }
+}
+
+#[derive(Default)]
+struct VatSums {
+    documents: usize,
+    taxable_sales: Money,
+    exempt_sales: Money,
+    // Tax name to amount collected
+    taxes: BTreeMap<String, Money>,
+}
+
+// Periodic VAT/GST return summary over the invoices and receipts dated from
+// `from` to `to` inclusive. There is one row per registration number and tax,
+// and the sales columns repeat on each row of a registration.
+pub fn vat_return_csv(documents: &[ReceiptInfo], from: &str, to: &str) -> Result<String, Error> {
+    let (first_day, last_day) = match (date::parse_days(from), date::parse_days(to)) {
+        (Some(first_day), Some(last_day)) => (first_day, last_day),
+        _ => return Err(anyhow!(format!("Could not read the return period `{from}` to `{to}`"))),
+    };
+    let mut registrations: BTreeMap<&str, VatSums> = BTreeMap::new();
+    for document in documents {
+        if !matches!(document.doc_type, DocType::Invoice | DocType::Receipt) {
+            continue;
+        }
+        let day = match date::parse_days(&document.date) {
+            Some(day) => day,
+            None => return Err(anyhow!(format!("Could not read the date `{}` of {}", document.date, document.doc_number))),
+        };
+        if day < first_day || day > last_day {
+            continue;
+        }
+        let sums = registrations.entry(document.vat_number.trim()).or_default();
+        sums.documents += 1;
+        for line in &document.item_lines {
+            let Some(amount) = line.amount else {
+                continue;
+            };
+            let sum = if line.taxable { &mut sums.taxable_sales } else { &mut sums.exempt_sales };
+            *sum = sum.checked_add(amount)?;
+        }
+        for total in &document.totals {
+            if is_tax_total(&total.name) {
+                let sum = sums.taxes.entry(total.name.trim_end_matches(':').to_owned()).or_default();
+                *sum = sum.checked_add(total.value)?;
+            }
+        }
+    }
+
+    let period_start = date::iso_from_days(first_day);
+    let period_end = date::iso_from_days(last_day);
+    let mut csv = String::from("vat_number,period_start,period_end,tax,taxable_sales,exempt_sales,tax_collected,documents\n");
+    for (vat_number, sums) in &registrations {
+        let mut row = |tax: &str, collected: Money| {
+            csv.push_str(&[
+                csv_field(vat_number),
+                period_start.clone(),
+                period_end.clone(),
+                csv_field(tax),
+                sums.taxable_sales.to_string(),
+                sums.exempt_sales.to_string(),
+                collected.to_string(),
+                sums.documents.to_string(),
+            ].join(","));
+            csv.push('\n');
+        };
+        if sums.taxes.is_empty() {
+            row("", Money::ZERO);
+        }
+        for (tax, collected) in &sums.taxes {
+            row(tax, *collected);
+        }
+    }
+    return Ok(csv);
+}
+
+// IIF is tab separated with no quoting
+fn iif_field(value: &str) -> String {
+    return value.replace(['\t', '\n', '\r'], " ");
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        return format!("\"{}\"", value.replace('"', "\"\""));
+    }
+    return value.to_owned();
+}
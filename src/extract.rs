@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use printpdf::lopdf::{Document, Object, ObjectId};
+use printpdf::lopdf::content::Content;
+use anyhow::{Error, Result, anyhow};
+use crate::stamp::inherited;
+use crate::{gen_pdf_bytes_with_options, GenOptions, PdfResources, ReceiptInfo};
+
+// Plain text of generated documents, for search indexing. printpdf writes
+// text with embedded fonts as glyph ids and maps them back to characters in
+// each font's ToUnicode CMap, so the text is read back through those. Text
+// is returned a line per baseline, top to bottom and left to right, which is
+// good enough for a search index but doesn't keep the columns of the table.
+
+// Renders and saves the document, and returns its text a String per page
+pub fn gen_pdf_bytes_with_text(receipt: &ReceiptInfo, resources: &PdfResources, options: &GenOptions) -> Result<(Vec<u8>, Vec<String>), Error> {
+    let bytes = gen_pdf_bytes_with_options(receipt, resources, options)?;
+    let text = extract_text(&bytes)?;
+    return Ok((bytes, text));
+}
+
+// Text drawn on one baseline at x
+struct Fragment {
+    x: f64,
+    y: f64,
+    text: String,
+}
+
+// Baselines closer than this are one line
+const LINE_TOLERANCE: f64 = 2.0;
+
+pub fn extract_text(pdf: &[u8]) -> Result<Vec<String>, Error> {
+    let doc = Document::load_mem(pdf)?;
+    let mut pages = Vec::new();
+    for (number, page_id) in doc.get_pages() {
+        let fonts = page_fonts(&doc, page_id);
+        let content = match doc.get_page_content(page_id).map(|content| Content::decode(&content)) {
+            Ok(Ok(content)) => content,
+            _ => return Err(anyhow!(format!("Could not read the content of page {number} of the document"))),
+        };
+        let mut fragments = Vec::new();
+        let (mut font, mut x, mut y) = (Vec::new(), 0.0, 0.0);
+        for operation in &content.operations {
+            let operands = &operation.operands;
+            match operation.operator.as_str() {
+                "BT" => (x, y) = (0.0, 0.0),
+                "Tf" => if let Some(Object::Name(name)) = operands.first() {
+                    font = name.clone();
+                },
+                "Td" | "TD" => if let (Some(dx), Some(dy)) = (operands.first().and_then(number_from), operands.get(1).and_then(number_from)) {
+                    (x, y) = (x + dx, y + dy);
+                },
+                "Tm" => if let (Some(e), Some(f)) = (operands.get(4).and_then(number_from), operands.get(5).and_then(number_from)) {
+                    (x, y) = (e, f);
+                },
+                "Tj" | "'" | "\"" | "TJ" => {
+                    let cmap = fonts.get(&font);
+                    let mut text = String::new();
+                    for operand in operands {
+                        match operand {
+                            Object::String(bytes, _) => text.push_str(&decode(bytes, cmap)),
+                            Object::Array(items) => for item in items {
+                                match item {
+                                    Object::String(bytes, _) => text.push_str(&decode(bytes, cmap)),
+                                    // A kern wide enough to be a space
+                                    other if number_from(other).is_some_and(|kern| kern < -200.0) => text.push(' '),
+                                    _ => {},
+                                }
+                            },
+                            _ => {},
+                        }
+                    }
+                    if !text.trim().is_empty() {
+                        fragments.push(Fragment { x, y, text });
+                    }
+                },
+                _ => {},
+            }
+        }
+        pages.push(join_lines(fragments));
+    }
+    return Ok(pages);
+}
+
+fn join_lines(mut fragments: Vec<Fragment>) -> String {
+    fragments.sort_by(|a, b| b.y.total_cmp(&a.y));
+    let mut lines: Vec<Vec<Fragment>> = Vec::new();
+    for fragment in fragments {
+        match lines.last_mut() {
+            Some(line) if (line[0].y - fragment.y).abs() < LINE_TOLERANCE => line.push(fragment),
+            _ => lines.push(vec![fragment]),
+        }
+    }
+    let lines: Vec<String> = lines.into_iter()
+        .map(|mut line| {
+            line.sort_by(|a, b| a.x.total_cmp(&b.x));
+            line.iter().map(|fragment| fragment.text.trim()).collect::<Vec<_>>().join(" ")
+        })
+        .collect();
+    return lines.join("\n");
+}
+
+fn number_from(object: &Object) -> Option<f64> {
+    return match object {
+        Object::Integer(value) => Some(*value as f64),
+        Object::Real(value) => Some(*value),
+        _ => None,
+    };
+}
+
+pub(crate) fn resolve<'a>(doc: &'a Document, object: &'a Object) -> Option<&'a Object> {
+    return match object {
+        Object::Reference(id) => doc.get_object(*id).ok(),
+        other => Some(other),
+    };
+}
+
+// The ToUnicode CMaps of the fonts of a page, by resource name. Fonts without
+// one (the built-in ones) are written in single byte text.
+fn page_fonts(doc: &Document, page_id: ObjectId) -> HashMap<Vec<u8>, HashMap<u32, String>> {
+    let mut fonts = HashMap::new();
+    let Some(resources) = inherited(doc, page_id, b"Resources") else { return fonts };
+    let Some(Ok(resources)) = resolve(doc, &resources).map(Object::as_dict) else { return fonts };
+    let Some(Ok(font_dict)) = resources.get(b"Font").ok().and_then(|fonts| resolve(doc, fonts)).map(Object::as_dict) else { return fonts };
+    for (name, font) in font_dict.iter() {
+        let Some(Ok(font)) = resolve(doc, font).map(Object::as_dict) else { continue };
+        let Some(Ok(cmap)) = font.get(b"ToUnicode").ok().and_then(|cmap| resolve(doc, cmap)).map(Object::as_stream) else { continue };
+        let content = cmap.decompressed_content().unwrap_or_else(|_| cmap.content.clone());
+        fonts.insert(name.clone(), parse_cmap(&String::from_utf8_lossy(&content)));
+    }
+    return fonts;
+}
+
+// The bfchar and bfrange sections of a CMap, e.g.
+//
+//     1 beginbfchar
+//     <0024> <0041>
+//     endbfchar
+//     1 beginbfrange
+//     <0030> <0039> <0030>
+//     endbfrange
+fn parse_cmap(text: &str) -> HashMap<u32, String> {
+    let mut map = HashMap::new();
+    let mut section = "";
+    for line in text.lines().map(str::trim) {
+        if line.ends_with("beginbfchar") || line.ends_with("beginbfrange") {
+            section = if line.ends_with("beginbfchar") { "bfchar" } else { "bfrange" };
+            continue;
+        }
+        if line.starts_with("end") {
+            section = "";
+            continue;
+        }
+        let codes: Vec<&str> = line.split(['<', '>'])
+            .map(str::trim)
+            .filter(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_hexdigit()))
+            .collect();
+        match (section, codes.as_slice()) {
+            ("bfchar", [code, unicode]) => if let Ok(code) = u32::from_str_radix(code, 16) {
+                map.insert(code, utf16_hex(unicode));
+            },
+            ("bfrange", [first, last, unicode]) => {
+                let (Ok(first), Ok(last)) = (u32::from_str_radix(first, 16), u32::from_str_radix(last, 16)) else { continue };
+                let Ok(start) = u32::from_str_radix(unicode, 16) else { continue };
+                for code in first..=last.min(first + 0xFFFF) {
+                    if let Some(c) = char::from_u32(start + code - first) {
+                        map.insert(code, c.to_string());
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+    return map;
+}
+
+fn utf16_hex(hex: &str) -> String {
+    let units: Vec<u16> = hex.as_bytes()
+        .chunks(4)
+        .filter_map(|chunk| u16::from_str_radix(std::str::from_utf8(chunk).ok()?, 16).ok())
+        .collect();
+    return String::from_utf16_lossy(&units);
+}
+
+// Two byte glyph ids with a CMap, Latin-1 without
+fn decode(bytes: &[u8], cmap: Option<&HashMap<u32, String>>) -> String {
+    let Some(cmap) = cmap else { return bytes.iter().map(|byte| *byte as char).collect() };
+    return bytes.chunks(2)
+        .filter_map(|pair| cmap.get(&pair.iter().fold(0u32, |code, byte| (code << 8) | *byte as u32)))
+        .map(String::as_str)
+        .collect();
+}
@@ -0,0 +1,301 @@
+use anyhow::{Error, Result, anyhow};
+use crate::money::{cleanup_amount, Money, Rounding};
+use crate::pdfa::{escape_xml, AssociatedFile};
+use crate::{date, is_subtotal, DocType, ReceiptInfo, TaxRate};
+
+// Factur-X (ZUGFeRD 2) e-invoices: the invoice as UN/CEFACT Cross Industry
+// Invoice XML in the EN 16931 profile, carried inside the PDF/A-3 document
+// so EU customers' accounts payable software can read it without keying it
+// in. The XML is made from the same figures as the printed invoice. The
+// document has no room for some things EN 16931 requires, like the
+// countries of the seller and buyer, so those come with the options.
+
+const FILE_NAME: &str = "factur-x.xml";
+const NAMESPACE: &str = "urn:factur-x:pdfa:CrossIndustryDocument:invoice:1p0#";
+
+#[derive(Debug, Clone)]
+pub struct EInvoice {
+    // ISO 3166-1 alpha-2, e.g. "FR"
+    pub seller_country: String,
+    pub buyer_country: String,
+    // ISO 4217, for documents whose currency has no code
+    pub currency: String,
+    // VAT on the taxable lines. Lines which aren't taxable are exempt.
+    pub tax_rate: TaxRate,
+    // Printed on exempt invoices, e.g. "Intra-community supply"
+    pub exemption_reason: String,
+    // Sent with line charges, the price over the unit price, e.g.
+    // "Handling". Line allowances are sent as discounts.
+    pub charge_reason: String,
+    // e.g. "Net 30". Needed when there's an amount due.
+    pub payment_terms: String,
+    // Unit of measure to UN/ECE Recommendation 20 code, e.g. ("BDL", "XBE"),
+    // before the built-in ones. Units neither knows are sent as C62 ("one").
+    pub unit_codes: Vec<(String, String)>,
+    pub rounding: Rounding,
+}
+
+const UNIT_CODES: [(&str, &str); 15] = [
+    ("EA", "C62"), ("PC", "C62"), ("LB", "LBR"), ("KG", "KGM"), ("G", "GRM"), ("FT", "FOT"), ("IN", "INH"), ("YD", "YRD"),
+    ("M", "MTR"), ("SQFT", "FTK"), ("M2", "MTK"), ("GAL", "GLL"), ("L", "LTR"), ("HR", "HUR"), ("DAY", "DAY"),
+];
+
+impl EInvoice {
+    fn unit_code(&self, uom: &str) -> String {
+        let uom = uom.trim();
+        if let Some((_, code)) = self.unit_codes.iter().find(|(name, _)| name.eq_ignore_ascii_case(uom)) {
+            return code.clone();
+        }
+        return UNIT_CODES.iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(uom))
+            .map_or("C62", |(_, code)| code)
+            .to_owned();
+    }
+}
+
+// The EN 16931 Cross Industry Invoice XML of an invoice
+pub fn facturx_xml(receipt: &ReceiptInfo, e_invoice: &EInvoice) -> Result<String, Error> {
+    if receipt.doc_type != DocType::Invoice {
+        return Err(anyhow!(format!("Document {} isn't an invoice, only invoices can be sent as Factur-X", receipt.doc_number)));
+    }
+    let rounding = &e_invoice.rounding;
+    let rate = &e_invoice.tax_rate;
+    let computed = receipt.compute_totals(std::slice::from_ref(rate), rounding)?;
+    // EN 16931 has one VAT rate per line and no other kinds of tax
+    for row in &receipt.totals {
+        let name = row.name.trim_end_matches(':').trim();
+        let checked = name.is_empty() || is_subtotal(&row.name) || row.name.eq("Total:") || name.to_uppercase().starts_with("DISCOUNT");
+        if !checked && !name.to_uppercase().starts_with(&rate.name.to_uppercase()) && row.value.cents() != 0 {
+            return Err(anyhow!(format!("The `{name}` total of document {} can't be sent as Factur-X, which only has the {} rate", receipt.doc_number, rate.name)));
+        }
+    }
+    // The tax as printed, which may be a cent off the computed one
+    let tax = match receipt.totals.iter().find(|row| row.name.trim_end_matches(':').trim().to_uppercase().starts_with(&rate.name.to_uppercase())) {
+        Some(row) => row.value,
+        None => Money::checked_sum(computed.taxes.iter().map(|(_, tax)| *tax))?,
+    };
+    let grand_total = computed.subtotal.checked_add(tax)?;
+    let due = match receipt.amount_due {
+        Some(amount_due) => amount_due,
+        None => grand_total.checked_sub(Money::checked_sum(receipt.payments.iter().map(|payment| payment.value))?)?,
+    };
+    let prepaid = grand_total.checked_sub(due)?;
+    // A refund is a credit note, which has the amounts of the invoice it
+    // credits, so its signs are turned around
+    let credit_note = grand_total.is_negative();
+    let sign = |amount: Money| if credit_note { amount.checked_neg() } else { Ok(amount) };
+
+    let issue_date = match date::to_iso(&receipt.date) {
+        Some(iso) => iso.replace('-', ""),
+        None => return Err(anyhow!(format!("Could not read the date `{}` of document {}", receipt.date, receipt.doc_number))),
+    };
+    let mut customer_lines = receipt.customer_info.lines().map(str::trim).filter(|line| !line.is_empty());
+    let Some(buyer) = customer_lines.next() else {
+        return Err(anyhow!(format!("Factur-X needs the buyer's name, document {} has no customer", receipt.doc_number)));
+    };
+    if receipt.company_name.trim().is_empty() {
+        return Err(anyhow!("Factur-X needs the seller's name, the company name is empty"));
+    }
+    let currency = match receipt.currency.as_ref().map(|currency| currency.code.trim()).filter(|code| !code.is_empty()) {
+        Some(code) => code.to_owned(),
+        None => e_invoice.currency.trim().to_owned(),
+    };
+    if currency.is_empty() {
+        return Err(anyhow!("Factur-X needs the currency code, set it in the currency format or the e-invoice options"));
+    }
+    if due.cents() > 0 && e_invoice.payment_terms.trim().is_empty() {
+        return Err(anyhow!(format!("Document {} has an amount due, Factur-X needs the payment terms for it", receipt.doc_number)));
+    }
+
+    let percent = format!("{}", rate.percent);
+    let (mut taxable, mut exempt) = (None, None);
+    let mut lines = String::new();
+    for (index, (line, amount)) in receipt.item_lines.iter().filter_map(|line| Some((line, line.amount?))).enumerate() {
+        let amount = sign(amount)?;
+        let quantity = match line.quantity.trim() {
+            "" => String::from("1"),
+            quantity if credit_note => negated(cleanup_amount(quantity)?),
+            quantity => cleanup_amount(quantity)?,
+        };
+        let unit_price = match line.unit_price.trim() {
+            "" => amount.to_string(),
+            unit_price => cleanup_amount(unit_price)?,
+        };
+        // Discounts are whatever takes the line below quantity times price
+        let gross = Money::from_f64(unit_price.parse::<f64>()? * quantity.parse::<f64>()?, rounding);
+        let allowance = gross.checked_sub(amount)?;
+        let (category, line_percent) = if line.taxable {
+            taxable = Some(taxable.unwrap_or(Money::ZERO).checked_add(amount)?);
+            ("S", percent.as_str())
+        } else {
+            exempt = Some(exempt.unwrap_or(Money::ZERO).checked_add(amount)?);
+            ("E", "0")
+        };
+        let name = if line.description.trim().is_empty() { &line.code } else { &line.description };
+        lines.push_str(&format!(
+            r#"<ram:IncludedSupplyChainTradeLineItem>
+<ram:AssociatedDocumentLineDocument><ram:LineID>{line_id}</ram:LineID></ram:AssociatedDocumentLineDocument>
+<ram:SpecifiedTradeProduct>{seller_id}<ram:Name>{name}</ram:Name></ram:SpecifiedTradeProduct>
+<ram:SpecifiedLineTradeAgreement><ram:NetPriceProductTradePrice><ram:ChargeAmount>{unit_price}</ram:ChargeAmount></ram:NetPriceProductTradePrice></ram:SpecifiedLineTradeAgreement>
+<ram:SpecifiedLineTradeDelivery><ram:BilledQuantity unitCode="{unit_code}">{quantity}</ram:BilledQuantity></ram:SpecifiedLineTradeDelivery>
+<ram:SpecifiedLineTradeSettlement>
+<ram:ApplicableTradeTax><ram:TypeCode>VAT</ram:TypeCode><ram:CategoryCode>{category}</ram:CategoryCode><ram:RateApplicablePercent>{line_percent}</ram:RateApplicablePercent></ram:ApplicableTradeTax>
+{allowance}<ram:SpecifiedTradeSettlementLineMonetarySummation><ram:LineTotalAmount>{amount}</ram:LineTotalAmount></ram:SpecifiedTradeSettlementLineMonetarySummation>
+</ram:SpecifiedLineTradeSettlement>
+</ram:IncludedSupplyChainTradeLineItem>
+"#,
+            line_id = index + 1,
+            seller_id = if line.code.trim().is_empty() { String::new() } else { format!("<ram:SellerAssignedID>{}</ram:SellerAssignedID>", escape_xml(&line.code)) },
+            name = escape_xml(name),
+            unit_code = escape_xml(&e_invoice.unit_code(&line.uom)),
+            allowance = match allowance.cents() {
+                0 => String::new(),
+                // Reason code 95 of UNTDID 5189 is a discount
+                _ if allowance.is_negative() => format!(
+                    "<ram:SpecifiedTradeAllowanceCharge><ram:ChargeIndicator><udt:Indicator>true</udt:Indicator></ram:ChargeIndicator><ram:ActualAmount>{}</ram:ActualAmount><ram:Reason>{}</ram:Reason></ram:SpecifiedTradeAllowanceCharge>\n",
+                    allowance.abs(),
+                    escape_xml(if e_invoice.charge_reason.trim().is_empty() { "Charge" } else { &e_invoice.charge_reason }),
+                ),
+                _ => format!(
+                    "<ram:SpecifiedTradeAllowanceCharge><ram:ChargeIndicator><udt:Indicator>false</udt:Indicator></ram:ChargeIndicator><ram:ActualAmount>{allowance}</ram:ActualAmount><ram:ReasonCode>95</ram:ReasonCode><ram:Reason>Discount</ram:Reason></ram:SpecifiedTradeAllowanceCharge>\n",
+                ),
+            },
+        ));
+    }
+
+    let (tax, grand_total, due, prepaid, subtotal) = (sign(tax)?, sign(grand_total)?, sign(due)?, sign(prepaid)?, sign(computed.subtotal)?);
+    let mut header_taxes = String::new();
+    if let Some(basis) = taxable {
+        if receipt.vat_number.trim().is_empty() {
+            return Err(anyhow!(format!("Document {} charges VAT, Factur-X needs the seller's VAT number for it", receipt.doc_number)));
+        }
+        header_taxes.push_str(&format!(
+            "<ram:ApplicableTradeTax><ram:CalculatedAmount>{tax}</ram:CalculatedAmount><ram:TypeCode>VAT</ram:TypeCode><ram:BasisAmount>{basis}</ram:BasisAmount><ram:CategoryCode>S</ram:CategoryCode><ram:RateApplicablePercent>{percent}</ram:RateApplicablePercent></ram:ApplicableTradeTax>\n",
+        ));
+    }
+    if let Some(basis) = exempt {
+        header_taxes.push_str(&format!(
+            "<ram:ApplicableTradeTax><ram:CalculatedAmount>0.00</ram:CalculatedAmount><ram:TypeCode>VAT</ram:TypeCode><ram:ExemptionReason>{}</ram:ExemptionReason><ram:BasisAmount>{basis}</ram:BasisAmount><ram:CategoryCode>E</ram:CategoryCode><ram:RateApplicablePercent>0</ram:RateApplicablePercent></ram:ApplicableTradeTax>\n",
+            escape_xml(if e_invoice.exemption_reason.trim().is_empty() { "Exempt" } else { &e_invoice.exemption_reason }),
+        ));
+    }
+    let seller_tax_id = match receipt.vat_number.trim() {
+        "" => String::new(),
+        vat_number => format!("<ram:SpecifiedTaxRegistration><ram:ID schemeID=\"VA\">{}</ram:ID></ram:SpecifiedTaxRegistration>", escape_xml(vat_number)),
+    };
+    let buyer_address: String = customer_lines.take(3)
+        .zip(["LineOne", "LineTwo", "LineThree"])
+        .map(|(line, element)| format!("<ram:{element}>{}</ram:{element}>", escape_xml(line)))
+        .collect();
+    let order = match receipt.order_id.trim() {
+        "" => String::new(),
+        order_id => format!("<ram:BuyerOrderReferencedDocument><ram:IssuerAssignedID>{}</ram:IssuerAssignedID></ram:BuyerOrderReferencedDocument>", escape_xml(order_id)),
+    };
+    let note = match receipt.notes.trim() {
+        "" => String::new(),
+        notes => format!("<ram:IncludedNote><ram:Content>{}</ram:Content></ram:IncludedNote>", escape_xml(notes)),
+    };
+    let payment_terms = match e_invoice.payment_terms.trim() {
+        "" => String::new(),
+        terms => format!("<ram:SpecifiedTradePaymentTerms><ram:Description>{}</ram:Description></ram:SpecifiedTradePaymentTerms>\n", escape_xml(terms)),
+    };
+    let prepaid = match prepaid.cents() {
+        0 => String::new(),
+        _ => format!("<ram:TotalPrepaidAmount>{prepaid}</ram:TotalPrepaidAmount>"),
+    };
+
+    return Ok(format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<rsm:CrossIndustryInvoice xmlns:rsm="urn:un:unece:uncefact:data:standard:CrossIndustryInvoice:100" xmlns:ram="urn:un:unece:uncefact:data:standard:ReusableAggregateBusinessInformationEntity:100" xmlns:qdt="urn:un:unece:uncefact:data:standard:QualifiedDataType:100" xmlns:udt="urn:un:unece:uncefact:data:standard:UnqualifiedDataType:100">
+<rsm:ExchangedDocumentContext><ram:GuidelineSpecifiedDocumentContextParameter><ram:ID>urn:cen.eu:en16931:2017</ram:ID></ram:GuidelineSpecifiedDocumentContextParameter></rsm:ExchangedDocumentContext>
+<rsm:ExchangedDocument>
+<ram:ID>{doc_number}</ram:ID>
+<ram:TypeCode>{type_code}</ram:TypeCode>
+<ram:IssueDateTime><udt:DateTimeString format="102">{issue_date}</udt:DateTimeString></ram:IssueDateTime>
+{note}
+</rsm:ExchangedDocument>
+<rsm:SupplyChainTradeTransaction>
+{lines}<ram:ApplicableHeaderTradeAgreement>
+<ram:SellerTradeParty><ram:Name>{seller}</ram:Name><ram:PostalTradeAddress><ram:CountryID>{seller_country}</ram:CountryID></ram:PostalTradeAddress>{seller_tax_id}</ram:SellerTradeParty>
+<ram:BuyerTradeParty><ram:Name>{buyer}</ram:Name><ram:PostalTradeAddress>{buyer_address}<ram:CountryID>{buyer_country}</ram:CountryID></ram:PostalTradeAddress></ram:BuyerTradeParty>
+{order}
+</ram:ApplicableHeaderTradeAgreement>
+<ram:ApplicableHeaderTradeDelivery/>
+<ram:ApplicableHeaderTradeSettlement>
+<ram:InvoiceCurrencyCode>{currency}</ram:InvoiceCurrencyCode>
+{header_taxes}{payment_terms}<ram:SpecifiedTradeSettlementHeaderMonetarySummation>
+<ram:LineTotalAmount>{subtotal}</ram:LineTotalAmount>
+<ram:TaxBasisTotalAmount>{subtotal}</ram:TaxBasisTotalAmount>
+<ram:TaxTotalAmount currencyID="{currency}">{tax}</ram:TaxTotalAmount>
+<ram:GrandTotalAmount>{grand_total}</ram:GrandTotalAmount>
+{prepaid}<ram:DuePayableAmount>{due}</ram:DuePayableAmount>
+</ram:SpecifiedTradeSettlementHeaderMonetarySummation>
+</ram:ApplicableHeaderTradeSettlement>
+</rsm:SupplyChainTradeTransaction>
+</rsm:CrossIndustryInvoice>
+"#,
+        doc_number = escape_xml(&receipt.doc_number),
+        seller = escape_xml(receipt.company_name.trim()),
+        seller_country = escape_xml(e_invoice.seller_country.trim()),
+        buyer = escape_xml(buyer),
+        buyer_country = escape_xml(e_invoice.buyer_country.trim()),
+        currency = escape_xml(&currency),
+        // Commercial invoice or credit note, from UNTDID 1001
+        type_code = if credit_note { 381 } else { 380 },
+    ));
+}
+
+// The XML as the file PDF/A-3 carries, with the Factur-X properties for the
+// XMP metadata and the extension schema describing them
+pub(crate) fn associated_file(receipt: &ReceiptInfo, e_invoice: &EInvoice) -> Result<AssociatedFile, Error> {
+    let xml = facturx_xml(receipt, e_invoice)?;
+    let properties = [
+        ("DocumentFileName", "The name of the embedded XML document"),
+        ("DocumentType", "The type of the hybrid document in capital letters, e.g. INVOICE or ORDER"),
+        ("Version", "The actual version of the standard applying to the embedded XML document"),
+        ("ConformanceLevel", "The conformance level of the embedded XML document"),
+    ];
+    let schema_properties: String = properties.iter()
+        .map(|(name, description)| format!(
+            "<rdf:li rdf:parseType=\"Resource\"><pdfaProperty:name>{name}</pdfaProperty:name><pdfaProperty:valueType>Text</pdfaProperty:valueType><pdfaProperty:category>external</pdfaProperty:category><pdfaProperty:description>{description}</pdfaProperty:description></rdf:li>\n",
+        ))
+        .collect();
+    let metadata = format!(
+        r#"<rdf:Description rdf:about="" xmlns:fx="{NAMESPACE}">
+<fx:DocumentType>INVOICE</fx:DocumentType>
+<fx:DocumentFileName>{FILE_NAME}</fx:DocumentFileName>
+<fx:Version>1.0</fx:Version>
+<fx:ConformanceLevel>EN 16931</fx:ConformanceLevel>
+</rdf:Description>
+<rdf:Description rdf:about=""
+ xmlns:pdfaExtension="http://www.aiim.org/pdfa/ns/extension/"
+ xmlns:pdfaSchema="http://www.aiim.org/pdfa/ns/schema#"
+ xmlns:pdfaProperty="http://www.aiim.org/pdfa/ns/property#">
+<pdfaExtension:schemas><rdf:Bag><rdf:li rdf:parseType="Resource">
+<pdfaSchema:schema>Factur-X PDFA Extension Schema</pdfaSchema:schema>
+<pdfaSchema:namespaceURI>{NAMESPACE}</pdfaSchema:namespaceURI>
+<pdfaSchema:prefix>fx</pdfaSchema:prefix>
+<pdfaSchema:property><rdf:Seq>
+{schema_properties}</rdf:Seq></pdfaSchema:property>
+</rdf:li></rdf:Bag></pdfaExtension:schemas>
+</rdf:Description>
+"#,
+    );
+    return Ok(AssociatedFile {
+        name: FILE_NAME,
+        mime_type: "text/xml",
+        description: "Factur-X invoice",
+        // The XML is the same invoice as the pages, in the EN 16931 profile
+        relationship: "Alternative",
+        content: xml.into_bytes(),
+        metadata,
+    });
+}
+
+// A quantity as cleanup_amount gives it, the other way round
+fn negated(quantity: String) -> String {
+    return match quantity.strip_prefix('-') {
+        Some(positive) => positive.to_owned(),
+        None => format!("-{quantity}"),
+    };
+}
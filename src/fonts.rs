@@ -0,0 +1,106 @@
+use std::collections::BTreeSet;
+use anyhow::{Error, Result, anyhow};
+use ttf_parser::{name_id, Face};
+use crate::{Discount, PdfResources, ReceiptInfo};
+
+// Report on the loaded fonts: what they are, under which license, and which
+// characters of a sample of documents they can't print. printpdf draws a
+// character the font has no glyph for as the .notdef box, so this is worth
+// running over a batch of real documents before enabling a new locale.
+
+#[derive(Debug, Clone)]
+pub struct FontReport {
+    // Which of the resources this is, e.g. "regular" or "mono"
+    pub face: &'static str,
+    pub full_name: String,
+    pub license: String,
+    pub glyph_count: u16,
+    // Characters of the documents the font has no glyph for, in code point
+    // order
+    pub missing: Vec<char>,
+}
+
+impl PdfResources {
+    pub fn font_report(&self, documents: &[ReceiptInfo]) -> Result<Vec<FontReport>, Error> {
+        let mut characters = BTreeSet::new();
+        for document in documents {
+            for text in document.printed_text() {
+                characters.extend(text.chars().filter(|c| !c.is_whitespace() && !c.is_control()));
+            }
+        }
+
+        let faces = [
+            ("regular", Some(&self.font_regular)),
+            ("bold", Some(&self.font_bold)),
+            ("semibold", self.font_semibold.as_ref()),
+            ("mono", Some(&self.font_mono)),
+            ("italic", self.font_italic.as_ref()),
+            ("bold italic", self.font_bold_italic.as_ref()),
+        ];
+        let mut reports = Vec::new();
+        for (name, data) in faces {
+            let Some(data) = data else { continue };
+            let face = Face::parse(data, 0).map_err(|err| anyhow!(format!("Couldn't read the {name} font: {err}")))?;
+            let lookup = |id: u16| {
+                face.names()
+                    .into_iter()
+                    .filter(|entry| entry.name_id == id)
+                    .find_map(|entry| entry.to_string())
+                    .unwrap_or_default()
+            };
+            reports.push(FontReport {
+                face: name,
+                full_name: lookup(name_id::FULL_NAME),
+                license: lookup(name_id::LICENSE),
+                glyph_count: face.number_of_glyphs(),
+                missing: characters.iter().copied().filter(|c| face.glyph_index(*c).is_none()).collect(),
+            });
+        }
+        return Ok(reports);
+    }
+}
+
+impl ReceiptInfo {
+    // Every piece of the document's own text which ends up on the page.
+    // Amounts print as digits, which every face has.
+    pub(crate) fn printed_text(&self) -> Vec<&str> {
+        let mut text = vec![
+            self.title.as_str(), &self.date, &self.company_name, &self.company_info_line, &self.customer_info,
+            &self.transaction_number, &self.order_id, &self.vat_number, &self.doc_number, &self.delivery_tickets,
+            &self.weigh_tickets, &self.employee, &self.slogan, &self.notes,
+            &self.supersedes,
+        ];
+        for line in &self.item_lines {
+            text.extend([line.code.as_str(), &line.description, &line.quantity, &line.unit_price, &line.uom]);
+            if let Some(Discount::Text(discount)) = &line.discount {
+                text.push(discount);
+            }
+            if let Some(rental) = &line.rental {
+                text.extend([rental.start.as_str(), &rental.end, &rental.rate]);
+            }
+        }
+        for amount in self.totals.iter().chain(&self.payments) {
+            text.push(&amount.name);
+        }
+        if let Some(statement) = &self.statement {
+            for line in &statement.transactions {
+                text.extend([line.date.as_str(), &line.reference, &line.description]);
+            }
+        }
+        if let Some(z_report) = &self.z_report {
+            text.extend(z_report.tenders.iter().map(|tender| tender.name.as_str()));
+            text.extend(z_report.clerks.iter().map(|clerk| clerk.clerk.as_str()));
+        }
+        if let Some(schedule) = &self.schedule {
+            text.extend([schedule.requested_date.as_str(), &schedule.window, &schedule.truck, &schedule.driver]);
+        }
+        text.extend(self.delivery_site.iter().map(|site| site.address.as_str()));
+        text.extend(self.attachments.iter().map(|attachment| attachment.caption.as_str()));
+        text.extend(self.related_documents.iter().map(|related| related.doc_number.as_str()));
+        if let Some(fiscal) = &self.fiscal {
+            text.extend([fiscal.fiscal_number.as_str(), &fiscal.signature]);
+            text.extend(fiscal.lines.iter().map(String::as_str));
+        }
+        return text;
+    }
+}
@@ -0,0 +1,121 @@
+use printpdf::lopdf::{dictionary, Dictionary, Document, Object, ObjectId, Stream};
+use anyhow::{Error, Result};
+use crate::signature::{Rect, SignatureBlock};
+
+// Fillable AcroForm fields for quotes. printpdf can't create form fields, so
+// they are added to the saved document afterwards, on top of the printed
+// signature block.
+
+pub(crate) fn add_quote_fields(pdf: &[u8], block: &SignatureBlock, page: u32) -> Result<Vec<u8>, Error> {
+    let mut doc = Document::load_mem(pdf)?;
+    // The signature block is at the bottom of the last page of the quote,
+    // which is the last page unless terms or attachments follow it
+    let page_id = match doc.get_pages().get(&page) {
+        Some(id) => *id,
+        None => return Ok(pdf.to_vec()),
+    };
+
+    let helvetica = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+        "Encoding" => "WinAnsiEncoding",
+    });
+    let zapf_dingbats = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "ZapfDingbats",
+    });
+
+    let fields = vec![
+        add_checkbox(&mut doc, page_id, "accepted", "Quote accepted", block.acceptance_rect()),
+        add_field(&mut doc, page_id, "Sig", "signature", "Signature", block.signature_rect()),
+        add_field(&mut doc, page_id, "Tx", "printed_name", "Printed name", block.name_rect()),
+        add_field(&mut doc, page_id, "Tx", "date", "Date signed", block.date_rect()),
+    ];
+    for field in &fields {
+        add_annotation(&mut doc, page_id, *field)?;
+    }
+
+    let acro_form = doc.add_object(dictionary! {
+        "Fields" => fields.into_iter().map(Object::Reference).collect::<Vec<_>>(),
+        // Let viewers draw the text fields themselves
+        "NeedAppearances" => true,
+        "DA" => Object::string_literal("/Helv 0 Tf 0 g"),
+        "DR" => dictionary! {
+            "Font" => dictionary! {
+                "Helv" => helvetica,
+                "ZaDb" => zapf_dingbats,
+            },
+        },
+    });
+    let catalog_id = doc.trailer.get(b"Root")?.as_reference()?;
+    doc.get_object_mut(catalog_id)?.as_dict_mut()?.set("AcroForm", acro_form);
+
+    let mut bytes = Vec::new();
+    doc.save_to(&mut bytes)?;
+    return Ok(bytes);
+}
+
+fn rect_object(rect: Rect) -> Object {
+    return Object::Array(rect.iter().map(|value| Object::Real(*value as _)).collect());
+}
+
+// Combined field and widget annotation
+fn field_dictionary(page_id: ObjectId, field_type: &str, name: &str, tooltip: &str, rect: Rect) -> Dictionary {
+    return dictionary! {
+        "Type" => "Annot",
+        "Subtype" => "Widget",
+        "FT" => field_type,
+        "T" => Object::string_literal(name),
+        "TU" => Object::string_literal(tooltip),
+        "Rect" => rect_object(rect),
+        "P" => page_id,
+        // Printable
+        "F" => 4,
+        "DA" => Object::string_literal("/Helv 9 Tf 0 g"),
+    };
+}
+
+fn add_field(doc: &mut Document, page_id: ObjectId, field_type: &str, name: &str, tooltip: &str, rect: Rect) -> ObjectId {
+    return doc.add_object(field_dictionary(page_id, field_type, name, tooltip, rect));
+}
+
+fn add_checkbox(doc: &mut Document, page_id: ObjectId, name: &str, tooltip: &str, rect: Rect) -> ObjectId {
+    let [llx, lly, urx, ury] = rect;
+    let (width, height) = (urx - llx, ury - lly);
+    let appearance = |content: String| {
+        return Stream::new(dictionary! {
+            "Type" => "XObject",
+            "Subtype" => "Form",
+            "BBox" => rect_object([0.0, 0.0, width, height]),
+        }, content.into_bytes());
+    };
+    // A check mark, the box itself is printed on the page
+    let checked = doc.add_object(appearance(format!(
+        "q 1.2 w {:.2} {:.2} m {:.2} {:.2} l {:.2} {:.2} l S Q",
+        width * 0.2, height * 0.5, width * 0.4, height * 0.2, width * 0.8, height * 0.8,
+    )));
+    let unchecked = doc.add_object(appearance(String::new()));
+
+    let mut field = field_dictionary(page_id, "Btn", name, tooltip, rect);
+    field.set("DA", Object::string_literal("/ZaDb 0 Tf 0 g"));
+    field.set("V", "Off");
+    field.set("AS", "Off");
+    field.set("AP", dictionary! {
+        "N" => dictionary! {
+            "Yes" => checked,
+            "Off" => unchecked,
+        },
+    });
+    return doc.add_object(field);
+}
+
+fn add_annotation(doc: &mut Document, page_id: ObjectId, annotation: ObjectId) -> Result<(), Error> {
+    let page = doc.get_object_mut(page_id)?.as_dict_mut()?;
+    match page.get_mut(b"Annots") {
+        Ok(Object::Array(annotations)) => annotations.push(Object::Reference(annotation)),
+        _ => page.set("Annots", vec![Object::Reference(annotation)]),
+    }
+    return Ok(());
+}
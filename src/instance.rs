@@ -0,0 +1,360 @@
+use ttf_parser::{Face, GlyphId, OutlineBuilder, Tag};
+use anyhow::{Error, Result, anyhow};
+use crate::sfnt::{self, Table};
+
+// Static faces cut from a variable font, so one Noto Sans variable font can
+// stand in for the regular, semibold and bold files. printpdf embeds fonts
+// as they are and PDF viewers draw a variable font at its default instance,
+// so each weight is written out as a plain TrueType font: the outlines with
+// the weight's deltas applied and composites flattened, the advances from
+// HVAR, and the names of the style. The variation tables are left out, and
+// so is the hinting, whose instructions would need instancing too.
+
+// Tables that are carried over as they are
+const COPIED: [&[u8; 4]; 2] = [b"cmap", b"post"];
+
+// Names that say which face this is, replaced with the instance's
+const STYLE_NAMES: [u16; 10] = [1, 2, 3, 4, 6, 16, 17, 21, 22, 25];
+
+pub(crate) fn static_instance(variable: &[u8], weight: u16, style: &str) -> Result<Vec<u8>, Error> {
+    let source = sfnt::read_tables(variable)?;
+    sfnt::table(&source, b"glyf")?;
+    let mut face = match Face::parse(variable, 0) {
+        Ok(face) => face,
+        Err(e) => return Err(anyhow!(format!("The font could not be parsed. Reason: `{e}`"))),
+    };
+    let wght = Tag::from_bytes(b"wght");
+    let Some(axis) = face.variation_axes().into_iter().find(|axis| axis.tag == wght) else {
+        return Err(anyhow!("The font has no weight axis"));
+    };
+    if (weight as f32) < axis.min_value || (weight as f32) > axis.max_value {
+        return Err(anyhow!(format!("The font's weights run from {} to {}, not to {weight}", axis.min_value, axis.max_value)));
+    }
+    face.set_variation(wght, weight as f32);
+
+    let count = face.number_of_glyphs();
+    let mut glyphs = Vec::with_capacity(count as usize);
+    let mut hmtx = Vec::with_capacity(count as usize * 4);
+    let mut bounds: Option<Bounds> = None;
+    let mut metrics = HorizontalMetrics::default();
+    let (mut max_points, mut max_contours) = (0, 0);
+    for id in 0..count {
+        let mut outline = Outline::default();
+        face.outline_glyph(GlyphId(id), &mut outline);
+        let advance = face.glyph_hor_advance(GlyphId(id)).unwrap_or(0);
+        let glyph = outline.glyph()?;
+        if let Some(glyph_bounds) = glyph.bounds {
+            bounds = Some(bounds.map_or(glyph_bounds, |bounds| bounds.union(&glyph_bounds)));
+            metrics.add(advance, &glyph_bounds);
+        }
+        metrics.advance_max = metrics.advance_max.max(advance);
+        let lsb = glyph.bounds.as_ref().map_or(0, |bounds| bounds.x_min);
+        hmtx.extend_from_slice(&advance.to_be_bytes());
+        hmtx.extend_from_slice(&lsb.to_be_bytes());
+        max_points = max_points.max(glyph.points);
+        max_contours = max_contours.max(glyph.contours);
+        glyphs.push(glyph.data);
+    }
+    let (glyf, loca) = sfnt::write_glyphs(&glyphs);
+    let bold = weight >= 700;
+
+    let bounds = bounds.unwrap_or_default();
+    let mut head = sfnt::table(&source, b"head")?.to_vec();
+    for (offset, value) in [(36, bounds.x_min), (38, bounds.y_min), (40, bounds.x_max), (42, bounds.y_max)] {
+        sfnt::write_u16(&mut head, offset, value as u16)?;
+    }
+    let mac_style = sfnt::read_u16(&head, 44)?;
+    sfnt::write_u16(&mut head, 44, if bold { mac_style | 1 } else { mac_style & !1 })?;
+    sfnt::write_u16(&mut head, 50, 1)?;
+
+    let mut hhea = sfnt::table(&source, b"hhea")?.to_vec();
+    sfnt::write_u16(&mut hhea, 10, metrics.advance_max)?;
+    sfnt::write_u16(&mut hhea, 12, metrics.min_lsb as u16)?;
+    sfnt::write_u16(&mut hhea, 14, metrics.min_rsb as u16)?;
+    sfnt::write_u16(&mut hhea, 16, metrics.x_max_extent as u16)?;
+    sfnt::write_u16(&mut hhea, 34, count)?;
+
+    // Version 1.0 of maxp, with nothing left of the composites or hinting
+    let mut maxp = sfnt::table(&source, b"maxp")?.to_vec();
+    if sfnt::read_u32(&maxp, 0)? != 0x00010000 || maxp.len() < 32 {
+        return Err(anyhow!("The font's maxp table is not for TrueType outlines"));
+    }
+    sfnt::write_u16(&mut maxp, 6, max_points)?;
+    sfnt::write_u16(&mut maxp, 8, max_contours)?;
+    for offset in [10, 12, 26, 28, 30] {
+        sfnt::write_u16(&mut maxp, offset, 0)?;
+    }
+
+    // The italic bit stays, bold and regular are the instance's
+    let mut os2 = sfnt::table(&source, b"OS/2")?.to_vec();
+    sfnt::write_u16(&mut os2, 4, weight)?;
+    let fs_selection = sfnt::read_u16(&os2, 62)?;
+    let italic = fs_selection & 1;
+    let selection = match (bold, weight == 400 && italic == 0) {
+        (true, _) => italic | 1 << 5,
+        (false, true) => 1 << 6,
+        (false, false) => italic,
+    };
+    sfnt::write_u16(&mut os2, 62, (fs_selection & !0x61) | selection)?;
+
+    let mut tables: Vec<Table> = vec![
+        (*b"glyf", glyf),
+        (*b"loca", loca),
+        (*b"hmtx", hmtx),
+        (*b"head", head),
+        (*b"hhea", hhea),
+        (*b"maxp", maxp),
+        (*b"OS/2", os2),
+        (*b"name", names(sfnt::table(&source, b"name")?, style)?),
+    ];
+    for tag in COPIED {
+        tables.push((*tag, sfnt::table(&source, tag)?.to_vec()));
+    }
+    return Ok(sfnt::write_tables(tables));
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Bounds {
+    x_min: i16,
+    y_min: i16,
+    x_max: i16,
+    y_max: i16,
+}
+
+impl Bounds {
+    fn union(&self, other: &Bounds) -> Bounds {
+        return Bounds {
+            x_min: self.x_min.min(other.x_min),
+            y_min: self.y_min.min(other.y_min),
+            x_max: self.x_max.max(other.x_max),
+            y_max: self.y_max.max(other.y_max),
+        };
+    }
+}
+
+// The extremes hhea keeps, the bearings over the glyphs with outlines
+struct HorizontalMetrics {
+    advance_max: u16,
+    min_lsb: i16,
+    min_rsb: i16,
+    x_max_extent: i16,
+}
+
+impl Default for HorizontalMetrics {
+    fn default() -> Self {
+        return Self { advance_max: 0, min_lsb: i16::MAX, min_rsb: i16::MAX, x_max_extent: i16::MIN };
+    }
+}
+
+impl HorizontalMetrics {
+    fn add(&mut self, advance: u16, bounds: &Bounds) {
+        let rsb = (advance as i32 - bounds.x_max as i32).clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+        self.min_lsb = self.min_lsb.min(bounds.x_min);
+        self.min_rsb = self.min_rsb.min(rsb);
+        self.x_max_extent = self.x_max_extent.max(bounds.x_max);
+    }
+}
+
+// A glyph's contours as ttf-parser draws them, every point written out
+// including the on-curve points TrueType leaves implied between two
+// off-curve ones
+#[derive(Default)]
+struct Outline {
+    contours: Vec<Vec<(f32, f32, bool)>>,
+    cubic: bool,
+}
+
+impl OutlineBuilder for Outline {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.contours.push(vec![(x, y, true)]);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        if let Some(contour) = self.contours.last_mut() {
+            contour.push((x, y, true));
+        }
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        if let Some(contour) = self.contours.last_mut() {
+            contour.push((x1, y1, false));
+            contour.push((x, y, true));
+        }
+    }
+
+    fn curve_to(&mut self, _x1: f32, _y1: f32, _x2: f32, _y2: f32, _x: f32, _y: f32) {
+        self.cubic = true;
+    }
+
+    fn close(&mut self) {}
+}
+
+struct Glyph {
+    data: Vec<u8>,
+    bounds: Option<Bounds>,
+    points: u16,
+    contours: u16,
+}
+
+// Point flags of simple glyphs
+const ON_CURVE: u8 = 0x01;
+const X_SHORT: u8 = 0x02;
+const Y_SHORT: u8 = 0x04;
+const REPEAT: u8 = 0x08;
+const X_SAME_OR_POSITIVE: u8 = 0x10;
+const Y_SAME_OR_POSITIVE: u8 = 0x20;
+
+impl Outline {
+    // A simple glyph without instructions, or no data for an empty glyph
+    fn glyph(self) -> Result<Glyph, Error> {
+        if self.cubic {
+            return Err(anyhow!("The font has cubic outlines, which TrueType glyphs can't hold"));
+        }
+        let mut contours: Vec<Vec<(i16, i16, bool)>> = Vec::new();
+        for contour in self.contours {
+            let mut points: Vec<(i16, i16, bool)> = contour.iter()
+                .map(|(x, y, on_curve)| (x.round() as i16, y.round() as i16, *on_curve))
+                .collect();
+            // The contour is drawn back to where it started
+            if points.len() > 1 && points.first() == points.last() {
+                points.pop();
+            }
+            if points.len() > 1 {
+                contours.push(points);
+            }
+        }
+        let points: Vec<(i16, i16, bool)> = contours.iter().flatten().copied().collect();
+        if points.is_empty() {
+            return Ok(Glyph { data: Vec::new(), bounds: None, points: 0, contours: 0 });
+        }
+        let bounds = Bounds {
+            x_min: points.iter().map(|point| point.0).min().unwrap_or(0),
+            y_min: points.iter().map(|point| point.1).min().unwrap_or(0),
+            x_max: points.iter().map(|point| point.0).max().unwrap_or(0),
+            y_max: points.iter().map(|point| point.1).max().unwrap_or(0),
+        };
+        let mut data = Vec::new();
+        data.extend_from_slice(&(contours.len() as i16).to_be_bytes());
+        for value in [bounds.x_min, bounds.y_min, bounds.x_max, bounds.y_max] {
+            data.extend_from_slice(&value.to_be_bytes());
+        }
+        let mut end = 0;
+        for contour in &contours {
+            end += contour.len();
+            data.extend_from_slice(&(end as u16 - 1).to_be_bytes());
+        }
+        // No instructions
+        data.extend_from_slice(&0u16.to_be_bytes());
+        let (mut flags, mut xs, mut ys) = (Vec::new(), Vec::new(), Vec::new());
+        let (mut x, mut y) = (0i16, 0i16);
+        for (point_x, point_y, on_curve) in &points {
+            let (dx, dy) = (*point_x as i32 - x as i32, *point_y as i32 - y as i32);
+            (x, y) = (*point_x, *point_y);
+            let mut flag = if *on_curve { ON_CURVE } else { 0 };
+            flag |= coordinate(dx, X_SHORT, X_SAME_OR_POSITIVE, &mut xs)?;
+            flag |= coordinate(dy, Y_SHORT, Y_SAME_OR_POSITIVE, &mut ys)?;
+            flags.push(flag);
+        }
+        data.extend_from_slice(&repeated(&flags));
+        data.extend_from_slice(&xs);
+        data.extend_from_slice(&ys);
+        return Ok(Glyph { data, bounds: Some(bounds), points: points.len() as u16, contours: contours.len() as u16 });
+    }
+}
+
+// Writes a coordinate as a byte, nothing or two bytes, returning its flags
+fn coordinate(delta: i32, short: u8, same_or_positive: u8, bytes: &mut Vec<u8>) -> Result<u8, Error> {
+    if delta == 0 {
+        return Ok(same_or_positive);
+    }
+    if delta.abs() <= 255 {
+        bytes.push(delta.unsigned_abs() as u8);
+        return Ok(if delta > 0 { short | same_or_positive } else { short });
+    }
+    let Ok(delta) = i16::try_from(delta) else { return Err(anyhow!("A glyph's points are too far apart for a TrueType glyph")) };
+    bytes.extend_from_slice(&delta.to_be_bytes());
+    return Ok(0);
+}
+
+// Runs of the same flag are written once with a count
+fn repeated(flags: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut index = 0;
+    while index < flags.len() {
+        let flag = flags[index];
+        let run = flags[index..].iter().take(256).take_while(|other| **other == flag).count();
+        if run > 1 {
+            bytes.push(flag | REPEAT);
+            bytes.push((run - 1) as u8);
+        } else {
+            bytes.push(flag);
+        }
+        index += run;
+    }
+    return bytes;
+}
+
+// The name table with the family's style names replaced, e.g. "Noto Sans"
+// and "SemiBold", written for Windows in English
+fn names(name: &[u8], style: &str) -> Result<Vec<u8>, Error> {
+    let count = sfnt::read_u16(name, 2)? as usize;
+    let storage = sfnt::read_u16(name, 4)? as usize;
+    let mut records: Vec<([u16; 4], Vec<u8>)> = Vec::new();
+    let mut family = None;
+    for index in 0..count {
+        let record = 6 + index * 12;
+        let mut ids = [0; 4];
+        for (field, id) in ids.iter_mut().enumerate() {
+            *id = sfnt::read_u16(name, record + field * 2)?;
+        }
+        let length = sfnt::read_u16(name, record + 8)? as usize;
+        let offset = storage + sfnt::read_u16(name, record + 10)? as usize;
+        let Some(text) = name.get(offset..offset + length) else { return Err(anyhow!("The font's name table is cut short")) };
+        // The typographic family over the legacy one, which may have a style in it
+        let windows_english = ids[0] == 3 && ids[1] == 1 && ids[2] == 0x409;
+        if windows_english && (ids[3] == 16 || (ids[3] == 1 && family.is_none())) {
+            let units: Vec<u16> = text.chunks(2).map(|pair| u16::from_be_bytes([pair[0], *pair.get(1).unwrap_or(&0)])).collect();
+            family = Some(String::from_utf16_lossy(&units));
+        }
+        if !STYLE_NAMES.contains(&ids[3]) {
+            records.push((ids, text.to_vec()));
+        }
+    }
+    let Some(family) = family else { return Err(anyhow!("The font has no family name")) };
+    let postscript = format!("{}-{}", family.replace(' ', ""), style.replace(' ', ""));
+    let full = format!("{family} {style}");
+    // Only the four styles fit the legacy family, the rest are families of their own
+    let ribbi = ["Regular", "Italic", "Bold", "Bold Italic"].contains(&style);
+    let mut styled = vec![(3, postscript.clone()), (4, full.clone()), (6, postscript)];
+    if ribbi {
+        styled.extend([(1, family), (2, style.to_owned())]);
+    } else {
+        let legacy_style = if style.ends_with("Italic") { "Italic" } else { "Regular" };
+        let legacy_family = full.trim_end_matches(" Italic").to_owned();
+        styled.extend([(1, legacy_family), (2, legacy_style.to_owned()), (16, family), (17, style.to_owned())]);
+    }
+    for (name_id, text) in styled {
+        let encoded: Vec<u8> = text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+        records.push(([3, 1, 0x409, name_id], encoded));
+    }
+    records.sort_by_key(|(ids, _)| *ids);
+
+    let mut table = Vec::new();
+    for value in [0, records.len() as u16, 6 + records.len() as u16 * 12] {
+        table.extend_from_slice(&value.to_be_bytes());
+    }
+    let mut strings = Vec::new();
+    for (ids, text) in &records {
+        for id in ids {
+            table.extend_from_slice(&id.to_be_bytes());
+        }
+        let (Ok(length), Ok(offset)) = (u16::try_from(text.len()), u16::try_from(strings.len())) else {
+            return Err(anyhow!("The font's names don't fit in a name table"));
+        };
+        table.extend_from_slice(&length.to_be_bytes());
+        table.extend_from_slice(&offset.to_be_bytes());
+        strings.extend_from_slice(text);
+    }
+    table.extend_from_slice(&strings);
+    return Ok(table);
+}
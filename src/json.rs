@@ -0,0 +1,175 @@
+use std::fmt;
+use printpdf::PdfDocumentReference;
+use anyhow::{Error, Result};
+use serde_json::Value;
+use crate::{date, gen_pdf, AmountFormat, PdfResources, ReceiptInfo};
+
+// Documents sent as JSON by middleware instead of built in Rust, with the
+// field names of ReceiptInfo. Fields added after the first POS integration,
+// e.g. `draft` or `schedule`, may be left out. Amounts are checked up front
+// so the sender gets every malformed field at once, not just the first one
+// serde trips over.
+
+// A field which couldn't be used, e.g. `item_lines[2].amount`. For JSON
+// which doesn't parse it's the position instead, e.g. "line 3, column 14".
+#[derive(Debug, Clone)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+// Returned inside the anyhow::Error, for senders which want the fields
+// through downcast_ref
+#[derive(Debug)]
+pub struct PayloadError {
+    pub fields: Vec<FieldError>,
+}
+
+impl fmt::Display for PayloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid document payload:")?;
+        for field in &self.fields {
+            write!(f, " `{}`: {};", field.field, field.message)?;
+        }
+        return Ok(());
+    }
+}
+
+impl std::error::Error for PayloadError {}
+
+pub fn receipt_from_json(json: &str) -> Result<ReceiptInfo, Error> {
+    return receipt_from_json_with_format(json, &AmountFormat::default());
+}
+
+// For a POS which writes amounts with other separators, e.g. "1.234,50".
+// The amounts are read in that format before they become Money.
+pub fn receipt_from_json_with_format(json: &str, input: &AmountFormat) -> Result<ReceiptInfo, Error> {
+    let mut document: Value = match serde_json::from_str(json) {
+        Ok(document) => document,
+        Err(e) => {
+            let field = format!("line {}, column {}", e.line(), e.column());
+            return Err(PayloadError { fields: vec![FieldError { field, message: e.to_string() }] }.into());
+        },
+    };
+    let fields = malformed_fields(&mut document, input);
+    if !fields.is_empty() {
+        return Err(PayloadError { fields }.into());
+    }
+    return match serde_json::from_value(document) {
+        Ok(receipt) => Ok(receipt),
+        Err(e) => {
+            // e.g. "missing field `doc_number`"
+            let message = e.to_string();
+            let field = match message.strip_prefix("missing field `").and_then(|rest| rest.split_once('`')) {
+                Some((field, _)) => field.to_owned(),
+                None => String::from("document"),
+            };
+            Err(PayloadError { fields: vec![FieldError { field, message }] }.into())
+        },
+    };
+}
+
+// Runs pre_pass with the default options, for senders which don't need any
+pub fn gen_pdf_from_json(json: &str, resources: &PdfResources) -> Result<PdfDocumentReference, Error> {
+    let mut receipt = receipt_from_json(json)?;
+    receipt.pre_pass()?;
+    return gen_pdf(&receipt, resources);
+}
+
+// The Money fields, as JSON pointers under the arrays they're in
+const AMOUNTS: [&str; 8] = [
+    "/amount_due",
+    "/change_due",
+    "/statement/opening_balance",
+    "/statement/closing_balance",
+    "/progress_billing/contract_total",
+    "/progress_billing/previously_billed",
+    "/progress_billing/this_invoice",
+    "/quote_reference/quoted_total",
+];
+const ARRAY_AMOUNTS: [(&str, &[&str]); 6] = [
+    ("/item_lines", &["amount"]),
+    ("/totals", &["value"]),
+    ("/payments", &["value"]),
+    ("/statement/transactions", &["amount", "balance"]),
+    ("/z_report/tenders", &["expected", "counted"]),
+    ("/z_report/clerks", &["sales"]),
+];
+
+// Rewrites the amounts sent as text as plain amounts, and returns the ones
+// which aren't amounts. Quantities and unit prices stay text, they're only
+// checked here.
+fn malformed_fields(document: &mut Value, input: &AmountFormat) -> Vec<FieldError> {
+    let count = |document: &Value, array: &str| document.pointer(array).and_then(Value::as_array).map_or(0, Vec::len);
+    let mut amounts: Vec<String> = AMOUNTS.iter().map(|pointer| pointer.to_string()).collect();
+    for (array, fields) in ARRAY_AMOUNTS {
+        for i in 0..count(document, array) {
+            amounts.extend(fields.iter().map(|field| format!("{array}/{i}/{field}")));
+        }
+    }
+    let mut texts = Vec::new();
+    for i in 0..count(document, "/item_lines") {
+        texts.extend(["quantity", "unit_price", "rental/rate"].map(|field| format!("/item_lines/{i}/{field}")));
+        for j in 0..count(document, &format!("/item_lines/{i}/price_breaks")) {
+            texts.push(format!("/item_lines/{i}/price_breaks/{j}/unit_price"));
+        }
+    }
+
+    let mut errors = Vec::new();
+    for pointer in amounts {
+        let Some(Value::String(text)) = document.pointer_mut(&pointer) else { continue };
+        if text.trim().is_empty() {
+            continue;
+        }
+        match input.cleanup(text) {
+            Ok(amount) => *text = amount,
+            Err(e) => errors.push(FieldError { field: field_name(&pointer), message: e.to_string() }),
+        }
+    }
+    for pointer in texts {
+        let Some(text) = document.pointer(&pointer).and_then(Value::as_str) else { continue };
+        if text.trim().is_empty() {
+            continue;
+        }
+        if let Err(e) = input.cleanup(text) {
+            errors.push(FieldError { field: field_name(&pointer), message: e.to_string() });
+        }
+    }
+    // A discount which isn't an amount is kept as text, e.g. "10%"
+    for i in 0..count(document, "/item_lines") {
+        if let Some(Value::String(text)) = document.pointer_mut(&format!("/item_lines/{i}/discount")) {
+            if let Ok(amount) = input.cleanup(text) {
+                *text = amount;
+            }
+        }
+    }
+
+    for i in 0..count(document, "/item_lines") {
+        for name in ["start", "end"] {
+            let Some(value) = document.pointer(&format!("/item_lines/{i}/rental/{name}")).and_then(Value::as_str) else { continue };
+            if date::parse_minutes(value).is_none() {
+                errors.push(FieldError {
+                    field: format!("item_lines[{i}].rental.{name}"),
+                    message: format!("`{value}` is not a valid date and time"),
+                });
+            }
+        }
+    }
+    return errors;
+}
+
+// "/item_lines/2/amount" is "item_lines[2].amount"
+fn field_name(pointer: &str) -> String {
+    let mut name = String::new();
+    for part in pointer.split('/').filter(|part| !part.is_empty()) {
+        if part.bytes().all(|byte| byte.is_ascii_digit()) {
+            name.push_str(&format!("[{part}]"));
+        } else {
+            if !name.is_empty() {
+                name.push('.');
+            }
+            name.push_str(part);
+        }
+    }
+    return name;
+}
@@ -0,0 +1,129 @@
+// Static text printed on the documents, with French translations for the
+// bilingual (Canadian) labels mode.
+
+pub(crate) struct Label {
+    pub en: &'static str,
+    pub fr: &'static str,
+}
+
+impl Label {
+    // Text for a label sitting on its own line, e.g. "Sold to / Vendu à :"
+    pub fn text(&self, bilingual: bool) -> String {
+        if !bilingual || self.en == self.fr {
+            return self.en.to_owned();
+        }
+        return format!("{} / {}", self.en.trim_end_matches(':'), self.fr);
+    }
+
+    // Whether the French text should be printed separately, for labels which are
+    // too narrow to hold both translations on one line (column headers)
+    pub fn second_line(&self, bilingual: bool) -> Option<&'static str> {
+        if bilingual && self.en != self.fr {
+            return Some(self.fr);
+        }
+        return None;
+    }
+}
+
+macro_rules! labels {
+    ($($name:ident: $en:expr, $fr:expr;)*) => {
+        $(pub(crate) const $name: Label = Label { en: $en, fr: $fr };)*
+    }
+}
+
+labels! {
+    DATE_TIME: "Date/Time:", "Date/Heure :";
+    ORDER_ID: "Order ID:", "N° de commande :";
+    VAT_NUMBER: "VAT Number:", "N° de TVA :";
+    INVOICE_NUMBER: "Invoice Number:", "N° de facture :";
+    RECEIPT_NUMBER: "Receipt Number:", "N° de reçu :";
+    QUOTE_NUMBER: "Quote Number:", "N° de soumission :";
+    STATEMENT_NUMBER: "Statement Number:", "N° de relevé :";
+    REPORT_NUMBER: "Report Number:", "N° de rapport :";
+    TICKET_NUMBER: "Ticket Number:", "N° de bon :";
+    SOLD_TO: "Sold to:", "Vendu à :";
+    CLERK: "Clerk:", "Commis :";
+    DELIVERY_TICKET: "Delivery Ticket #:", "Bon de livraison n° :";
+    WEIGH_TICKET: "Weigh Ticket #:", "Billet de pesée n° :";
+    DELIVERY_SCHEDULE: "Delivery Schedule", "Livraison prévue";
+    REQUESTED_DATE: "Requested:", "Demandée :";
+    DELIVERY_WINDOW: "Window:", "Plage horaire :";
+    TRUCK: "Truck:", "Camion :";
+    DRIVER: "Driver:", "Chauffeur :";
+    DELIVERY_SITE: "Delivery Site", "Lieu de livraison";
+    SITE_MAP: "Site map", "Plan du site";
+    DIRECTIONS: "Directions", "Itinéraire";
+
+    LINE_NUMBER: "#", "#";
+    CODE: "Code", "Code";
+    DESCRIPTION: "Description", "Description";
+    UOM: "U/M", "U/M";
+    QUANTITY: "Quantity", "Quantité";
+    UNIT_PRICE: "Unit Price", "Prix unitaire";
+    DISCOUNT: "Discount", "Rabais";
+    TOTAL: "Total", "Total";
+
+    DATE: "Date", "Date";
+    REFERENCE: "Reference", "Référence";
+    AMOUNT: "Amount", "Montant";
+    BALANCE: "Balance", "Solde";
+    BALANCE_FORWARD: "Balance forward", "Solde reporté";
+
+    CURRENT: "Current", "Courant";
+    DAYS_31_60: "31-60 Days", "31-60 jours";
+    DAYS_61_90: "61-90 Days", "61-90 jours";
+    OVER_90: "Over 90 Days", "Plus de 90 jours";
+
+    AMOUNT_DUE: "Amount Due:", "Montant dû :";
+    TENDER: "Tender", "Paiement";
+    CHANGE_DUE: "Change Due:", "Monnaie à rendre :";
+    REFUND_DUE: "Refund Due:", "Remboursement dû :";
+    QUOTE: "Quote", "Soumission";
+    INVOICE: "Invoice", "Facture";
+    RECEIPT: "Receipt", "Reçu";
+    STATEMENT: "Statement", "Relevé";
+    Z_REPORT: "Z Report", "Rapport Z";
+    DELIVERY: "Delivery Ticket", "Bon de livraison";
+    REF: "Ref:", "Réf. :";
+    QUOTED: "Quoted", "Soumis";
+    INVOICED: "Invoiced", "Facturé";
+    VARIANCE: "Variance", "Écart";
+
+    EXPECTED: "Expected", "Attendu";
+    COUNTED: "Counted", "Compté";
+    OVER_SHORT: "Over/Short", "Écart";
+    TRANSACTIONS: "Transactions", "Transactions";
+    CLERK_COLUMN: "Clerk", "Commis";
+
+    PROGRESS_BILLING: "Progress Billing", "Facturation progressive";
+    CONTRACT_TOTAL: "Contract Total:", "Total du contrat :";
+    PREVIOUSLY_BILLED: "Previously Billed:", "Déjà facturé :";
+    THIS_INVOICE: "This Invoice:", "Cette facture :";
+    BILLED_TO_DATE: "Billed to Date:", "Facturé à ce jour :";
+    BALANCE_TO_FINISH: "Balance to Finish:", "Solde à facturer :";
+    PERCENT_COMPLETE: "Percent Complete:", "Pourcentage d'achèvement :";
+
+    NOTES: "Notes:", "Remarques :";
+    RECEIVED_BY: "Received By", "Reçu par";
+    PRINT_NAME: "Print Name", "Nom en lettres moulées";
+    ACCEPT_QUOTE: "I accept this quote", "J'accepte cette soumission";
+    CLAIMS_NOTICE: "All claims and returned goods MUST be accompanied by this bill", "Toute réclamation et tout retour de marchandise DOIVENT être accompagnés de cette facture";
+    INTEREST_NOTICE: "*INTEREST AT THE RATE OF 1.5% PER MONTH WILL BE CHARGED ON ALL OVERDUE INVOICES*", "*DES INTÉRÊTS DE 1,5 % PAR MOIS SERONT EXIGÉS SUR TOUTE FACTURE EN SOUFFRANCE*";
+    PAGE: "Page", "Page";
+    CONTINUED: "continued", "suite";
+    CHECKSUM: "Checksum:", "Somme de contrôle :";
+    FISCAL_NUMBER: "Fiscal No.:", "N° fiscal :";
+    FISCAL_SIGNATURE: "Signature:", "Signature :";
+    DRAFT: "DRAFT", "ÉBAUCHE";
+    TBD: "TBD", "À déf.";
+    CONTRACT: "Contract", "Contrat";
+    RENTAL: "Rental:", "Location :";
+    HOURS: "hours", "heures";
+    DAYS: "days", "jours";
+    AMOUNTS_IN: "Amounts in", "Montants en";
+    PRICE_OVERRIDE_NOTE: "* price manually adjusted", "* prix ajusté manuellement";
+    TERMS_AND_CONDITIONS: "Terms & Conditions", "Modalités et conditions";
+    REVISED: "REVISED", "RÉVISÉ";
+    SUPERSEDES_REVISION: "supersedes revision", "remplace la révision";
+    CHANGES: "Changes from the Previous Version", "Modifications depuis la version précédente";
+}
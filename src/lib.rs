@@ -1,28 +1,129 @@
-use printpdf::{PdfDocument, PdfDocumentReference, Mm, PdfLayerReference, Point, Line, Pt, SvgTransform, Svg};
+#![deny(unsafe_code)]
+
+use printpdf::{PdfDocument, PdfDocumentReference, PdfLayerReference, IndirectFontRef, CurTransMat, Mm, Pt, SvgTransform, Svg, LinkAnnotation, Rect, Actions};
 use std::{fs, sync::Arc};
 use anyhow::{Error, Result, anyhow};
 use number_to_words::number_to_words;
 
-macro_rules! lpad {
-    ($arg:expr) => {{
-        format!("${:>11}", $arg)
-    }}
-}
+mod attachments;
+mod budget;
+mod calibration;
+mod catalog;
+mod checksum;
+mod compliance;
+mod date;
+mod deposit;
+mod diff;
+mod drawing;
+mod export;
+mod extract;
+mod facturx;
+mod fonts;
+mod forms;
+mod instance;
+#[cfg(feature = "serde")]
+mod json;
+mod labels;
+mod limits;
+mod markdown;
+mod merge;
+mod metrics;
+mod money;
+mod numbering;
+mod nup;
+mod page;
+mod pdfa;
+mod portal;
+mod qr;
+mod rental;
+mod report;
+mod rewrite;
+#[cfg(feature = "sample-data")]
+mod sample;
+mod search;
+mod sfnt;
+mod signature;
+mod site;
+mod stamp;
+mod store;
+mod subset;
+mod surcharge;
+mod template;
+mod textflow;
+mod theme;
+mod thumbnails;
+mod totals;
+mod uom;
+
+pub use attachments::Attachment;
+pub use calibration::{calibrate_pdf, gen_calibration_page, Calibration};
+pub use catalog::{PriceBreak, Product, ProductCatalog};
+pub use compliance::{ChainedSignature, ComplianceHook, FiscalInfo};
+pub use deposit::ContainerDeposit;
+pub use diff::{diff, Change, DocumentDiff};
+pub use export::{vat_return_csv, GlAccounts};
+pub use extract::{extract_text, gen_pdf_bytes_with_text};
+pub use facturx::{facturx_xml, EInvoice};
+pub use fonts::FontReport;
+#[cfg(feature = "serde")]
+pub use json::{gen_pdf_from_json, receipt_from_json, receipt_from_json_with_format, FieldError, PayloadError};
+pub use limits::Limits;
+pub use markdown::{parse as parse_markdown, wrap_runs, Block as MarkdownBlock, Run as MarkdownRun, Style as MarkdownStyle};
+pub use merge::append_pdfs;
+pub use money::{cleanup_amount, parse_amount, AmountFormat, CurrencyFormat, Discount, Money, NegativeStyle, Rounding, RoundingMode, SymbolPosition};
+pub use numbering::{NumberingFormat, NumberingMode};
+pub use page::PageSize;
+pub use nup::two_up_pdf;
+pub use portal::PortalLink;
+pub use rental::{Rental, RentalPeriod};
+pub use report::{gen_sales_summary, ReportPeriod};
+pub use rewrite::DescriptionRule;
+#[cfg(feature = "sample-data")]
+pub use sample::{sample_documents, SampleGenerator};
+pub use search::{search, SearchQuery};
+pub use site::DeliverySite;
+pub use stamp::stamp_pdf;
+pub use store::{PdfStore, StoredDocument};
+pub use surcharge::{Surcharge, SurchargeAmount};
+pub use template::{ColumnLayout, LogoPlacement, Template, TextPlacement};
+pub use theme::{BoxStyle, Edges, FontWeight, LineStyle, Theme, ThemeColor};
+pub use totals::{ComputedTotals, MismatchedTotal, TaxRate, TotalsMismatch};
+pub use uom::UomCatalog;
+use drawing::QuickShapes;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum DocType {
     Invoice,
     Receipt,
     Quote,
+    Statement,
+    // End-of-day till reconciliation
+    ZReport,
+    // Goes out with the truck, quantities without prices
+    DeliveryTicket,
+}
+
+impl Default for DocType {
+    fn default() -> Self {
+        return DocType::Receipt;
+    }
 }
 
 enum DocLayout {
     Standard,
     StandardWithDiscounts,
     Receipt,
+    Statement,
+    ZReport,
+    DeliveryTicket,
 }
 
-#[derive(Debug)]
+// Default so documents can be built with ..Default::default() and callers
+// keep compiling as fields are added
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReceiptInfo {
     pub title: String,
     pub date: String,
@@ -39,148 +140,846 @@ pub struct ReceiptInfo {
     pub weigh_tickets: String,
     pub totals: Vec<Amount>,
     pub payments: Vec<Amount>,
-    pub amount_due: String,
+    // None when the POS leaves it blank
+    #[cfg_attr(feature = "serde", serde(default, with = "money::optional"))]
+    pub amount_due: Option<Money>,
     pub employee: String,
     pub slogan: String,
+    pub notes: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub statement: Option<Statement>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub quote_reference: Option<QuoteReference>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub progress_billing: Option<ProgressBilling>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub z_report: Option<ZReport>,
+    // Photos appended to the document, one per page
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub attachments: Vec<Attachment>,
+    // A quote or invoice still being put together. Lines may have no price
+    // yet (printed as "TBD"), the totals aren't checked and the document is
+    // stamped DRAFT.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub draft: bool,
+    // Surcharges pre_pass added to the totals
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub surcharges: Vec<Amount>,
+    // 0 for the original document, incremented every time a corrected one
+    // replaces it. Revised documents say so in the header.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub revision: u32,
+    // Document number of the revision this one replaces, when the correction
+    // was given a new number
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub supersedes: String,
+    // The documents this one follows from, e.g. the quote and invoice a
+    // receipt was for, printed as a "Ref:" line above the item lines
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub related_documents: Vec<RelatedDocument>,
+    // What was tendered over the total, filled in by pre_pass
+    #[cfg_attr(feature = "serde", serde(default, with = "money::optional"))]
+    pub change_due: Option<Money>,
+    // Printed on invoices and delivery tickets
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub schedule: Option<Schedule>,
+    // Printed on delivery tickets
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub delivery_site: Option<DeliverySite>,
+    // Filled in by the compliance hooks
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fiscal: Option<FiscalInfo>,
+    // Printed as a QR code in the bottom left corner, e.g. a link to pay the
+    // invoice online. Takes the place of the portal link.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub qr_payload: Option<String>,
+    // Dollars with GenOptions::amount_format when None
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub currency: Option<CurrencyFormat>,
+    // Problems found by pre_pass which don't prevent the document from being
+    // generated, but which someone should look at.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub warnings: Vec<String>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ItemLine {
     pub code: String,
     pub description: String,
     pub quantity: String,
     pub unit_price: String,
-    pub amount: String,
+    // None for lines without a price, e.g. a note or a line still TBD
+    #[cfg_attr(feature = "serde", serde(default, with = "money::optional"))]
+    pub amount: Option<Money>,
     pub uom: String,
-    pub discount: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub discount: Option<Discount>,
     pub taxable: bool,
+    // The clerk typed in the price instead of using the price list
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub price_override: bool,
+    // From the product catalog, for reporting
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub category: String,
+    // Product image from the catalog, a file in data_dir/products
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub image: String,
+    // Quantity pricing from the catalog, shown on quotes
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub price_breaks: Vec<PriceBreak>,
+    // For equipment billed by the hour or the day
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub rental: Option<Rental>,
 }
 
 #[derive(Debug)]
-pub struct Amount {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Statement {
+    // None for statements without a balance forward
+    #[cfg_attr(feature = "serde", serde(default, with = "money::optional"))]
+    pub opening_balance: Option<Money>,
+    // As reported by the source document, checked by pre_pass
+    #[cfg_attr(feature = "serde", serde(default, with = "money::optional"))]
+    pub closing_balance: Option<Money>,
+    pub transactions: Vec<StatementLine>,
+    // Aging summary of the closing balance, filled in by pre_pass
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub aging: Option<Aging>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StatementLine {
+    pub date: String,
+    pub reference: String,
+    pub description: String,
+    // Charges are positive, payments and credits negative
+    pub amount: Money,
+    // Running balance after this transaction, filled in by pre_pass
+    #[cfg_attr(feature = "serde", serde(default, with = "money::optional"))]
+    pub balance: Option<Money>,
+    // When the invoice is due. Falls back to `date` for aging if empty.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub due_date: String,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Aging {
+    pub current: Money,
+    pub days_31_60: Money,
+    pub days_61_90: Money,
+    pub over_90: Money,
+    pub total: Money,
+}
+
+// The quote an invoice was made from, used to show customers how the final
+// price deviates from what they were quoted
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuoteReference {
+    pub quote_number: String,
+    pub quoted_total: Money,
+}
+
+// When and how the goods go out, for dispatch
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Schedule {
+    pub requested_date: String,
+    // e.g. "8:00-10:00" or "AM"
+    pub window: String,
+    pub truck: String,
+    pub driver: String,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RelatedDocument {
+    pub doc_type: DocType,
+    pub doc_number: String,
+}
+
+// Percent-complete billing against a fixed contract, as used by construction
+// customers
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProgressBilling {
+    pub contract_total: Money,
+    pub previously_billed: Money,
+    pub this_invoice: Money,
+    // Computed from the amounts billed to date if empty
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub percent_complete: String,
+}
+
+// Till data the POS sends at the end of the day
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ZReport {
+    pub tenders: Vec<TillTender>,
+    // e.g. "Sales", "Returns", "Voids", "No Sales" with their counts
+    pub transaction_counts: Vec<TransactionCount>,
+    pub clerks: Vec<ClerkTotal>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TillTender {
     pub name: String,
+    // What the POS recorded
+    pub expected: Money,
+    // What was in the drawer
+    pub counted: Money,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TransactionCount {
+    pub name: String,
+    // As the POS sends it, e.g. "12"
     pub value: String,
 }
 
-trait QuickShapes {
-    // fn add_closed_shape<T>(&mut self, points: Vec<T>);
-    fn add_box(&self, x1: Mm, y1: Mm, x2: Mm, y2: Mm);
-    fn add_line(&self, x1: Mm, y1: Mm, x2: Mm, y2: Mm);
-}
-
-impl QuickShapes for PdfLayerReference {
-    fn add_box(&self, x1: Mm, y1: Mm, x2: Mm, y2: Mm) {
-        self.add_shape(Line {
-            points: vec![
-                (Point::new(x1, y1), false),
-                (Point::new(x2, y1), false),
-                (Point::new(x2, y2), false),
-                (Point::new(x1, y2), false),
-            ],
-            is_closed: true,
-            has_fill: false,
-            has_stroke: true,
-            is_clipping_path: false,
-        });
-    }
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClerkTotal {
+    pub clerk: String,
+    pub transactions: String,
+    pub sales: Money,
+}
 
-    fn add_line(&self, x1: Mm, y1: Mm, x2: Mm, y2: Mm) {
-        self.add_shape(Line {
-            points: vec![
-                (Point::new(x1, y1), false),
-                (Point::new(x2, y2), false),
-            ],
-            is_closed: true,
-            has_fill: false,
-            has_stroke: true,
-            is_clipping_path: false,
-        });
-    }
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Amount {
+    pub name: String,
+    // Zero on the rows without a name, which print as a rule
+    pub value: Money,
 }
 
+// Rendering switches which aren't part of the document itself
+#[derive(Debug, Default)]
+pub struct GenOptions {
+    // Adds a "#" column numbering the item lines, so customers have a stable
+    // way of referring to a line.
+    pub line_numbers: bool,
+    // Prints every static label in both English and French
+    pub bilingual_labels: bool,
+    pub theme: Theme,
+    // Gives quotes fillable acceptance, signature and date fields. Only takes
+    // effect through gen_pdf_bytes_with_options, as the fields are added to
+    // the saved document.
+    pub fillable_quote: bool,
+    // When set, a signed download link is printed as a QR code in the bottom
+    // left corner, which is also clickable in the PDF
+    pub portal: Option<PortalLink>,
+    // For amounts computed while rendering, e.g. variances and over/short
+    pub rounding: Rounding,
+    // Grouping and negative style of every printed amount
+    pub amount_format: AmountFormat,
+    // Moves and scales every page for the printer. Like fillable_quote, only
+    // takes effect through gen_pdf_bytes_with_options.
+    pub calibration: Calibration,
+    // Decimal places of the item quantities, per unit of measure
+    pub uom_catalog: UomCatalog,
+    // Prints the quantity converted to the other measurement system below
+    // it, for units the catalog has a conversion for
+    pub dual_units: bool,
+    // Marks manually priced lines with a "*" and a footnote, as the audit
+    // requires on office copies
+    pub mark_price_overrides: bool,
+    // For customers on confidential contract pricing: unit prices and
+    // discounts print as "Contract", the line totals are still shown
+    pub hide_unit_prices: bool,
+    // Prints the start of ReceiptInfo::checksum in the bottom right corner
+    pub print_checksum: bool,
+    // For a corrected document replacing an earlier one: the changes from
+    // diff(earlier, corrected), listed on a page after the document
+    pub changes: Option<DocumentDiff>,
+    // Adds a column with the product images of the lines the catalog has
+    // one for, for retail
+    pub product_images: bool,
+    // Prints the catalog's quantity price breaks below the lines of quotes
+    pub price_breaks: bool,
+    // Items the driver ticks off on delivery tickets, e.g. "Site accessible"
+    // or "Dumped where directed", printed above the signature
+    pub delivery_checklist: Vec<String>,
+    // Largest file gen_pdf_bytes_with_options may produce, in bytes. Larger
+    // documents are made smaller at some cost to their look, or rejected.
+    pub max_size: Option<usize>,
+    // Where the header, logo, item table and slogan go
+    pub template: Template,
+    // Narrows the columns right of the description to what they hold, and
+    // gives the description the room left over
+    pub fit_columns: bool,
+    // Paper other than Letter is scaled onto in the saved file, so like
+    // calibration it's only right through gen_pdf_bytes_with_options
+    pub page_size: PageSize,
+    // PDF/A-3b for archiving, through gen_pdf_bytes_with_options. Needs the
+    // sRGB color profile among the resources and can't be used with
+    // fillable_quote.
+    pub pdfa: bool,
+    // Carries the invoice as Factur-X XML for EU customers' accounts payable
+    // software, which makes it PDF/A like `pdfa`. Other documents and drafts
+    // are left as they are.
+    pub e_invoice: Option<EInvoice>,
+}
+
+// Top of the item table on the pages it continues on, in points
+const CONTINUATION_TOP: f64 = 720.0;
+
+const SIGNATURE_BLOCK: signature::SignatureBlock = signature::SignatureBlock {
+    x1: 350.0,
+    x2: 558.0,
+    bottom: 70.0,
+};
+
 pub struct PdfResources {
     font_regular: Arc<[u8]>,
     font_bold: Arc<[u8]>,
+    font_semibold: Option<Arc<[u8]>>,
     font_mono: Arc<[u8]>,
+    font_italic: Option<Arc<[u8]>>,
+    font_bold_italic: Option<Arc<[u8]>>,
     logo: Svg,
+    terms: Option<String>,
+    thumbnails: thumbnails::ThumbnailCache,
+    // sRGB ICC profile, only needed for PDF/A
+    icc_profile: Option<Arc<[u8]>>,
+}
+
+// Settings for the computations done by pre_pass
+#[derive(Debug, Default)]
+pub struct PrePassOptions {
+    pub rounding: Rounding,
+    pub surcharges: Vec<Surcharge>,
+    pub container_deposits: Vec<ContainerDeposit>,
+    pub limits: Limits,
+    // Separators the POS writes amounts with, when they aren't the English
+    // ones. Quantities, unit prices and discounts are rewritten as plain
+    // amounts before anything else; the Money fields are read in this format
+    // by receipt_from_json_with_format.
+    pub input_format: Option<AmountFormat>,
+    pub description_rules: Vec<DescriptionRule>,
+    pub product_catalog: ProductCatalog,
+    // Document number formats required by law, the first one for the
+    // document type applies
+    pub numbering: Vec<NumberingFormat>,
+    // Run in order after everything else, e.g. to sign the document
+    pub compliance: Vec<Arc<dyn ComplianceHook>>,
+    // Fails documents whose totals don't add up, see compute_totals
+    pub verify_totals: bool,
+    pub tax_rates: Vec<TaxRate>,
 }
 
 impl ReceiptInfo {
     pub fn pre_pass(&mut self) -> Result<(), Error> {
+        return self.pre_pass_with_options(&PrePassOptions::default());
+    }
+
+    pub fn pre_pass_with_options(&mut self, options: &PrePassOptions) -> Result<(), Error> {
+        let rounding = &options.rounding;
+        if let Some(input) = &options.input_format {
+            self.normalize_amounts(input);
+        }
+        self.apply_numbering(&options.numbering)?;
+        // The catalog descriptions are written as they should be printed
+        self.rewrite_descriptions(&options.description_rules);
+        self.apply_catalog(&options.product_catalog);
         let receipt_payment_pos = self.payments
             .iter()
             .position(|tender| tender.name.eq("Pay on Account"));
         if let Some(index) = receipt_payment_pos {
             let tender = self.payments.remove(index);
-            let value_as_float = str::parse::<f64>(&tender.value)?.abs(); 
-            let number_in_words = number_to_words(value_as_float, false);
+            let value = tender.value;
+            // number_to_words has nothing to say about zero
+            let number_in_words = if value == Money::ZERO {
+                String::from("zero")
+            } else {
+                number_to_words(value.abs().to_f64(), false)
+            };
+            // A negative payment on account is a deposit being paid back
+            let description = if value.is_negative() {
+                format!("Refunded from cash deposit the sum of {number_in_words} dollars.")
+            } else {
+                format!("Received as cash deposit the sum of {number_in_words} dollars for materials.")
+            };
             self.item_lines.push(
                 ItemLine {
-                    code: String::new(),
-                    description: format!("Received as cash deposit the sum of {number_in_words} dollars for materials."),
-                    quantity: String::new(),
-                    unit_price: String::new(),
-                    discount: None,
-                    uom: String::new(),
-                    amount: format!("{value_as_float:.2}"),
+                    description,
+                    amount: Some(value),
                     taxable: false,
+                    ..ItemLine::default()
                 }
             );
             self.totals.clear();
             self.totals.push(
                 Amount {
                     name: String::from("Total:"),
-                    value: format!("{value_as_float:.2}"),
+                    value,
                 }
             )
         }
+        self.verify_totals(options)?;
+        self.add_container_deposits(&options.container_deposits, rounding)?;
+        self.add_surcharges(&options.surcharges, rounding)?;
+        self.check_limits(&options.limits)?;
+        self.check_rentals(rounding)?;
+        if matches!(self.doc_type, DocType::Invoice | DocType::Receipt) {
+            self.compute_change_due()?;
+        }
+        if !self.draft {
+            for line in &self.item_lines {
+                if !line.quantity.is_empty() && line.amount.is_none() {
+                    self.warnings.push(format!("Line `{}` ({}) has a quantity but no price", line.code, line.description));
+                }
+                // Unit prices may have fractional cents, only the line total is rounded
+                let parsed = (money::parse_amount(&line.quantity), money::parse_amount(&line.unit_price));
+                if let (Ok(quantity), Ok(unit_price), Some(amount), None) = (parsed.0, parsed.1, line.amount, &line.discount) {
+                    let expected = Money::from_f64(quantity * unit_price, rounding);
+                    if expected != amount {
+                        self.warnings.push(format!(
+                            "Line `{}` ({}) totals {amount}, but {} x {} comes to {expected}",
+                            line.code, line.description, line.quantity, line.unit_price,
+                        ));
+                    }
+                }
+            }
+        }
+        if let Some(statement) = &mut self.statement {
+            // Drafts are expected not to add up yet
+            let mut balance_warnings = Vec::new();
+            statement.compute_balances(&mut balance_warnings)?;
+            if !self.draft {
+                self.warnings.append(&mut balance_warnings);
+            }
+            statement.compute_aging(&self.date, &mut self.warnings)?;
+        }
+        for hook in &options.compliance {
+            hook.apply(self)?;
+        }
+        return Ok(());
+    }
+
+    // Tenders over the total are given back: as change when paid in cash,
+    // otherwise as a refund. The amount due of an overpaid document is zero.
+    fn compute_change_due(&mut self) -> Result<(), Error> {
+        let Some(total) = self.total() else { return Ok(()) };
+        let tendered = Money::checked_sum(self.payments.iter().map(|tender| tender.value))?;
+        let over = tendered.checked_sub(total)?;
+        if self.payments.is_empty() || total.is_negative() || over <= Money::ZERO {
+            return Ok(());
+        }
+        self.change_due = Some(over);
+        if self.amount_due.is_some_and(Money::is_negative) {
+            self.amount_due = Some(Money::ZERO);
+        }
+        return Ok(());
+    }
+
+    // Adds an amount pre_pass billed on top of what the POS sent to the total
+    // and the amount due, and to the subtotal for item lines
+    fn add_to_totals(&mut self, amount: Money, is_item_line: bool) -> Result<(), Error> {
+        for total in &mut self.totals {
+            if total.name.eq("Total:") || (is_item_line && is_subtotal(&total.name)) {
+                total.value = total.value.checked_add(amount)?;
+            }
+        }
+        if let Some(amount_due) = self.amount_due {
+            self.amount_due = Some(amount_due.checked_add(amount)?);
+        }
+        return Ok(());
+    }
+
+    // Fills {doc_number}, {doc_type} and {date} in a URL template, e.g.
+    // "https://erp/documents/{doc_number}/pdf". Values are percent-encoded.
+    pub fn fill_url_template(&self, template: &str) -> Result<String, Error> {
+        let mut url = String::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            url.push_str(&rest[..start]);
+            let end = match rest[start..].find('}') {
+                Some(end) => start + end,
+                None => return Err(anyhow!(format!("Unclosed placeholder in the URL template `{template}`"))),
+            };
+            let value = match &rest[start + 1..end] {
+                "doc_number" => self.doc_number.clone(),
+                "doc_type" => self.doc_type.slug().to_owned(),
+                "date" => date::to_iso(&self.date).unwrap_or_else(|| self.date.clone()),
+                other => return Err(anyhow!(format!("Unknown placeholder `{{{other}}}` in the URL template `{template}`"))),
+            };
+            url.push_str(&percent_encode(&value));
+            rest = &rest[end + 1..];
+        }
+        url.push_str(rest);
+        return Ok(url);
+    }
+}
+
+impl DocType {
+    // Lowercase name for use in URLs and file names
+    pub fn slug(&self) -> &'static str {
+        return match self {
+            DocType::Invoice => "invoice",
+            DocType::Receipt => "receipt",
+            DocType::Quote => "quote",
+            DocType::Statement => "statement",
+            DocType::ZReport => "z-report",
+            DocType::DeliveryTicket => "delivery-ticket",
+        };
+    }
+}
+
+fn doc_type_label(doc_type: DocType) -> &'static labels::Label {
+    return match doc_type {
+        DocType::Invoice => &labels::INVOICE,
+        DocType::Receipt => &labels::RECEIPT,
+        DocType::Quote => &labels::QUOTE,
+        DocType::Statement => &labels::STATEMENT,
+        DocType::ZReport => &labels::Z_REPORT,
+        DocType::DeliveryTicket => &labels::DELIVERY,
+    };
+}
+
+fn is_cash(tender: &str) -> bool {
+    return tender.to_uppercase().contains("CASH");
+}
+
+fn is_subtotal(name: &str) -> bool {
+    let name = name.to_uppercase();
+    return name.starts_with("SUBTOTAL") || name.starts_with("SUB TOTAL") || name.starts_with("SUB-TOTAL");
+}
+
+// Encodes everything except the unreserved characters of RFC 3986
+pub(crate) fn percent_encode(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    return encoded;
+}
+
+// Label and amount of each row of the progress billing block
+type ProgressRows = Vec<(&'static labels::Label, String)>;
+
+impl ProgressBilling {
+    // Rows of the progress billing block, rounded for display, and the
+    // percentage complete
+    fn summary(&self) -> Result<(ProgressRows, f64), Error> {
+        let (contract_total, previously_billed, this_invoice) = (self.contract_total, self.previously_billed, self.this_invoice);
+        let billed_to_date = previously_billed.checked_add(this_invoice)?;
+        let percent_complete = if !self.percent_complete.is_empty() {
+            str::parse::<f64>(self.percent_complete.trim_end_matches('%'))?
+        } else if contract_total != Money::ZERO {
+            billed_to_date.cents() as f64 / contract_total.cents() as f64 * 100.0
+        } else {
+            0.0
+        };
+        return Ok((vec![
+            (&labels::CONTRACT_TOTAL, contract_total.to_string()),
+            (&labels::PREVIOUSLY_BILLED, previously_billed.to_string()),
+            (&labels::THIS_INVOICE, this_invoice.to_string()),
+            (&labels::BILLED_TO_DATE, billed_to_date.to_string()),
+            (&labels::BALANCE_TO_FINISH, contract_total.checked_sub(billed_to_date)?.to_string()),
+        ], percent_complete));
+    }
+}
+
+impl Statement {
+    // Recomputes the running balance of every transaction and checks the
+    // result against the closing balance reported by the source document.
+    fn compute_balances(&mut self, warnings: &mut Vec<String>) -> Result<(), Error> {
+        let mut balance = self.opening_balance.unwrap_or(Money::ZERO);
+        for line in &mut self.transactions {
+            balance = balance.checked_add(line.amount)?;
+            line.balance = Some(balance);
+        }
+        if let Some(closing_balance) = self.closing_balance {
+            if closing_balance != balance {
+                warnings.push(format!("Statement closing balance {closing_balance} does not match the computed running balance {balance}"));
+            }
+        }
+        return Ok(());
+    }
+
+    // Sorts the outstanding charges into aging buckets by how many days past
+    // due they are on `as_of`. Payments and credits are applied to the oldest
+    // charges first. The balance forward has no date of its own, so it is
+    // treated as being at least as old as the oldest transaction.
+    fn compute_aging(&mut self, as_of: &str, warnings: &mut Vec<String>) -> Result<(), Error> {
+        let Some(today) = date::parse_days(as_of) else {
+            warnings.push(format!("Could not compute aging, the statement date `{as_of}` is not a recognized date"));
+            return Ok(());
+        };
+        let mut charges: Vec<(i64, Money)> = Vec::new();
+        let mut credits = Money::ZERO;
+        let mut oldest_age = 0;
+        for line in &self.transactions {
+            let amount = line.amount;
+            let due_date = if line.due_date.is_empty() { &line.date } else { &line.due_date };
+            let age = match date::parse_days(due_date) {
+                Some(due) => today - due,
+                None => {
+                    warnings.push(format!("Transaction `{}` has no recognizable due date, aging it as current", line.reference));
+                    0
+                },
+            };
+            oldest_age = oldest_age.max(age);
+            if amount.is_negative() {
+                credits = credits.checked_sub(amount)?;
+            } else {
+                charges.push((age, amount));
+            }
+        }
+        if let Some(opening_balance) = self.opening_balance {
+            if opening_balance.is_negative() {
+                credits = credits.checked_sub(opening_balance)?;
+            } else {
+                charges.push((oldest_age, opening_balance));
+            }
+        }
+
+        // Oldest first
+        charges.sort_by_key(|(age, _)| std::cmp::Reverse(*age));
+        let mut buckets = [Money::ZERO; 4];
+        for (age, mut amount) in charges {
+            let applied = credits.min(amount);
+            credits = credits.checked_sub(applied)?;
+            amount = amount.checked_sub(applied)?;
+            let bucket = match age {
+                ..=30 => 0,
+                31..=60 => 1,
+                61..=90 => 2,
+                _ => 3,
+            };
+            buckets[bucket] = buckets[bucket].checked_add(amount)?;
+        }
+        // Unapplied credit leaves the account in credit
+        buckets[0] = buckets[0].checked_sub(credits)?;
+
+        self.aging = Some(Aging {
+            current: buckets[0],
+            days_31_60: buckets[1],
+            days_61_90: buckets[2],
+            over_90: buckets[3],
+            total: Money::checked_sum(buckets)?,
+        });
         return Ok(());
     }
 }
 impl PdfResources {
     pub fn load(data_dir: &str) -> Result<Self, Error> {
-        let font_regular_file = format!("{data_dir}/fonts/NotoSans-Regular.ttf");
-        let font_regular = match fs::read(&font_regular_file) {
-            Ok(bytes) => bytes,
-            Err(e) => return Err(anyhow!(format!("Could not read the font from the file: `{}`. Reason: `{e}`", &font_regular_file)).into()),
-        };
-        let font_bold_file = format!("{data_dir}/fonts/NotoSans-Bold.ttf");
-        let font_bold = match fs::read(&font_bold_file) {
-            Ok(bytes) => bytes,
-            Err(e) => return Err(anyhow!(format!("Could not read the font from the file: `{}`. Reason: `{e}`", &font_bold_file)).into()),
+        // A variable Noto Sans stands in for the upright faces, cut at each
+        // of the weights a theme can ask for
+        let variable_file = format!("{data_dir}/fonts/NotoSans-VariableFont_wdth,wght.ttf");
+        let (font_regular, font_semibold, font_bold) = match read_optional_font(&variable_file)? {
+            Some(variable) => (
+                instance_font(&variable, &variable_file, 400, "Regular")?,
+                Some(instance_font(&variable, &variable_file, 600, "SemiBold")?),
+                instance_font(&variable, &variable_file, 700, "Bold")?,
+            ),
+            None => {
+                let font_regular_file = format!("{data_dir}/fonts/NotoSans-Regular.ttf");
+                let font_regular = match fs::read(&font_regular_file) {
+                    Ok(bytes) => bytes,
+                    Err(e) => return Err(anyhow!(format!("Could not read the font from the file: `{}`. Reason: `{e}`", &font_regular_file))),
+                };
+                let font_bold_file = format!("{data_dir}/fonts/NotoSans-Bold.ttf");
+                let font_bold = match fs::read(&font_bold_file) {
+                    Ok(bytes) => bytes,
+                    Err(e) => return Err(anyhow!(format!("Could not read the font from the file: `{}`. Reason: `{e}`", &font_bold_file))),
+                };
+                // Semibold is only needed if the theme asks for it, bold stands in otherwise
+                let font_semibold = read_optional_font(&format!("{data_dir}/fonts/NotoSans-SemiBold.ttf"))?;
+                (font_regular, font_semibold, font_bold)
+            },
         };
         let font_mono_file = format!("{data_dir}/fonts/NotoSansMono-Regular.ttf");
         let font_mono = match fs::read(&font_mono_file) {
             Ok(bytes) => bytes,
-            Err(e) => return Err(anyhow!(format!("Could not read the font from the file: `{}`. Reason: `{e}`", &font_mono_file)).into()),
+            Err(e) => return Err(anyhow!(format!("Could not read the font from the file: `{}`. Reason: `{e}`", &font_mono_file))),
+        };
+        // Italic faces are optional, text falls back to the upright faces
+        let variable_italic_file = format!("{data_dir}/fonts/NotoSans-Italic-VariableFont_wdth,wght.ttf");
+        let (font_italic, font_bold_italic) = match read_optional_font(&variable_italic_file)? {
+            Some(variable) => (
+                Some(instance_font(&variable, &variable_italic_file, 400, "Italic")?),
+                Some(instance_font(&variable, &variable_italic_file, 700, "Bold Italic")?),
+            ),
+            None => (
+                read_optional_font(&format!("{data_dir}/fonts/NotoSans-Italic.ttf"))?,
+                read_optional_font(&format!("{data_dir}/fonts/NotoSans-BoldItalic.ttf"))?,
+            ),
         };
         let logo = {
             let svg_file = format!("{data_dir}/logo.svg");
             let svg = match fs::read_to_string(&svg_file) {
                 Ok(file_as_string) => file_as_string,
-                Err(e) => return Err(anyhow!(format!("Could not read the logo from the file: `{}`. Reason: `{e}`", &svg_file)).into()),
+                Err(e) => return Err(anyhow!(format!("Could not read the logo from the file: `{}`. Reason: `{e}`", &svg_file))),
             };
             match Svg::parse(&svg) {
                 Ok(svg) => svg,
-                Err(e) => return Err(anyhow!(format!("Could not parse the svg loaded from: `{}`. Reason: {e}", &svg_file)).into()),
+                Err(e) => return Err(anyhow!(format!("Could not parse the svg loaded from: `{}`. Reason: {e}", &svg_file))),
             }
         };
+        // Terms & conditions are optional. Markdown is preferred over plain text
+        // if both happen to exist.
+        let mut terms = None;
+        for terms_file in [format!("{data_dir}/terms.md"), format!("{data_dir}/terms.txt")] {
+            match fs::read_to_string(&terms_file) {
+                Ok(text) => {
+                    terms = Some(text);
+                    break;
+                },
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(anyhow!(format!("Could not read the terms from the file: `{}`. Reason: `{e}`", &terms_file))),
+            }
+        }
+        let icc_file = format!("{data_dir}/color/sRGB.icc");
+        let icc_profile = match fs::read(&icc_file) {
+            Ok(bytes) => Some(bytes),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(anyhow!(format!("Could not read the color profile from the file: `{}`. Reason: `{e}`", &icc_file))),
+        };
         // Converting from Vec to Arc doesn't reallocate the memory. Party!
         // This would be a safe thing to use raw pointers on, but I don't want
         // to implement that right now!
         return Ok(Self { 
             font_regular: Arc::from(font_regular),
             font_bold: Arc::from(font_bold),
+            font_semibold: font_semibold.map(Arc::from),
             font_mono: Arc::from(font_mono),
+            font_italic: font_italic.map(Arc::from),
+            font_bold_italic: font_bold_italic.map(Arc::from),
             logo,
+            terms,
+            thumbnails: thumbnails::ThumbnailCache::new(Some(format!("{data_dir}/products"))),
+            icc_profile: icc_profile.map(Arc::from),
         });
     }
+
+    // The fonts in the crate's fonts directory and a placeholder logo,
+    // compiled in so nothing has to be shipped alongside the binary. There
+    // are no terms, no product images and no color profile for PDF/A.
+    #[cfg(feature = "embedded-resources")]
+    pub fn embedded() -> Result<Self, Error> {
+        let logo = match Svg::parse(EMBEDDED_LOGO) {
+            Ok(svg) => svg,
+            Err(e) => return Err(anyhow!(format!("Could not parse the embedded logo. Reason: {e}"))),
+        };
+        return Ok(Self {
+            font_regular: Arc::from(&include_bytes!("../fonts/NotoSans-Regular.ttf")[..]),
+            font_bold: Arc::from(&include_bytes!("../fonts/NotoSans-Bold.ttf")[..]),
+            font_semibold: Some(Arc::from(&include_bytes!("../fonts/NotoSans-SemiBold.ttf")[..])),
+            font_mono: Arc::from(&include_bytes!("../fonts/NotoSansMono-Regular.tff")[..]),
+            font_italic: Some(Arc::from(&include_bytes!("../fonts/NotoSans-Italic.ttf")[..])),
+            font_bold_italic: Some(Arc::from(&include_bytes!("../fonts/NotoSans-BoldItalic.ttf")[..])),
+            logo,
+            terms: None,
+            thumbnails: thumbnails::ThumbnailCache::new(None),
+            icc_profile: None,
+        });
+    }
+}
+
+// An empty frame where the logo goes
+#[cfg(feature = "embedded-resources")]
+const EMBEDDED_LOGO: &str = r##"<svg xmlns="http://www.w3.org/2000/svg" width="160" height="100" viewBox="0 0 160 100">
+<rect x="2" y="2" width="156" height="96" fill="none" stroke="#999999" stroke-width="2" stroke-dasharray="6 4"/>
+</svg>"##;
+
+fn read_optional_font(font_file: &str) -> Result<Option<Vec<u8>>, Error> {
+    return match fs::read(font_file) {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(anyhow!(format!("Could not read the font from the file: `{}`. Reason: `{e}`", font_file))),
+    };
+}
+
+fn instance_font(variable: &[u8], font_file: &str, weight: u16, style: &str) -> Result<Vec<u8>, Error> {
+    return match instance::static_instance(variable, weight, style) {
+        Ok(font) => Ok(font),
+        Err(e) => Err(anyhow!(format!("Could not cut the {style} face from the variable font in the file: `{}`. Reason: `{e}`", font_file))),
+    };
 }
 
 pub fn gen_pdf(receipt: &ReceiptInfo, resources: &PdfResources) -> Result<PdfDocumentReference, Error> {
+    return gen_pdf_with_options(receipt, resources, &GenOptions::default());
+}
+
+// Renders and saves the document, including anything printpdf can't express
+// which is added to the saved file afterwards
+pub fn gen_pdf_bytes_with_options(receipt: &ReceiptInfo, resources: &PdfResources, options: &GenOptions) -> Result<Vec<u8>, Error> {
+    let mut bytes = render_bytes(receipt, resources, options, &budget::Reduction::default())?;
+    if let Some(max_size) = options.max_size {
+        for reduction in &budget::REDUCTIONS {
+            if bytes.len() <= max_size {
+                break;
+            }
+            bytes = render_bytes(receipt, resources, options, reduction)?;
+        }
+        if bytes.len() > max_size {
+            return Err(budget::over_budget_error(&bytes, max_size));
+        }
+    }
+    return Ok(bytes);
+}
+
+fn render_bytes(receipt: &ReceiptInfo, resources: &PdfResources, options: &GenOptions, reduction: &budget::Reduction) -> Result<Vec<u8>, Error> {
+    let (doc, last_page) = render(receipt, resources, options, reduction)?;
+    let mut bytes = doc.save_to_bytes()?;
+    if options.fillable_quote && receipt.doc_type == DocType::Quote {
+        bytes = forms::add_quote_fields(&bytes, &SIGNATURE_BLOCK, last_page)?;
+    }
+    if options.page_size != PageSize::Letter {
+        bytes = page::fit_to_paper(&bytes, &options.page_size)?;
+    }
+    // Last, so the form fields are moved along with the page
+    if !options.calibration.is_identity() {
+        bytes = calibration::calibrate_pdf(&bytes, &options.calibration)?;
+    }
+    if reduction.subset_fonts {
+        bytes = subset::subset_fonts(&bytes)?;
+    }
+    if reduction.compress {
+        bytes = budget::compress(&bytes)?;
+    }
+    let invoice_xml = match &options.e_invoice {
+        Some(e_invoice) if receipt.doc_type == DocType::Invoice && !receipt.draft => Some(facturx::associated_file(receipt, e_invoice)?),
+        _ => None,
+    };
+    if options.pdfa || invoice_xml.is_some() {
+        let Some(icc_profile) = &resources.icc_profile else {
+            return Err(anyhow!("PDF/A needs the sRGB color profile, color/sRGB.icc in the data directory"));
+        };
+        bytes = pdfa::make_pdfa(&bytes, receipt, icc_profile, invoice_xml.as_ref())?;
+    }
+    return Ok(bytes);
+}
+
+// Paper other than Letter is only applied to the saved file, see
+// gen_pdf_bytes_with_options
+pub fn gen_pdf_with_options(receipt: &ReceiptInfo, resources: &PdfResources, options: &GenOptions) -> Result<PdfDocumentReference, Error> {
+    if options.page_size != PageSize::Letter {
+        return Err(anyhow!(format!("The page size {:?} is applied when saving, use gen_pdf_bytes_with_options for it", options.page_size)));
+    }
+    return Ok(render(receipt, resources, options, &budget::Reduction::default())?.0);
+}
+
+// Also returns the number of the page the document ends on, before the
+// terms and attachments
+fn render(receipt: &ReceiptInfo, resources: &PdfResources, options: &GenOptions, reduction: &budget::Reduction) -> Result<(PdfDocumentReference, u32), Error> {
     // Create and initialize document
-    // 8.5" x 11" = 215.9mm x 279.4mm = 612pt x 792pt
-    let (doc, page1, layer1) = PdfDocument::new("PDF_Document_title", Pt(612.0).into(), Pt(792.0).into(), "Layer 1");
+    // 8.5" x 11" = 215.9mm x 279.4mm = 612pt x 792pt, other paper is as tall
+    // as its proportions make it at that width
+    options.page_size.check(options.template.table_top, options.template.table_bottom)?;
+    let page_height = options.page_size.layout_height();
+    let top_offset: Mm = Pt(options.page_size.top_offset()).into();
+    let (doc, page1, layer1) = PdfDocument::new("PDF_Document_title", Pt(612.0).into(), Pt(page_height).into(), "Layer 1");
     let font_regular = doc.add_external_font(
         resources.font_regular.as_ref()
     )?;
@@ -190,6 +989,39 @@ pub fn gen_pdf(receipt: &ReceiptInfo, resources: &PdfResources) -> Result<PdfDoc
     let font_mono = doc.add_external_font(
         resources.font_mono.as_ref()
     )?;
+    let font_semibold = match &resources.font_semibold {
+        Some(font) if !reduction.required_fonts_only => doc.add_external_font(font.as_ref())?,
+        _ => font_bold.clone(),
+    };
+    let font_italic = match &resources.font_italic {
+        Some(font) if !reduction.required_fonts_only => doc.add_external_font(font.as_ref())?,
+        _ => font_regular.clone(),
+    };
+    let font_bold_italic = match &resources.font_bold_italic {
+        Some(font) if !reduction.required_fonts_only => doc.add_external_font(font.as_ref())?,
+        _ => font_bold.clone(),
+    };
+    // Text is measured in the faces it's drawn in
+    let mono_metrics = metrics::FontMetrics::new(&resources.font_mono, "mono")?;
+    let regular_metrics = metrics::FontMetrics::new(&resources.font_regular, "regular")?;
+    let italic_metrics = match &resources.font_italic {
+        Some(font) if !reduction.required_fonts_only => metrics::FontMetrics::new(font, "italic")?,
+        _ => metrics::FontMetrics::new(&resources.font_regular, "regular")?,
+    };
+    let run_fonts = textflow::RunFonts {
+        regular: &font_regular,
+        bold: &font_bold,
+        italic: &font_italic,
+        bold_italic: &font_bold_italic,
+    };
+    let font_for_weight = |weight: FontWeight| match weight {
+        FontWeight::Regular => &font_regular,
+        FontWeight::SemiBold => &font_semibold,
+        FontWeight::Bold => &font_bold,
+    };
+    let title_font = font_for_weight(options.theme.title_weight);
+    let label_font = font_for_weight(options.theme.label_weight);
+    let total_font = font_for_weight(options.theme.total_weight);
     let current_layer = doc.get_page(page1).get_layer(layer1);
     let left_margin: Mm = Pt(54.0).into();
     let right_margin: Mm = Pt(558.0).into();
@@ -210,21 +1042,43 @@ pub fn gen_pdf(receipt: &ReceiptInfo, resources: &PdfResources) -> Result<PdfDoc
         DocType::Receipt => {
             DocLayout::Receipt
         },
+        DocType::Statement => {
+            DocLayout::Statement
+        },
+        DocType::ZReport => {
+            DocLayout::ZReport
+        },
+        DocType::DeliveryTicket => {
+            DocLayout::DeliveryTicket
+        },
     };
+    // Drawn first so everything else is legible on top of it
+    if receipt.draft {
+        draw_draft_stamp(&current_layer, options.bilingual_labels, &font_bold, page_height);
+    }
+
+    // The header is placed from the top of the page
+    current_layer.save_graphics_state();
+    current_layer.set_ctm(CurTransMat::Translate(Mm(0.0), top_offset));
+
     // Add title
-    current_layer.use_text(&receipt.title, 14.0, Pt(254.0).into(), Pt(750.0).into(), &font_bold);
+    let template = &options.template;
+    let place_text = |layer: &PdfLayerReference, text: &str, placement: &TextPlacement, font: &IndirectFontRef| {
+        layer.use_text(text, placement.size, Pt(placement.x).into(), Pt(placement.y).into(), font);
+    };
+    place_text(&current_layer, &receipt.title, &template.title, title_font);
 
     // Add company header
-    current_layer.use_text(&receipt.company_name, 28.0, Pt(225.0).into(), Pt(712.0).into(), &font_bold);
-    current_layer.use_text(&receipt.company_info_line, 18.0, Pt(228.0).into(), Pt(690.0).into(), &font_regular);
+    place_text(&current_layer, &receipt.company_name, &template.company_name, title_font);
+    place_text(&current_layer, &receipt.company_info_line, &template.company_info, &font_regular);
 
     // Add logo
     let logo_transform = SvgTransform {
-        translate_x: Some(Pt(55.0)),
-        translate_y: Some(Pt(682.0)),
+        translate_x: Some(Pt(template.logo.x)),
+        translate_y: Some(Pt(template.logo.y)),
         rotate: None,
-        scale_x: Some(0.65),
-        scale_y: Some(0.65),
+        scale_x: Some(template.logo.scale),
+        scale_y: Some(template.logo.scale),
         dpi: None,
     };
     resources.logo.clone().add_to_layer(&current_layer, logo_transform);
@@ -236,35 +1090,52 @@ pub fn gen_pdf(receipt: &ReceiptInfo, resources: &PdfResources) -> Result<PdfDoc
     // current_layer.add_box(left_margin, headers_bottom_border, right_margin, headers_bottom_border + Pt(headers_size).into());
     let spacing: Mm = Pt(5.0).into();
     let font_size = 8.0;
-    let header_positions = [
-        left_margin, 
-        Pt(222.0).into(),
-        Pt(390.0).into(),
-    ];
+    // The order ID squeezes in between the date and VAT number when present
+    let has_order_id = !receipt.order_id.is_empty();
+    let header_positions: [Mm; 4] = if has_order_id {
+        [left_margin, Pt(180.0).into(), Pt(290.0).into(), Pt(390.0).into()]
+    } else {
+        [left_margin, left_margin, Pt(222.0).into(), Pt(390.0).into()]
+    };
+    let bilingual = options.bilingual_labels;
     let doctype = match receipt.doc_type {
-        DocType::Invoice => "Invoice Number:",
-        DocType::Receipt => "Receipt Number:",
-        DocType::Quote => "Quote Number:",
+        DocType::Invoice => &labels::INVOICE_NUMBER,
+        DocType::Receipt => &labels::RECEIPT_NUMBER,
+        DocType::Quote => &labels::QUOTE_NUMBER,
+        DocType::Statement => &labels::STATEMENT_NUMBER,
+        DocType::ZReport => &labels::REPORT_NUMBER,
+        DocType::DeliveryTicket => &labels::TICKET_NUMBER,
     };
     let text_bottom = headers_bottom_border + Pt(20.0).into();
-    current_layer.use_text("Date/Time:"      , font_size, header_positions[0] + spacing, text_bottom, &font_bold);
-    // current_layer.use_text("Order ID:"      , font_size, header_positions[1] + spacing, text_bottom, &font_bold);
-    current_layer.use_text("VAT Number:", font_size, header_positions[1] + spacing, text_bottom, &font_bold);
-    current_layer.use_text(doctype, font_size, header_positions[2] + spacing, text_bottom, &font_bold);
+    current_layer.use_text(labels::DATE_TIME.text(bilingual), font_size, header_positions[0] + spacing, text_bottom, label_font);
+    if has_order_id {
+        current_layer.use_text(labels::ORDER_ID.text(bilingual), font_size, header_positions[1] + spacing, text_bottom, label_font);
+    }
+    current_layer.use_text(labels::VAT_NUMBER.text(bilingual), font_size, header_positions[2] + spacing, text_bottom, label_font);
+    current_layer.use_text(doctype.text(bilingual), font_size, header_positions[3] + spacing, text_bottom, label_font);
     let font_size = 10.0;
     let text_bottom = headers_bottom_border + Pt(4.0).into();
     current_layer.use_text(&receipt.date,      font_size, header_positions[0] + spacing, text_bottom, &font_regular);
-    // current_layer.use_text(order_id,           font_size, header_positions[1] + spacing, headers_bottom_border, &font_regular);
-    current_layer.use_text(&receipt.vat_number, font_size, header_positions[1] + spacing, text_bottom, &font_regular);
-    current_layer.use_text(&receipt.doc_number,     font_size + 6.0, header_positions[2] + spacing, text_bottom - Pt(1.0).into(), &font_bold);
+    if has_order_id {
+        current_layer.use_text(&receipt.order_id, font_size, header_positions[1] + spacing, text_bottom, &font_regular);
+    }
+    current_layer.use_text(&receipt.vat_number, font_size, header_positions[2] + spacing, text_bottom, &font_regular);
+    current_layer.use_text(&receipt.doc_number,     font_size + 6.0, header_positions[3] + spacing, text_bottom - Pt(1.0).into(), title_font);
+    if receipt.revision > 0 {
+        let mut revised = format!("{} — {} {}", labels::REVISED.text(bilingual), labels::SUPERSEDES_REVISION.text(bilingual), receipt.revision - 1);
+        if !receipt.supersedes.is_empty() && receipt.supersedes != receipt.doc_number {
+            revised.push_str(&format!(" ({})", receipt.supersedes));
+        }
+        current_layer.use_text(&revised, 8.0, header_positions[3] + spacing, headers_bottom_border + Pt(32.0).into(), &font_bold);
+    }
 
     
     // Box for headers2
-    current_layer.add_box(left_margin, Pt(530.0).into(), right_margin, Pt(630.0).into());
+    current_layer.add_styled_box(left_margin, Pt(530.0).into(), right_margin, Pt(630.0).into(), &options.theme.box_style);
     //Pt 264 to 524 Leaves space for 16 possible line items per page
     // Fill out customer info
     let mut current_y: Mm = Pt(618.0).into();
-    current_layer.use_text("Sold to:", 8.0, left_margin + spacing, current_y, &font_bold);
+    current_layer.use_text(labels::SOLD_TO.text(bilingual), 8.0, left_margin + spacing, current_y, label_font);
     let line_height = Pt(13.0).into();
     receipt.customer_info.split("\n").for_each(
         |line| {
@@ -277,25 +1148,56 @@ pub fn gen_pdf(receipt: &ReceiptInfo, resources: &PdfResources) -> Result<PdfDoc
     let font_size = 12.0;
     current_y = Pt(618.0).into();
     let left_border: Mm = Into::<Mm>::into(Pt(390.0)) + spacing;
-    current_layer.use_text("Clerk:", 8.0, left_border, current_y, &font_bold);
+    current_layer.use_text(labels::CLERK.text(bilingual), 8.0, left_border, current_y, label_font);
     current_layer.use_text(&receipt.employee, font_size, left_border, current_y - Pt(16.0).into(), &font_regular);
-    current_layer.use_text("Delivery Ticket #:", 8.0, left_border, current_y - Pt(32.0).into(), &font_bold);
+    current_layer.use_text(labels::DELIVERY_TICKET.text(bilingual), 8.0, left_border, current_y - Pt(32.0).into(), label_font);
     current_layer.use_text(&receipt.delivery_tickets, font_size, left_border, current_y - Pt(48.0).into(), &font_regular);
-    current_layer.use_text("Weigh Ticket #:", 8.0, left_border, current_y - Pt(64.0).into(), &font_bold);
+    current_layer.use_text(labels::WEIGH_TICKET.text(bilingual), 8.0, left_border, current_y - Pt(64.0).into(), label_font);
     current_layer.use_text(&receipt.weigh_tickets, font_size, left_border, current_y - Pt(80.0).into(), &font_regular);
 
-    let li_top: Mm = Pt(514.0).into();
-    let li_bottom: Mm = Pt(254.0).into();
-    current_layer.add_box(left_margin, li_bottom, right_margin, li_top);
+    // Delivery schedule between the customer and the clerk
+    if let (Some(schedule), DocType::Invoice | DocType::DeliveryTicket) = (&receipt.schedule, receipt.doc_type) {
+        let x1: Mm = Pt(250.0).into();
+        let x2: Mm = Pt(382.0).into();
+        let top: Mm = Pt(624.0).into();
+        current_layer.add_styled_box(x1, Pt(536.0).into(), x2, top, &options.theme.box_style);
+        current_layer.use_text(labels::DELIVERY_SCHEDULE.text(bilingual), 8.0, x1 + spacing, top - Pt(11.0).into(), label_font);
+        let rows = [
+            (&labels::REQUESTED_DATE, &schedule.requested_date),
+            (&labels::DELIVERY_WINDOW, &schedule.window),
+            (&labels::TRUCK, &schedule.truck),
+            (&labels::DRIVER, &schedule.driver),
+        ];
+        let mut row_y = top - Pt(11.0).into();
+        for (label, value) in rows {
+            row_y -= Pt(17.0).into();
+            current_layer.use_text(label.text(bilingual), 6.5, x1 + spacing, row_y + Pt(8.0).into(), label_font);
+            current_layer.use_text(value, 9.0, x1 + spacing, row_y, &font_regular);
+        }
+    }
+
+    if !receipt.related_documents.is_empty() {
+        let references: Vec<String> = receipt.related_documents
+            .iter()
+            .map(|related| format!("{} {}", doc_type_label(related.doc_type).text(bilingual), related.doc_number))
+            .collect();
+        let text = format!("{} {}", labels::REF.text(bilingual), references.join(", "));
+        current_layer.use_text(&text, 7.0, left_margin + spacing, Pt(520.0).into(), &font_regular);
+    }
+    current_layer.restore_graphics_state();
+
+    let li_top: Mm = Into::<Mm>::into(Pt(template.table_top)) + top_offset;
+    let li_bottom: Mm = Pt(template.table_bottom).into();
 
     // vertical lines to divide line item on invoice
-    let max_desc_length;
+    // Descriptions wrap at the width of their column, templates can also
+    // limit the characters per row
+    let mut max_desc_chars = None;
     let (code_index, desc_index, uom_index, quantity_index, price_index, disc_index, total_index);
-    let li_vlines: Vec<Mm> = match layout_type {
+    let mut li_vlines: Vec<Mm> = match layout_type {
         DocLayout::Standard => {
             (code_index, desc_index, uom_index, quantity_index, price_index, disc_index, total_index) =
                     (Some(0), Some(1), Some(2), Some(3), Some(4), None, Some(5));
-            max_desc_length = 30;
             vec![
                 left_margin,      //      | Code
                 Pt(95.0).into(), // Code | Desc
@@ -308,7 +1210,6 @@ pub fn gen_pdf(receipt: &ReceiptInfo, resources: &PdfResources) -> Result<PdfDoc
         DocLayout::StandardWithDiscounts => {
             (code_index, desc_index, uom_index, quantity_index, price_index, disc_index, total_index) =
                     (Some(0), Some(1), Some(2), Some(3), Some(4), Some(5), Some(6));
-            max_desc_length = 25;
             vec![
                 left_margin,      //      | Code
                 Pt(95.0).into(), // Code | Desc
@@ -320,7 +1221,6 @@ pub fn gen_pdf(receipt: &ReceiptInfo, resources: &PdfResources) -> Result<PdfDoc
             ]
         },
         DocLayout::Receipt => {
-            max_desc_length = 90;
             (code_index, desc_index, uom_index, quantity_index, price_index, disc_index, total_index) =
                     (None, Some(0), None, None, None, None, Some(1));
             vec![
@@ -328,82 +1228,534 @@ pub fn gen_pdf(receipt: &ReceiptInfo, resources: &PdfResources) -> Result<PdfDoc
                 Pt(483.0).into(), // Desc | Total
             ]
         },
+        DocLayout::Statement => {
+            // Statement rows aren't item lines, they are drawn separately below
+            (code_index, desc_index, uom_index, quantity_index, price_index, disc_index, total_index) =
+                    (None, None, None, None, None, None, None);
+            vec![
+                left_margin,      //      | Date
+                Pt(130.0).into(), // Date | Reference
+                Pt(210.0).into(), // Reference | Description
+                Pt(400.0).into(), // Description | Amount
+                Pt(479.0).into(), // Amount | Balance
+            ]
+        },
+        DocLayout::ZReport => {
+            // Till tenders are drawn separately below
+            (code_index, desc_index, uom_index, quantity_index, price_index, disc_index, total_index) =
+                    (None, None, None, None, None, None, None);
+            vec![
+                left_margin,      //      | Tender
+                Pt(252.0).into(), // Tender | Expected
+                Pt(354.0).into(), // Expected | Counted
+                Pt(456.0).into(), // Counted | Over/Short
+            ]
+        },
+        DocLayout::DeliveryTicket => {
+            (code_index, desc_index, uom_index, quantity_index, price_index, disc_index, total_index) =
+                    (Some(0), Some(1), Some(2), Some(3), None, None, None);
+            vec![
+                left_margin,      //      | Code
+                Pt(95.0).into(), // Code | Desc
+                Pt(400.0).into(), // Desc | U/M
+                Pt(450.0).into(), // U/M | Qty
+            ]
+        },
     };
 
-    for i in 1..li_vlines.len() {
-        current_layer.add_line(li_vlines[i], li_bottom, li_vlines[i], li_top);
+    let layout_name = match layout_type {
+        DocLayout::Standard => "standard",
+        DocLayout::StandardWithDiscounts => "standard-with-discounts",
+        DocLayout::Receipt => "receipt",
+        DocLayout::Statement => "statement",
+        DocLayout::ZReport => "z-report",
+        DocLayout::DeliveryTicket => "delivery-ticket",
+    };
+    // The template moves the dividers, the left edge stays at the margin
+    if let Some(columns) = template.columns(layout_name) {
+        li_vlines.truncate(1);
+        li_vlines.extend(columns.lines.iter().map(|line| Into::<Mm>::into(Pt(*line))));
+        max_desc_chars = Some(columns.desc_chars).filter(|chars| *chars > 0);
     }
 
-    // Populate line items and subtotals
-    {
-        // Add headers
-        let font_size = 12.0;
-        let line_height = 20.0;
-        let line_height_mm = Pt(line_height).into();
+    // Right of the "Ref:" line
+    if let Some(code) = receipt.currency.as_ref().map(|currency| &currency.code).filter(|code| !code.is_empty()) {
+        let note = format!("{} {code}", labels::AMOUNTS_IN.text(bilingual));
+        let note_x = right_margin - italic_metrics.width(&note, 7.0);
+        current_layer.use_text(&note, 7.0, note_x, li_top + Pt(6.0).into(), &font_italic);
+    }
+
+    // The line number column is carved out of the description column: every
+    // column left of the description moves over to make room.
+    let number_index = if options.line_numbers && desc_index.is_some() {
+        let number_width: Mm = Pt(20.0).into();
+        for line in &mut li_vlines[1..=desc_index.unwrap_or(0)] {
+            *line += number_width;
+        }
+        li_vlines.insert(1, left_margin + number_width);
+        Some(0)
+    } else {
+        None
+    };
+    let shift = number_index.map_or(0, |_| 1);
+    let code_index = code_index.map(|i| i + shift);
+    let desc_index = desc_index.map(|i| i + shift);
+    let uom_index = uom_index.map(|i| i + shift);
+    let quantity_index = quantity_index.map(|i| i + shift);
+    let price_index = price_index.map(|i| i + shift);
+    let disc_index = disc_index.map(|i| i + shift);
+    let total_index = total_index.map(|i| i + shift);
+
+    // So is the product image column, right of the line numbers
+    let has_images = options.product_images && receipt.item_lines.iter().any(|line| !line.image.is_empty());
+    let image_index = match (has_images, desc_index) {
+        (true, Some(desc_index)) => {
+            let image_width: Mm = Pt(thumbnails::THUMBNAIL_SIZE + 4.0).into();
+            let column = shift;
+            let edge = li_vlines[column];
+            for line in &mut li_vlines[column + 1..=desc_index] {
+                *line += image_width;
+            }
+            li_vlines.insert(column + 1, edge + image_width);
+            Some(column)
+        },
+        _ => None,
+    };
+    let shift = image_index.map_or(0, |_| 1);
+    let code_index = code_index.map(|i| i + shift);
+    let desc_index = desc_index.map(|i| i + shift);
+    let uom_index = uom_index.map(|i| i + shift);
+    let quantity_index = quantity_index.map(|i| i + shift);
+    let price_index = price_index.map(|i| i + shift);
+    let disc_index = disc_index.map(|i| i + shift);
+    let total_index = total_index.map(|i| i + shift);
+
+
+    // Column headers get the French translation on a second, smaller line in
+    // bilingual mode since there is no room for both side by side
+    let add_column_header = |layer: &PdfLayerReference, label: &labels::Label, x: Mm, y: Mm| {
+        match label.second_line(bilingual) {
+            Some(second_line) => {
+                layer.use_text(label.en, 9.0, x, y + Pt(6.0).into(), &font_regular);
+                layer.use_text(second_line, 8.0, x, y - Pt(2.5).into(), &font_regular);
+            },
+            None => layer.use_text(label.en, 12.0, x, y, &font_regular),
+        }
+    };
+    let column_header_width = |label: &labels::Label| -> Mm {
+        return match label.second_line(bilingual) {
+            Some(second_line) => wider(regular_metrics.width(label.en, 9.0), regular_metrics.width(second_line, 8.0)),
+            None => regular_metrics.width(label.en, 12.0),
+        };
+    };
+
+    // Amounts follow the currency of the document, or GenOptions::amount_format
+    // without one
+    let currency = receipt.currency.clone().unwrap_or_default();
+    let amount_format = match &receipt.currency {
+        Some(currency) => currency.amount_format(options.amount_format.negative),
+        None => options.amount_format,
+    };
+    // Lines up the decimal points
+    let format_quantity = |line: &ItemLine| -> String {
+        let quantity = options.uom_catalog.format_quantity(&line.uom, &line.quantity);
+        return match quantity.split_once('.') {
+            Some((whole, fraction)) => format!("{whole:>7}.{fraction:<2}"),
+            None if options.uom_catalog.precision(&line.uom).is_some() => format!("{quantity:>7}   "),
+            None => format!("{quantity:>10}"),
+        };
+    };
+
+    // Characters of room for the amounts of the item table, see fit_columns
+    let mut item_amount_chars = money::AMOUNT_WIDTH;
+    if let (true, Some(desc_index)) = (options.fit_columns, desc_index) {
+        let font_size = 8.0;
         let spacing: Mm = Pt(5.0).into();
-        let mut bottom_border = li_top - line_height_mm;
-        let mut cursor_y = bottom_border + spacing;
-        current_layer.add_line(left_margin, bottom_border, right_margin, bottom_border);
-        if let Some(code_index) = code_index {         current_layer.use_text("Code"       , font_size, li_vlines[code_index] + spacing, cursor_y, &font_regular) };
-        if let Some(desc_index) = desc_index {         current_layer.use_text("Description", font_size, li_vlines[desc_index] + spacing, cursor_y, &font_regular) };
-        if let Some(uom_index) = uom_index {           current_layer.use_text("U/M"        , font_size, li_vlines[uom_index] + spacing, cursor_y, &font_regular) };
-        if let Some(quantity_index) = quantity_index { current_layer.use_text("Quantity"   , font_size, li_vlines[quantity_index] + spacing, cursor_y, &font_regular) };
-        if let Some(price_index) = price_index {       current_layer.use_text("Unit Price" , font_size, li_vlines[price_index] + spacing, cursor_y, &font_regular) };
-        if let Some(disc_index) = disc_index {         current_layer.use_text("Discount"   , font_size, li_vlines[disc_index] + spacing, cursor_y, &font_regular) };
-        if let Some(total_index) = total_index {       current_layer.use_text("Total"      , font_size, li_vlines[total_index] + spacing, cursor_y, &font_regular) };
+        let symbol_chars = currency.symbol.chars().count() + (currency.symbol_position == SymbolPosition::After) as usize;
+        item_amount_chars = receipt.item_lines.iter()
+            .flat_map(|line| [
+                Some(money::normalize_unit_price(&line.unit_price)),
+                line.discount.as_ref().map(Discount::to_string),
+                line.amount.map(|amount| amount.to_string()),
+            ])
+            .flatten()
+            .map(|amount| amount_format.display(&amount).chars().count() + symbol_chars)
+            .chain([labels::TBD.text(bilingual).chars().count(), labels::CONTRACT.en.chars().count()])
+            .max()
+            .unwrap_or(0);
+        let amount_width = mono_metrics.width(&"0".repeat(item_amount_chars), font_size);
+        let widest = |texts: Vec<String>, size: f64| texts.iter().map(|text| mono_metrics.width(text, size)).fold(Mm(0.0), wider);
+        let columns = [
+            (uom_index, &labels::UOM, widest(receipt.item_lines.iter().map(|line| line.uom.clone()).collect(), font_size)),
+            (quantity_index, &labels::QUANTITY, widest(receipt.item_lines.iter().map(format_quantity).collect(), font_size)),
+            (price_index, &labels::UNIT_PRICE, amount_width),
+            (disc_index, &labels::DISCOUNT, amount_width),
+            (total_index, &labels::TOTAL, amount_width),
+        ];
+        let mut widths = vec![Mm(0.0); li_vlines.len()];
+        for (index, label, content) in columns {
+            let Some(index) = index else { continue };
+            widths[index] = wider(column_header_width(label), content) + spacing * 2.0;
+        }
+        if options.mark_price_overrides {
+            if let Some(index) = price_index.or(total_index) {
+                widths[index] += mono_metrics.width("*", font_size);
+            }
+        }
+        // Only when the description keeps at least an inch
+        let needed = widths[desc_index + 1..].iter().fold(Mm(0.0), |sum, width| sum + *width);
+        if right_margin - li_vlines[desc_index] - needed >= Pt(72.0).into() {
+            let mut right = right_margin;
+            for index in (desc_index + 1..li_vlines.len()).rev() {
+                right -= widths[index];
+                li_vlines[index] = right;
+            }
+        }
+    }
+    let column_right = |index: usize| -> Mm {
+        return li_vlines.get(index + 1).copied().unwrap_or(right_margin);
+    };
+    // After the line numbers and images took their room out of it
+    if let Some(desc_index) = desc_index {
+        check_desc_width(column_right(desc_index) - li_vlines[desc_index], &mono_metrics)?;
+    }
+
+    // The symbol at the left of the room for `chars` characters and the
+    // amount right aligned in it, or the symbol after the right aligned
+    // amount. Returns where the amount starts and its width.
+    let use_amount_in = |layer: &PdfLayerReference, amount: &str, font_size: f64, x: Mm, y: Mm, font: &IndirectFontRef, chars: usize| -> (Mm, Mm) {
+        let text = amount_format.display(amount);
+        let right = x + mono_metrics.width(&"0".repeat(chars), font_size);
+        let width = mono_metrics.width(&text, font_size);
+        if amount_format.is_red(amount) {
+            layer.save_graphics_state();
+            layer.set_fill_color(options.theme.negative_color.to_pdf());
+        }
+        let text_x = match currency.symbol_position {
+            SymbolPosition::Before => {
+                if !currency.symbol.is_empty() {
+                    layer.use_text(&currency.symbol, font_size, x, y, font);
+                }
+                right - width
+            },
+            SymbolPosition::After => {
+                let text_x = right - mono_metrics.width(&format!("{text} {}", currency.symbol), font_size);
+                layer.use_text(&currency.symbol, font_size, right - mono_metrics.width(&currency.symbol, font_size), y, font);
+                text_x
+            },
+        };
+        layer.use_text(&text, font_size, text_x, y, font);
+        if amount_format.is_red(amount) {
+            layer.restore_graphics_state();
+        }
+        return (text_x, width);
+    };
+    // Amounts always have two decimals, only unit prices keep more
+    let use_amount = |layer: &PdfLayerReference, amount: &str, font_size: f64, x: Mm, y: Mm, font: &IndirectFontRef| -> (Mm, Mm) {
+        return use_amount_in(layer, &money::two_decimals(amount), font_size, x, y, font, money::AMOUNT_WIDTH);
+    };
+
+    // The table outline goes on top of the row shading, so it's drawn once
+    // the rows of a page are done
+    let draw_table_frame = |layer: &PdfLayerReference, top: Mm| {
+        layer.add_styled_box(left_margin, li_bottom, right_margin, top, &options.theme.box_style);
+        for line in &li_vlines[1..] {
+            layer.add_styled_line(*line, li_bottom, *line, top, options.theme.rule_style);
+        }
+    };
+
+    // Closes the table of a full page and starts the next page with enough
+    // of the header to tell which document it's from. Returns the new page
+    // and the top of its table.
+    let continue_on_new_page = |current_layer: &PdfLayerReference, table_top: Mm| -> (PdfLayerReference, Mm) {
+        draw_table_frame(current_layer, table_top);
+        current_layer.use_text(labels::CONTINUED.text(bilingual), 8.0, right_margin - Pt(80.0).into(), li_bottom - Pt(12.0).into(), &font_italic);
+        let (page, layer) = doc.add_page(Pt(612.0).into(), Pt(page_height).into(), "Layer 1");
+        let current_layer = doc.get_page(page).get_layer(layer);
+        if receipt.draft {
+            draw_draft_stamp(&current_layer, bilingual, &font_bold, page_height);
+        }
+        let header_y = Into::<Mm>::into(Pt(744.0)) + top_offset;
+        current_layer.use_text(&receipt.company_name, 14.0, left_margin, header_y, title_font);
+        let doc_number = format!("{} {} ({})", doc_type_label(receipt.doc_type).text(bilingual), receipt.doc_number, labels::CONTINUED.text(bilingual));
+        current_layer.use_text(&doc_number, 10.0, Pt(330.0).into(), header_y, &font_regular);
+        return (current_layer, Into::<Mm>::into(Pt(CONTINUATION_TOP)) + top_offset);
+    };
+    // Column headers of the statement and Z-report tables, one per column.
+    // Returns the bottom of the first row.
+    let draw_column_headers = |current_layer: &PdfLayerReference, top: Mm, headers: &[&labels::Label]| -> Mm {
+        let line_height_mm: Mm = Pt(20.0).into();
+        let spacing: Mm = Pt(5.0).into();
+        let bottom_border = top - line_height_mm;
+        if let Some(fill) = options.theme.header_fill {
+            current_layer.add_filled_box(left_margin, bottom_border, right_margin, top, fill);
+        }
+        current_layer.add_styled_line(left_margin, bottom_border, right_margin, bottom_border, options.theme.rule_style);
+        for (i, header) in headers.iter().enumerate() {
+            add_column_header(current_layer, header, li_vlines[i] + spacing, bottom_border + spacing);
+        }
+        return bottom_border - line_height_mm;
+    };
+
+    // Populate line items and subtotals. Lines which don't fit above the
+    // totals continue on further pages, and the rest of the document goes on
+    // the last one. So do statement transactions and Z-report tenders.
+    let (mut current_layer, mut table_top, mut pages) = {
+        let mut current_layer = current_layer.clone();
+        let mut pages = vec![current_layer.clone()];
+        let mut table_top = li_top;
+        let spacing: Mm = Pt(5.0).into();
+        // Returns the bottom of the first row
+        let draw_table_header = |current_layer: &PdfLayerReference, top: Mm| -> Mm {
+            let line_height_mm: Mm = Pt(20.0).into();
+            let bottom_border = top - line_height_mm;
+            let cursor_y = bottom_border + spacing;
+            if let Some(fill) = options.theme.header_fill {
+                current_layer.add_filled_box(left_margin, bottom_border, right_margin, top, fill);
+            }
+            current_layer.add_styled_line(left_margin, bottom_border, right_margin, bottom_border, options.theme.rule_style);
+            if let Some(number_index) = number_index {     add_column_header(current_layer, &labels::LINE_NUMBER, li_vlines[number_index] + spacing, cursor_y) };
+            if let Some(code_index) = code_index {         add_column_header(current_layer, &labels::CODE, li_vlines[code_index] + spacing, cursor_y) };
+            if let Some(desc_index) = desc_index {         add_column_header(current_layer, &labels::DESCRIPTION, li_vlines[desc_index] + spacing, cursor_y) };
+            if let Some(uom_index) = uom_index {           add_column_header(current_layer, &labels::UOM, li_vlines[uom_index] + spacing, cursor_y) };
+            if let Some(quantity_index) = quantity_index { add_column_header(current_layer, &labels::QUANTITY, li_vlines[quantity_index] + spacing, cursor_y) };
+            if let Some(price_index) = price_index {       add_column_header(current_layer, &labels::UNIT_PRICE, li_vlines[price_index] + spacing, cursor_y) };
+            if let Some(disc_index) = disc_index {         add_column_header(current_layer, &labels::DISCOUNT, li_vlines[disc_index] + spacing, cursor_y) };
+            if let Some(total_index) = total_index {       add_column_header(current_layer, &labels::TOTAL, li_vlines[total_index] + spacing, cursor_y) };
+            return bottom_border - line_height_mm;
+        };
 
         // Add content
-        bottom_border -= line_height_mm;
-        cursor_y = bottom_border + spacing;
+        let mut bottom_border = draw_table_header(&current_layer, table_top);
+        let mut cursor_y = bottom_border + spacing;
         let font_size = 8.0;
         let line_height_mm: Mm = Pt(15.0).into();
-        for line in &receipt.item_lines {
-            let desc_lines = split_into_lines(&line.description, max_desc_length);            
+        let mut lines_on_page = 0;
+        for (line_number, line) in receipt.item_lines.iter().enumerate() {
+            let desc_lines = match desc_index {
+                Some(desc_index) => {
+                    let width = column_right(desc_index) - li_vlines[desc_index] - spacing * 2.0;
+                    mono_metrics.wrap(&line.description, font_size, width, max_desc_chars)
+                },
+                None => Vec::new(),
+            };
             let item_line_font = &font_mono;
+            let converted = match (options.dual_units, quantity_index) {
+                (true, Some(_)) => options.uom_catalog.convert(&line.uom, &line.quantity),
+                _ => None,
+            };
+            let thumbnail = match image_index {
+                Some(_) if !line.image.is_empty() => resources.thumbnails.get(&line.image)?,
+                _ => None,
+            };
+            let price_breaks = match (desc_index, receipt.doc_type) {
+                (Some(_), DocType::Quote) if options.price_breaks && !options.hide_unit_prices && !line.price_breaks.is_empty() => {
+                    Some(catalog::describe_price_breaks(&line.price_breaks, &amount_format, &currency))
+                },
+                _ => None,
+            };
+            let rental = match (desc_index, &line.rental) {
+                (Some(_), Some(rental)) => Some(rental.describe(bilingual, &amount_format, &currency)?),
+                _ => None,
+            };
+            // Thumbnails take two rows, the rental period and the price breaks
+            // get a row of their own
+            let row_lines = desc_lines.len().max(if converted.is_some() || thumbnail.is_some() { 2 } else { 1 })
+                + rental.is_some() as usize + price_breaks.is_some() as usize;
+            let rental_row = row_lines - 1 - price_breaks.is_some() as usize;
+            // A line too long for a whole page is left to run over the totals
+            if bottom_border - line_height_mm * (row_lines as f64 - 1.0) < li_bottom && lines_on_page > 0 {
+                (current_layer, table_top) = continue_on_new_page(&current_layer, table_top);
+                pages.push(current_layer.clone());
+                bottom_border = draw_table_header(&current_layer, table_top);
+                cursor_y = bottom_border + spacing;
+                lines_on_page = 0;
+            }
+            lines_on_page += 1;
+            if let (Some(fill), true) = (options.theme.row_stripe_fill, line_number % 2 == 1) {
+                let row_lines = row_lines as f64;
+                current_layer.add_filled_box(left_margin, bottom_border - line_height_mm * (row_lines - 1.0), right_margin, bottom_border + line_height_mm, fill);
+            }
 
+            if let Some(number_index) = number_index {
+                current_layer.use_text(format!("{:>3}", line_number + 1), font_size, li_vlines[number_index] + spacing, cursor_y, item_line_font);
+            }
+            if let (Some(image_index), Some(thumbnail)) = (image_index, &thumbnail) {
+                let top = bottom_border + line_height_mm - Pt(2.0).into();
+                thumbnails::draw_thumbnail(&current_layer, thumbnail, li_vlines[image_index] + Pt(2.0).into(), top);
+            }
             if let Some(code_index) = code_index {
                 current_layer.use_text(&line.code, font_size, li_vlines[code_index] + spacing, cursor_y, item_line_font);
             }
-            if let Some(desc_index) = desc_index {
-                current_layer.use_text(&desc_lines[0], font_size, li_vlines[desc_index] + spacing, cursor_y, item_line_font);
+            if let (Some(desc_index), Some(first_line)) = (desc_index, desc_lines.first()) {
+                current_layer.use_text(first_line, font_size, li_vlines[desc_index] + spacing, cursor_y, item_line_font);
             }
             if let Some(uom_index) = uom_index {
                 current_layer.use_text(&line.uom, font_size, li_vlines[uom_index] + spacing, cursor_y, item_line_font);
             }
             if let Some(quantity_index) = quantity_index {
-                let qty = if line.uom.eq("EA") && line.quantity.ends_with(".00") { 
-                    format!("{:>7}   ", &line.quantity[..line.quantity.len()-3])
-                } else {
-                    format!("{:>10}", line.quantity)
-                };
-                current_layer.use_text(&qty, font_size, li_vlines[quantity_index] + spacing, cursor_y, item_line_font);
+                current_layer.use_text(format_quantity(line), font_size, li_vlines[quantity_index] + spacing, cursor_y, item_line_font);
             }
-            if let Some(price_index) = price_index {
-                current_layer.use_text(&lpad!(&line.unit_price), font_size, li_vlines[price_index] + spacing, cursor_y, item_line_font);
+            // Unpriced lines of a draft
+            let to_be_determined = receipt.draft && line.amount.is_none() && !line.quantity.is_empty();
+            if let (Some(price_index), true) = (price_index, to_be_determined) {
+                current_layer.use_text(format!("{:>item_amount_chars$}", labels::TBD.text(bilingual)), font_size, li_vlines[price_index] + spacing, cursor_y, item_line_font);
+            } else if let (Some(price_index), true) = (price_index, options.hide_unit_prices) {
+                current_layer.use_text(format!("{:>item_amount_chars$}", labels::CONTRACT.en), font_size, li_vlines[price_index] + spacing, cursor_y, item_line_font);
+            } else if let Some(price_index) = price_index {
+                let price_x = li_vlines[price_index] + spacing;
+                let price = money::normalize_unit_price(&line.unit_price);
+                let (price_x, price_width) = use_amount_in(&current_layer, &price, font_size, price_x, cursor_y, item_line_font, item_amount_chars);
+                // The original price no longer applies once a line is discounted
+                if line.discount.is_some() && !line.unit_price.is_empty() {
+                    current_layer.add_strikethrough(price_x, cursor_y, price_width, font_size);
+                }
             }
             if let Some(disc_index) = disc_index {
-                if let Some(discount) = &line.discount {
-                    current_layer.use_text(&lpad!(discount), font_size, li_vlines[disc_index] + spacing, cursor_y, item_line_font);
+                match &line.discount {
+                    // The discount would give the list price away
+                    Some(_) if options.hide_unit_prices => {
+                        current_layer.use_text(format!("{:>item_amount_chars$}", labels::CONTRACT.en), font_size, li_vlines[disc_index] + spacing, cursor_y, item_line_font);
+                    },
+                    Some(discount) => {
+                        use_amount_in(&current_layer, &discount.to_string(), font_size, li_vlines[disc_index] + spacing, cursor_y, item_line_font, item_amount_chars);
+                    },
+                    None => {},
                 }
             }
-            if let Some(total_index) = total_index {
-                current_layer.use_text(&lpad!(&line.amount), font_size, li_vlines[total_index] + spacing, cursor_y, item_line_font);
+            if let (Some(total_index), true) = (total_index, to_be_determined) {
+                current_layer.use_text(format!("{:>item_amount_chars$}", labels::TBD.text(bilingual)), font_size, li_vlines[total_index] + spacing, cursor_y, item_line_font);
+            } else if let (Some(total_index), Some(amount)) = (total_index, line.amount) {
+                use_amount_in(&current_layer, &amount.to_string(), font_size, li_vlines[total_index] + spacing, cursor_y, item_line_font, item_amount_chars);
             }
             if line.taxable {
                 current_layer.use_text("T", font_size, right_margin + spacing, cursor_y, item_line_font)
             }
+            if options.mark_price_overrides && line.price_override {
+                // Right after the unit price, or the line total on receipts
+                if let Some(index) = price_index.or(total_index) {
+                    let marker_x = li_vlines[index] + spacing + mono_metrics.width(&"0".repeat(item_amount_chars), font_size);
+                    current_layer.use_text("*", font_size, marker_x, cursor_y, item_line_font);
+                }
+            }
 
-            // Add additional description lines
-            if let Some(desc_index) = desc_index {
-                for i in 1..desc_lines.len() {
-                    bottom_border -= line_height_mm;
-                    cursor_y = bottom_border + spacing;
-                    current_layer.use_text(&desc_lines[i], font_size, li_vlines[desc_index] + spacing, cursor_y, &font_mono);
+            // Add additional description lines, and the converted quantity
+            // below the quantity
+            for i in 1..row_lines {
+                bottom_border -= line_height_mm;
+                cursor_y = bottom_border + spacing;
+                if let (Some(desc_index), Some(desc_line)) = (desc_index, desc_lines.get(i)) {
+                    current_layer.use_text(desc_line, font_size, li_vlines[desc_index] + spacing, cursor_y, &font_mono);
+                }
+                if let (Some(quantity_index), Some(converted), 1) = (quantity_index, &converted, i) {
+                    current_layer.use_text(format!("({converted})"), 7.0, li_vlines[quantity_index] + spacing, cursor_y, &font_mono);
+                }
+                if let (Some(desc_index), Some(rental), true) = (desc_index, &rental, i == rental_row) {
+                    current_layer.use_text(rental, 7.0, li_vlines[desc_index] + spacing, cursor_y, &font_mono);
+                }
+                if let (Some(desc_index), Some(price_breaks), true) = (desc_index, &price_breaks, i + 1 == row_lines) {
+                    current_layer.use_text(price_breaks, 7.0, li_vlines[desc_index] + spacing, cursor_y, &font_mono);
                 }
             }
             bottom_border -= line_height_mm;
             cursor_y = bottom_border + spacing;
         }
+        (current_layer, table_top, pages)
+    };
+
+    // Populate statement transactions with their running balance
+    if let (DocLayout::Statement, Some(statement)) = (&layout_type, &receipt.statement) {
+        let spacing: Mm = Pt(5.0).into();
+        let headers = [&labels::DATE, &labels::REFERENCE, &labels::DESCRIPTION, &labels::AMOUNT, &labels::BALANCE];
+        let mut bottom_border = draw_column_headers(&current_layer, table_top, &headers);
+        let mut cursor_y = bottom_border + spacing;
+        let font_size = 8.0;
+        let line_height_mm: Mm = Pt(15.0).into();
+        if let Some(opening_balance) = statement.opening_balance {
+            current_layer.use_text(labels::BALANCE_FORWARD.text(bilingual), font_size, li_vlines[2] + spacing, cursor_y, &font_mono);
+            use_amount(&current_layer, &opening_balance.to_string(), font_size, li_vlines[4] + spacing, cursor_y, &font_mono);
+            bottom_border -= line_height_mm;
+            cursor_y = bottom_border + spacing;
+        }
+        let first_row = if statement.opening_balance.is_none() { 0 } else { 1 };
+        let mut rows_on_page = first_row;
+        check_desc_width(li_vlines[3] - li_vlines[2], &mono_metrics)?;
+        for (row, line) in statement.transactions.iter().enumerate() {
+            let desc_lines = mono_metrics.wrap(&line.description, font_size, li_vlines[3] - li_vlines[2] - spacing * 2.0, max_desc_chars);
+            let row_lines = desc_lines.len().max(1) as f64;
+            if bottom_border - line_height_mm * (row_lines - 1.0) < li_bottom && rows_on_page > 0 {
+                (current_layer, table_top) = continue_on_new_page(&current_layer, table_top);
+                pages.push(current_layer.clone());
+                bottom_border = draw_column_headers(&current_layer, table_top, &headers);
+                cursor_y = bottom_border + spacing;
+                rows_on_page = 0;
+            }
+            rows_on_page += 1;
+            if let (Some(fill), true) = (options.theme.row_stripe_fill, (first_row + row) % 2 == 1) {
+                current_layer.add_filled_box(left_margin, bottom_border - line_height_mm * (row_lines - 1.0), right_margin, bottom_border + line_height_mm, fill);
+            }
+            current_layer.use_text(&line.date, font_size, li_vlines[0] + spacing, cursor_y, &font_mono);
+            current_layer.use_text(&line.reference, font_size, li_vlines[1] + spacing, cursor_y, &font_mono);
+            if let Some(first_line) = desc_lines.first() {
+                current_layer.use_text(first_line, font_size, li_vlines[2] + spacing, cursor_y, &font_mono);
+            }
+            use_amount(&current_layer, &line.amount.to_string(), font_size, li_vlines[3] + spacing, cursor_y, &font_mono);
+            use_amount(&current_layer, &line.balance.map(|balance| balance.to_string()).unwrap_or_default(), font_size, li_vlines[4] + spacing, cursor_y, &font_mono);
+            for desc_line in desc_lines.iter().skip(1) {
+                bottom_border -= line_height_mm;
+                cursor_y = bottom_border + spacing;
+                current_layer.use_text(desc_line, font_size, li_vlines[2] + spacing, cursor_y, &font_mono);
+            }
+            bottom_border -= line_height_mm;
+            cursor_y = bottom_border + spacing;
+        }
+    }
+
+    // Reconcile each tender of the till against what was counted
+    if let (DocLayout::ZReport, Some(z_report)) = (&layout_type, &receipt.z_report) {
+        let spacing: Mm = Pt(5.0).into();
+        let headers = [&labels::TENDER, &labels::EXPECTED, &labels::COUNTED, &labels::OVER_SHORT];
+        let mut bottom_border = draw_column_headers(&current_layer, table_top, &headers);
+        let mut cursor_y = bottom_border + spacing;
+        let font_size = 10.0;
+        let line_height_mm: Mm = Pt(15.0).into();
+        let (mut expected_total, mut counted_total) = (Money::ZERO, Money::ZERO);
+        let mut rows_on_page = 0;
+        // The total row is kept with the last tender
+        let tender_count = z_report.tenders.len();
+        for (row, tender) in z_report.tenders.iter().enumerate() {
+            let kept_rows = if row + 1 == tender_count { 1.0 } else { 0.0 };
+            if bottom_border - line_height_mm * kept_rows < li_bottom && rows_on_page > 0 {
+                (current_layer, table_top) = continue_on_new_page(&current_layer, table_top);
+                pages.push(current_layer.clone());
+                bottom_border = draw_column_headers(&current_layer, table_top, &headers);
+                cursor_y = bottom_border + spacing;
+                rows_on_page = 0;
+            }
+            rows_on_page += 1;
+            let (expected, counted) = (tender.expected, tender.counted);
+            expected_total = expected_total.checked_add(expected)?;
+            counted_total = counted_total.checked_add(counted)?;
+            if let (Some(fill), true) = (options.theme.row_stripe_fill, row % 2 == 1) {
+                current_layer.add_filled_box(left_margin, bottom_border, right_margin, bottom_border + line_height_mm, fill);
+            }
+            current_layer.use_text(&tender.name, font_size, li_vlines[0] + spacing, cursor_y, &font_regular);
+            use_amount(&current_layer, &expected.to_string(), font_size, li_vlines[1] + spacing, cursor_y, &font_mono);
+            use_amount(&current_layer, &counted.to_string(), font_size, li_vlines[2] + spacing, cursor_y, &font_mono);
+            use_amount(&current_layer, &counted.checked_sub(expected)?.signed(), font_size, li_vlines[3] + spacing, cursor_y, &font_mono);
+            bottom_border -= line_height_mm;
+            cursor_y = bottom_border + spacing;
+        }
+        current_layer.add_line(left_margin, bottom_border + line_height_mm, right_margin, bottom_border + line_height_mm);
+        current_layer.use_text(labels::TOTAL.text(bilingual), font_size, li_vlines[0] + spacing, cursor_y, total_font);
+        use_amount(&current_layer, &expected_total.to_string(), font_size, li_vlines[1] + spacing, cursor_y, &font_mono);
+        use_amount(&current_layer, &counted_total.to_string(), font_size, li_vlines[2] + spacing, cursor_y, &font_mono);
+        use_amount(&current_layer, &counted_total.checked_sub(expected_total)?.signed(), font_size, li_vlines[3] + spacing, cursor_y, &font_mono);
+    }
+
+    draw_table_frame(&current_layer, table_top);
+    if options.mark_price_overrides && receipt.item_lines.iter().any(|line| line.price_override) {
+        current_layer.use_text(labels::PRICE_OVERRIDE_NOTE.text(bilingual), 7.0, left_margin + Pt(5.0).into(), li_bottom - Pt(10.0).into(), &font_italic);
     }
 
     // add totals below table on right side
@@ -420,12 +1772,59 @@ pub fn gen_pdf(receipt: &ReceiptInfo, resources: &PdfResources) -> Result<PdfDoc
             continue;
         }
         let font = if amount.name.eq("Total:") {
-            &font_bold
+            total_font
         } else {
             &font_regular
         };
         current_layer.use_text(&amount.name, font_size, x1, current_y, font);
-        current_layer.use_text(&lpad!(amount.value), 10.0, x2, current_y, &font_mono);
+        use_amount(&current_layer, &amount.value.to_string(), 10.0, x2, current_y, &font_mono);
+        if amount.name.eq("Total:") {
+            current_layer.add_underline(x2, current_y, mono_metrics.width(&"0".repeat(money::AMOUNT_WIDTH), 10.0), 10.0);
+        }
+    }
+
+    // Amount due stands out below the totals
+    if let Some(amount_due) = receipt.amount_due {
+        current_y -= line_height * 1.5;
+        let label_x = if bilingual { x1 - Pt(80.0).into() } else { x1 - Pt(20.0).into() };
+        current_layer.use_text(labels::AMOUNT_DUE.text(bilingual), 12.0, label_x, current_y, total_font);
+        use_amount(&current_layer, &amount_due.to_string(), 12.0, x2 - Pt(10.0).into(), current_y, &font_mono);
+    }
+
+    // Transaction counts of the till below the totals
+    if let Some(z_report) = &receipt.z_report {
+        current_y -= line_height * 1.5;
+        current_layer.use_text(labels::TRANSACTIONS.text(bilingual), font_size, x1, current_y, total_font);
+        current_y -= Pt(4.0).into();
+        current_layer.add_line(x1, current_y, right_margin, current_y);
+        for count in &z_report.transaction_counts {
+            current_y -= line_height;
+            current_layer.use_text(&count.name, 10.0, x1, current_y, &font_regular);
+            current_layer.use_text(format!("{:>12}", count.value), 10.0, x2, current_y, &font_mono);
+        }
+    }
+
+    // Progress billing summary below the totals
+    if let Some(progress) = &receipt.progress_billing {
+        // The French labels are a lot longer
+        let (x1, label_size) = if bilingual {
+            (last_x - Pt(200.0).into(), 7.5)
+        } else {
+            (last_x - Pt(115.0).into(), 9.0)
+        };
+        current_y -= line_height * 1.5;
+        current_layer.use_text(labels::PROGRESS_BILLING.text(bilingual), font_size, x1, current_y, total_font);
+        current_y -= Pt(4.0).into();
+        current_layer.add_line(x1, current_y, right_margin, current_y);
+        let (rows, percent_complete) = progress.summary()?;
+        for (name, value) in rows {
+            current_y -= line_height;
+            current_layer.use_text(name.text(bilingual), label_size, x1, current_y, &font_regular);
+            use_amount(&current_layer, &value, 10.0, x2, current_y, &font_mono);
+        }
+        current_y -= line_height;
+        current_layer.use_text(labels::PERCENT_COMPLETE.text(bilingual), label_size, x1, current_y, &font_regular);
+        current_layer.use_text(format!("{:>11.1}%", percent_complete), 10.0, x2, current_y, &font_mono);
     }
 
     // Add tenders below table on left side
@@ -433,57 +1832,239 @@ pub fn gen_pdf(receipt: &ReceiptInfo, resources: &PdfResources) -> Result<PdfDoc
     let x1 = left_margin + spacing;
     let x2: Mm = Pt(200.0).into();
     current_y -= line_height;
-    current_layer.use_text("Tender", font_size, x1, current_y, &font_regular);
-    current_y -= Pt(4.0).into();
-    current_layer.add_line(x1, current_y, x2 + Pt(80.0).into(), current_y);
-    for amount in &receipt.payments {
-        current_y -= line_height;
-        current_layer.use_text(&amount.name, 10.0, x1, current_y, &font_regular);
-        current_layer.use_text(&lpad!(amount.value), 10.0, x2, current_y, &font_mono);
+    if let Some(z_report) = &receipt.z_report {
+        // Z reports show the clerk breakdown here instead of tenders
+        let x_transactions: Mm = Pt(150.0).into();
+        current_layer.use_text(labels::CLERK_COLUMN.text(bilingual), font_size, x1, current_y, &font_regular);
+        current_layer.use_text(labels::TRANSACTIONS.text(bilingual), 8.0, x_transactions, current_y, &font_regular);
+        current_y -= Pt(4.0).into();
+        current_layer.add_line(x1, current_y, x2 + Pt(80.0).into(), current_y);
+        for clerk in &z_report.clerks {
+            current_y -= line_height;
+            current_layer.use_text(&clerk.clerk, 10.0, x1, current_y, &font_regular);
+            current_layer.use_text(format!("{:>6}", clerk.transactions), 10.0, x_transactions, current_y, &font_mono);
+            use_amount(&current_layer, &clerk.sales.to_string(), 10.0, x2, current_y, &font_mono);
+        }
+    } else if receipt.doc_type == DocType::DeliveryTicket {
+        // Nothing is paid on a delivery ticket, the site takes the space
+        if let Some(site) = &receipt.delivery_site {
+            site.draw(&current_layer, bilingual, label_font, &font_regular)?;
+        }
+    } else {
+        current_layer.use_text(labels::TENDER.text(bilingual), font_size, x1, current_y, &font_regular);
+        current_y -= Pt(4.0).into();
+        current_layer.add_line(x1, current_y, x2 + Pt(80.0).into(), current_y);
+        for amount in &receipt.payments {
+            current_y -= line_height;
+            current_layer.use_text(&amount.name, 10.0, x1, current_y, &font_regular);
+            use_amount(&current_layer, &amount.value.to_string(), 10.0, x2, current_y, &font_mono);
+        }
+        if let Some(change_due) = receipt.change_due {
+            let label = if receipt.payments.iter().any(|tender| is_cash(&tender.name)) {
+                &labels::CHANGE_DUE
+            } else {
+                &labels::REFUND_DUE
+            };
+            current_y -= line_height;
+            current_layer.use_text(label.text(bilingual), 10.0, x1, current_y, total_font);
+            use_amount(&current_layer, &change_due.to_string(), 10.0, x2, current_y, &font_mono);
+        }
     }
 
-    //Pt 54 to 94 for signature box 
-    current_layer.add_box(
-        Pt(350.0).into(), Pt(84.0).into(), right_margin, Pt(84.0).into()
-    );
-    // Add signature line
-    current_layer.use_text("Received By", 10.0, Pt(350.0).into(), Pt(74.0).into(), &font_regular);
+    // Compare against the quote this invoice was made from
+    if let (DocType::Invoice, Some(quote)) = (&receipt.doc_type, &receipt.quote_reference) {
+        let Some(invoiced_total) = receipt.total().or(receipt.amount_due) else {
+            return Err(anyhow!(format!("Invoice {} has no total to compare with quote {}", receipt.doc_number, quote.quote_number)));
+        };
+        let variance = invoiced_total.checked_sub(quote.quoted_total)?;
+        current_y -= line_height * 1.5;
+        current_layer.use_text(format!("{} {}", labels::QUOTE.text(bilingual), quote.quote_number), font_size, x1, current_y, &font_regular);
+        current_y -= Pt(4.0).into();
+        current_layer.add_line(x1, current_y, x2 + Pt(80.0).into(), current_y);
+        for (name, value) in [(&labels::QUOTED, quote.quoted_total.to_string()), (&labels::INVOICED, invoiced_total.to_string()), (&labels::VARIANCE, variance.signed())] {
+            current_y -= line_height;
+            current_layer.use_text(name.text(bilingual), 10.0, x1, current_y, &font_regular);
+            use_amount(&current_layer, &value, 10.0, x2, current_y, &font_mono);
+        }
+    }
+
+    // Aging summary for statements, across the page above the signature box
+    let aging = receipt.statement.as_ref().and_then(|statement| statement.aging.as_ref());
+    let aging_bottom: Mm = Pt(100.0).into();
+    let aging_top: Mm = Pt(136.0).into();
+
+    // Add notes below the tenders, left of the signature box, or above the
+    // aging summary when there is one
+    if !receipt.notes.trim().is_empty() {
+        let (notes_top, notes_bottom): (Mm, Mm) = match aging {
+            Some(_) => (Pt(176.0).into(), aging_top + Pt(4.0).into()),
+            None => (Pt(140.0).into(), Pt(64.0).into()),
+        };
+        let notes_top = Mm(notes_top.0.min((current_y - Pt(8.0).into()).0));
+        current_layer.use_text(labels::NOTES.text(bilingual), 8.0, x1, notes_top, label_font);
+        textflow::draw_text_box(&current_layer, &receipt.notes, x1, notes_top, notes_bottom, 65, &run_fonts);
+    }
+
+    if let Some(aging) = aging {
+        let aging_middle: Mm = Pt(120.0).into();
+        current_layer.add_styled_box(left_margin, aging_bottom, right_margin, aging_top, &options.theme.box_style);
+        current_layer.add_styled_line(left_margin, aging_middle, right_margin, aging_middle, options.theme.rule_style);
+        let column_width = (right_margin - left_margin) / 5.0;
+        let columns = [
+            (&labels::CURRENT, &aging.current),
+            (&labels::DAYS_31_60, &aging.days_31_60),
+            (&labels::DAYS_61_90, &aging.days_61_90),
+            (&labels::OVER_90, &aging.over_90),
+            (&labels::TOTAL, &aging.total),
+        ];
+        for (i, (label, value)) in columns.iter().enumerate() {
+            let x = left_margin + column_width * i as f64;
+            if i > 0 {
+                current_layer.add_styled_line(x, aging_bottom, x, aging_top, options.theme.rule_style);
+            }
+            match label.second_line(bilingual) {
+                Some(second_line) => {
+                    current_layer.use_text(label.en, 7.0, x + spacing, aging_middle + Pt(9.0).into(), label_font);
+                    current_layer.use_text(second_line, 7.0, x + spacing, aging_middle + Pt(2.0).into(), label_font);
+                },
+                None => current_layer.use_text(label.en, 8.0, x + spacing, aging_middle + Pt(4.0).into(), label_font),
+            }
+            use_amount(&current_layer, &value.to_string(), 8.0, x + spacing, aging_bottom + Pt(5.0).into(), &font_mono);
+        }
+    }
+
+    // Pt 70 to 100 for the signature block, between the aging box and the notices
+    SIGNATURE_BLOCK.draw(&current_layer, bilingual, &font_regular);
+    if options.fillable_quote && receipt.doc_type == DocType::Quote {
+        SIGNATURE_BLOCK.draw_acceptance(&current_layer, bilingual, &font_regular);
+    }
+    if receipt.doc_type == DocType::DeliveryTicket {
+        SIGNATURE_BLOCK.draw_checklist(&current_layer, &options.delivery_checklist, template.table_bottom, &regular_metrics, &font_regular)?;
+    }
 
     // Add terms
-    current_layer.use_text("All claims and returned goods MUST be accompanied by this bill", 8.0, Pt(180.0).into(), Pt(54.0).into(), &font_regular);
-    current_layer.use_text("*INTEREST AT THE RATE OF 1.5% PER MONHTH WILL BE CHARGED ON ALL OVERDUE INVOICES*", 8.0, Pt(130.0).into(), Pt(44.0).into(), &font_regular);
+    if receipt.doc_type == DocType::ZReport {
+        // Internal document, the customer notices don't apply
+    } else if bilingual {
+        current_layer.use_text(labels::CLAIMS_NOTICE.en, 7.0, Pt(200.0).into(), Pt(64.0).into(), &font_regular);
+        current_layer.use_text(labels::CLAIMS_NOTICE.fr, 7.0, Pt(150.0).into(), Pt(56.0).into(), &font_regular);
+        current_layer.use_text(labels::INTEREST_NOTICE.en, 7.0, Pt(155.0).into(), Pt(48.0).into(), &font_italic);
+        current_layer.use_text(labels::INTEREST_NOTICE.fr, 7.0, Pt(165.0).into(), Pt(40.0).into(), &font_italic);
+    } else {
+        current_layer.use_text(labels::CLAIMS_NOTICE.en, 8.0, Pt(180.0).into(), Pt(54.0).into(), &font_regular);
+        current_layer.use_text(labels::INTEREST_NOTICE.en, 8.0, Pt(130.0).into(), Pt(44.0).into(), &font_italic);
+    }
     
     // Add slogan
-    current_layer.use_text(&receipt.slogan, 9.0, Pt(254.0).into(), Pt(30.0).into(), &font_regular);
-    return Ok(doc);
-
-}
-
-// Split any text which goes over a maximimum number of characters into separate
-// lines
-fn split_into_lines(string: &str, max_length: usize) -> Vec<String> {
-    let mut lines = Vec::new();
-    if string.is_empty() {
-        return Vec::new();
-    }
-
-    lines.push(string.to_owned());
-    while unsafe { lines.last().unwrap_unchecked().len() } > max_length {
-        let last_line = unsafe { lines.pop().unwrap_unchecked() };
-        let final_whitespace = &last_line[..max_length+1]
-            .chars()
-            .enumerate()
-            .filter(|(_, char)| char.eq(&' ') || char.eq(&'-'))
-            .last();
-        if let Some((index, _)) = final_whitespace {
-            let (first_str, last_str)= last_line.split_at(*index+1);
-            lines.push(first_str.to_owned());
-            lines.push(format!(" {last_str}"));
-        } else {
-            let (first_str, last_str)= last_line.split_at(max_length+1);
-            lines.push(format!("{first_str}-"));
-            lines.push(format!(" {last_str}"));
+    place_text(&current_layer, template.slogan_text.as_deref().unwrap_or(&receipt.slogan), &template.slogan, &font_regular);
+
+    if options.print_checksum {
+        let checksum = format!("{} {}", labels::CHECKSUM.text(bilingual), receipt.short_checksum());
+        let checksum_x = right_margin - mono_metrics.width(&checksum, 6.5);
+        current_layer.use_text(&checksum, 6.5, checksum_x, Pt(18.0).into(), &font_mono);
+    }
+
+    // Required by law where there is one, so it takes the corner the portal
+    // link would otherwise go in
+    let fiscal = receipt.fiscal.as_ref();
+    if let Some(fiscal) = fiscal {
+        if !fiscal.qr_payload.is_empty() {
+            qr::draw_qr(&current_layer, &fiscal.qr_payload, Pt(54.0).into(), Pt(10.0).into(), Pt(54.0).into())?;
+        }
+        let mut lines = vec![
+            format!("{} {}", labels::FISCAL_NUMBER.text(bilingual), fiscal.fiscal_number),
+            format!("{} {}", labels::FISCAL_SIGNATURE.text(bilingual), fiscal.signature),
+        ];
+        lines.extend(fiscal.lines.iter().cloned());
+        let mut line_y = 8.0 + 7.5 * lines.len() as f64;
+        for line in &lines {
+            current_layer.use_text(line, 6.0, Pt(112.0).into(), Pt(line_y).into(), &font_mono);
+            line_y -= 7.5;
+        }
+    }
+
+    // The document's own QR code, e.g. a payment page, or else the
+    // self-service download link, below the notes and left of the notices
+    let footer_qr = match (&receipt.qr_payload, &options.portal) {
+        (Some(payload), _) if !payload.trim().is_empty() => Some(payload.trim().to_owned()),
+        (_, Some(portal)) => Some(portal.url(&receipt.doc_number)?),
+        _ => None,
+    };
+    // Only the fiscal code takes its spot, fiscal lines alone leave it free
+    let fiscal_qr = fiscal.is_some_and(|fiscal| !fiscal.qr_payload.is_empty());
+    if let (Some(payload), false) = (footer_qr, fiscal_qr) {
+        let qr_x: Mm = Pt(54.0).into();
+        let qr_y: Mm = Pt(10.0).into();
+        let qr_size: Mm = Pt(54.0).into();
+        qr::draw_qr(&current_layer, &payload, qr_x, qr_y, qr_size)?;
+        // Clickable too when it's a link
+        if payload.starts_with("https://") || payload.starts_with("http://") {
+            current_layer.add_link_annotation(LinkAnnotation::new(
+                Rect::new(qr_x, qr_y, qr_x + qr_size, qr_y + qr_size),
+                None,
+                None,
+                Actions::Uri(payload),
+                None,
+            ));
+        }
+    }
+
+    // Only the pages of the document itself are numbered, not the terms or
+    // the attachments after it
+    if pages.len() > 1 {
+        for (number, page) in pages.iter().enumerate() {
+            let footer = format!("{} {} / {}", labels::PAGE.text(bilingual), number + 1, pages.len());
+            let footer_x = right_margin - mono_metrics.width(&footer, 8.0);
+            page.use_text(&footer, 8.0, footer_x, Pt(30.0).into(), &font_mono);
         }
     }
-    return lines;
+
+    // Append terms & conditions to invoices and quotes
+    if let Some(terms) = template.terms.as_ref().or(resources.terms.as_ref()) {
+        if matches!(receipt.doc_type, DocType::Invoice | DocType::Quote) && !terms.trim().is_empty() {
+            textflow::add_text_pages(&doc, &labels::TERMS_AND_CONDITIONS.text(bilingual), terms, &run_fonts);
+        }
+    }
+    if let Some(changes) = options.changes.as_ref().filter(|changes| !changes.is_empty()) {
+        textflow::add_text_pages(&doc, &labels::CHANGES.text(bilingual), &changes.to_markdown(), &run_fonts);
+    }
+    attachments::add_attachment_pages(&doc, &receipt.attachments, &font_regular, reduction.max_image_pixels)?;
+    return Ok((doc, pages.len() as u32));
+
+}
+
+// Large gray "DRAFT" across the middle of the page
+fn draw_draft_stamp(layer: &PdfLayerReference, bilingual: bool, font: &IndirectFontRef, page_height: f64) {
+    let text = labels::DRAFT.text(bilingual);
+    let font_size = if bilingual { 64.0 } else { 120.0 };
+    // Roughly the advance of Noto Sans Bold capitals
+    let half_width = text.chars().count() as f64 * font_size * 0.68 / 2.0;
+    let (sin, cos) = 35f64.to_radians().sin_cos();
+    // Centers the text on the page along the rotated baseline
+    let x = 306.0 - half_width * cos + font_size * 0.35 * sin;
+    let y = page_height / 2.0 - half_width * sin - font_size * 0.35 * cos;
+    layer.save_graphics_state();
+    layer.set_fill_color(ThemeColor::gray(0.88).to_pdf());
+    layer.set_ctm(CurTransMat::Raw([cos, sin, -sin, cos, x, y]));
+    layer.use_text(text, font_size, Mm(0.0), Mm(0.0), font);
+    layer.restore_graphics_state();
+}
+
+// A description column has to fit one character of the 8pt item font
+// between its paddings, like MIN_DESC_WIDTH of templates
+fn check_desc_width(width: Mm, mono_metrics: &metrics::FontMetrics) -> Result<(), Error> {
+    let spacing: Mm = Pt(5.0).into();
+    let needed = spacing * 2.0 + mono_metrics.width("M", 8.0);
+    if width < needed {
+        return Err(anyhow!(format!(
+            "The description column is {:.1}pt wide, it needs at least {:.1}pt",
+            Into::<Pt>::into(width).0, Into::<Pt>::into(needed).0,
+        )));
+    }
+    return Ok(());
+}
+
+// The wider of two widths
+fn wider(a: Mm, b: Mm) -> Mm {
+    return if a > b { a } else { b };
 }
\ No newline at end of file
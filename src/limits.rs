@@ -0,0 +1,51 @@
+use anyhow::{Error, Result, anyhow};
+use crate::{money, Money, ReceiptInfo};
+
+// Sanity limits on the numbers of a document. A value past them is far more
+// likely to come from a corrupted export than from a real sale, so pre_pass
+// fails with a "Suspicious value" error instead of printing e.g. a
+// $10,000,000 invoice. Compared against the absolute value, so refunds are
+// held to the same limits.
+
+#[derive(Debug, Clone, Default)]
+pub struct Limits {
+    pub max_line_amount: Option<Money>,
+    pub max_quantity: Option<f64>,
+    // Checked against the "Total:" row and the amount due
+    pub max_total: Option<Money>,
+}
+
+impl ReceiptInfo {
+    pub(crate) fn check_limits(&self, limits: &Limits) -> Result<(), Error> {
+        for line in &self.item_lines {
+            let name = format!("line `{}` ({})", line.code, line.description);
+            check_amount(&name, "amount", line.amount, limits.max_line_amount)?;
+            check_quantity(&name, &line.quantity, limits.max_quantity)?;
+        }
+        for total in self.totals.iter().filter(|total| total.name.eq("Total:")) {
+            check_amount("the document", "total", Some(total.value), limits.max_total)?;
+        }
+        check_amount("the document", "amount due", self.amount_due, limits.max_total)?;
+        return Ok(());
+    }
+}
+
+fn check_amount(name: &str, field: &str, amount: Option<Money>, limit: Option<Money>) -> Result<(), Error> {
+    let (Some(amount), Some(limit)) = (amount, limit) else { return Ok(()) };
+    if amount.abs() > limit.abs() {
+        return Err(anyhow!(format!("Suspicious value: the {field} of {name} is {amount}, over the limit of {limit}")));
+    }
+    return Ok(());
+}
+
+fn check_quantity(name: &str, value: &str, limit: Option<f64>) -> Result<(), Error> {
+    let Some(limit) = limit else { return Ok(()) };
+    if value.trim().is_empty() {
+        return Ok(());
+    }
+    let parsed = money::parse_amount(value)?;
+    if !parsed.is_finite() || parsed.abs() > limit {
+        return Err(anyhow!(format!("Suspicious value: the quantity of {name} is {value}, over the limit of {limit}")));
+    }
+    return Ok(());
+}
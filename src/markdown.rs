@@ -0,0 +1,183 @@
+// A deliberately tiny Markdown subset for blurbs written by office staff:
+// `# headings`, `- bullet` / `* bullet` lists, `**bold**` / `__bold__` and
+// `*italic*` / `_italic_`. Anything else is passed through as plain text.
+// Public so a front end can preview a blurb the way it will be laid out.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    Regular,
+    Bold,
+    Italic,
+    BoldItalic,
+}
+
+impl Style {
+    fn new(bold: bool, italic: bool) -> Self {
+        match (bold, italic) {
+            (false, false) => Style::Regular,
+            (true, false) => Style::Bold,
+            (false, true) => Style::Italic,
+            (true, true) => Style::BoldItalic,
+        }
+    }
+
+    fn with_bold(self) -> Self {
+        match self {
+            Style::Regular | Style::Bold => Style::Bold,
+            Style::Italic | Style::BoldItalic => Style::BoldItalic,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Run {
+    pub text: String,
+    pub style: Style,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Block {
+    Heading(Vec<Run>),
+    Paragraph(Vec<Run>),
+    Bullet(Vec<Run>),
+}
+
+pub fn parse(text: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let flush = |paragraph: &mut Vec<&str>, blocks: &mut Vec<Block>| {
+        if !paragraph.is_empty() {
+            blocks.push(Block::Paragraph(parse_inline(&paragraph.join(" "))));
+            paragraph.clear();
+        }
+    };
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() {
+            flush(&mut paragraph, &mut blocks);
+        } else if line.starts_with('#') {
+            flush(&mut paragraph, &mut blocks);
+            let runs = parse_inline(line.trim_start_matches('#').trim())
+                .into_iter()
+                .map(|run| Run { style: run.style.with_bold(), ..run })
+                .collect();
+            blocks.push(Block::Heading(runs));
+        } else if let Some(item) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Bullet(parse_inline(item.trim())));
+        } else {
+            paragraph.push(line);
+        }
+    }
+    flush(&mut paragraph, &mut blocks);
+    return blocks;
+}
+
+// Splits a line into styled runs. Emphasis markers only take effect when a
+// matching closing marker exists, so stray asterisks (e.g. "5 * 2") print as-is.
+pub(crate) fn parse_inline(text: &str) -> Vec<Run> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut runs = Vec::new();
+    let mut current = String::new();
+    let (mut bold, mut italic) = (false, false);
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '*' || c == '_' {
+            let double = chars.get(i + 1) == Some(&c);
+            let marker_len = if double { 2 } else { 1 };
+            let is_open = if double { bold } else { italic };
+            if is_open || has_closing_marker(&chars[i + marker_len..], c, marker_len) {
+                if !current.is_empty() {
+                    runs.push(Run { text: std::mem::take(&mut current), style: Style::new(bold, italic) });
+                }
+                if double {
+                    bold = !bold;
+                } else {
+                    italic = !italic;
+                }
+                i += marker_len;
+                continue;
+            }
+        }
+        current.push(c);
+        i += 1;
+    }
+    if !current.is_empty() {
+        runs.push(Run { text: current, style: Style::new(bold, italic) });
+    }
+    return runs;
+}
+
+fn has_closing_marker(rest: &[char], marker: char, marker_len: usize) -> bool {
+    let mut i = 0;
+    while i < rest.len() {
+        if rest[i] == marker {
+            let run_len = rest[i..].iter().take_while(|c| **c == marker).count();
+            if run_len == marker_len || (marker_len == 1 && run_len > 2) {
+                return true;
+            }
+            i += run_len;
+        } else {
+            i += 1;
+        }
+    }
+    return false;
+}
+
+struct Word<'a> {
+    text: &'a str,
+    style: Style,
+    // Not preceded by whitespace, e.g. the comma in "**bold**,"
+    attached: bool,
+}
+
+fn split_words(runs: &[Run]) -> Vec<Word<'_>> {
+    let mut words = Vec::new();
+    let mut separated = true;
+    for run in runs {
+        let mut rest = run.text.as_str();
+        loop {
+            let trimmed = rest.trim_start();
+            if trimmed.len() != rest.len() {
+                separated = true;
+            }
+            if trimmed.is_empty() {
+                break;
+            }
+            let end = trimmed.find(char::is_whitespace).unwrap_or(trimmed.len());
+            words.push(Word { text: &trimmed[..end], style: run.style, attached: !separated });
+            separated = false;
+            rest = &trimmed[end..];
+        }
+    }
+    return words;
+}
+
+// Greedy word wrap over styled runs, counting characters. Adjacent words with
+// the same style are merged back into a single run per line.
+pub fn wrap_runs(runs: &[Run], max_chars: usize) -> Vec<Vec<Run>> {
+    let mut lines = Vec::new();
+    let mut line: Vec<Run> = Vec::new();
+    let mut line_len = 0;
+    for word in split_words(runs) {
+        let word_len = word.text.chars().count();
+        if !line.is_empty() && !word.attached && line_len + 1 + word_len > max_chars {
+            lines.push(std::mem::take(&mut line));
+            line_len = 0;
+        }
+        let mut text = String::new();
+        if !line.is_empty() && !word.attached {
+            text.push(' ');
+        }
+        text.push_str(word.text);
+        line_len += text.chars().count();
+        match line.last_mut() {
+            Some(last) if last.style == word.style => last.text.push_str(&text),
+            _ => line.push(Run { text, style: word.style }),
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    return lines;
+}
@@ -0,0 +1,49 @@
+use printpdf::lopdf::{Document, Object};
+use anyhow::{Error, Result, anyhow};
+use crate::stamp::inherited;
+
+// Appends other PDFs (e.g. vendor weigh tickets) behind a generated document,
+// so the customer receives a single file.
+
+// Attributes a page may inherit from its page tree, which have to be copied
+// onto the page itself once it's moved into another tree
+const INHERITABLE: [&[u8]; 4] = [b"Resources", b"MediaBox", b"CropBox", b"Rotate"];
+
+pub fn append_pdfs(pdf: &[u8], appendices: &[Vec<u8>]) -> Result<Vec<u8>, Error> {
+    let mut doc = Document::load_mem(pdf)?;
+    let catalog_id = doc.trailer.get(b"Root")?.as_reference()?;
+    let pages_id = doc.get_object(catalog_id)?.as_dict()?.get(b"Pages")?.as_reference()?;
+
+    for appendix in appendices {
+        let mut other = Document::load_mem(appendix)?;
+        other.renumber_objects_with(doc.max_id + 1);
+        let page_ids: Vec<_> = other.get_pages().into_values().collect();
+        if page_ids.is_empty() {
+            return Err(anyhow!("A PDF to append has no pages"));
+        }
+        for page_id in &page_ids {
+            let attributes: Vec<_> = INHERITABLE.iter()
+                .filter_map(|key| inherited(&other, *page_id, key).map(|value| (*key, value)))
+                .collect();
+            let page = other.get_object_mut(*page_id)?.as_dict_mut()?;
+            for (key, value) in attributes {
+                page.set(key, value);
+            }
+            page.set("Parent", pages_id);
+        }
+        doc.max_id = other.max_id;
+        doc.objects.extend(other.objects);
+
+        let pages = doc.get_object_mut(pages_id)?.as_dict_mut()?;
+        let count = pages.get(b"Count")?.as_i64()?;
+        match pages.get_mut(b"Kids")? {
+            Object::Array(kids) => kids.extend(page_ids.iter().map(|id| Object::Reference(*id))),
+            _ => return Err(anyhow!("The page tree of the document has no Kids array")),
+        }
+        pages.set("Count", count + page_ids.len() as i64);
+    }
+
+    let mut bytes = Vec::new();
+    doc.save_to(&mut bytes)?;
+    return Ok(bytes);
+}
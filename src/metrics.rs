@@ -0,0 +1,82 @@
+use anyhow::{Error, Result, anyhow};
+use printpdf::{Mm, Pt};
+use ttf_parser::Face;
+
+// Widths of text in the loaded fonts, from the advance of each glyph in the
+// font's own metrics. Characters the font has no glyph for are measured as
+// the .notdef box printpdf draws for them.
+
+pub(crate) struct FontMetrics<'a> {
+    face: Face<'a>,
+    units_per_em: f64,
+}
+
+impl<'a> FontMetrics<'a> {
+    pub(crate) fn new(data: &'a [u8], name: &str) -> Result<Self, Error> {
+        let face = Face::parse(data, 0).map_err(|err| anyhow!(format!("Couldn't read the {name} font: {err}")))?;
+        let units_per_em = face.units_per_em() as f64;
+        return Ok(Self { face, units_per_em });
+    }
+
+    pub(crate) fn width(&self, text: &str, font_size: f64) -> Mm {
+        let units: u32 = text.chars()
+            .map(|c| {
+                let glyph = self.face.glyph_index(c).unwrap_or(ttf_parser::GlyphId(0));
+                self.face.glyph_hor_advance(glyph).unwrap_or(0) as u32
+            })
+            .sum();
+        return Pt(units as f64 / self.units_per_em * font_size).into();
+    }
+
+    // Wraps text to max_width at the last space or hyphen that fits, or
+    // mid-word with a hyphen when there is none. Lines after the first are indented by a
+    // space. At most max_chars characters go on a line when it's set. Every
+    // line gets at least one character, even when max_width is narrower.
+    pub(crate) fn wrap(&self, text: &str, font_size: f64, max_width: Mm, max_chars: Option<usize>) -> Vec<String> {
+        let mut lines = Vec::new();
+        if text.is_empty() {
+            return lines;
+        }
+        let fits = |line: &str| {
+            return self.width(line, font_size) <= max_width && max_chars.is_none_or(|max| line.chars().count() <= max);
+        };
+        let mut rest = text.to_owned();
+        // Only the indent is left when max_width is narrower than a space
+        while !fits(&rest) && !rest.trim().is_empty() {
+            // Byte offset just past the characters which fit, keeping at
+            // least the first one after the indent so every line makes
+            // progress. A space which doesn't fit can still end the line.
+            let min_end = rest.char_indices().find(|(_, c)| *c != ' ').map_or(rest.len(), |(index, c)| index + c.len_utf8());
+            let mut end = min_end;
+            for (index, c) in rest.char_indices() {
+                let next = index + c.len_utf8();
+                if next <= min_end {
+                    continue;
+                }
+                if !fits(&rest[..next]) {
+                    if c == ' ' {
+                        end = next;
+                    }
+                    break;
+                }
+                end = next;
+            }
+            // A break at the very start would leave the line unchanged
+            let break_at = rest[..end].rfind([' ', '-']).filter(|index| *index > 0);
+            let (first, last) = match break_at {
+                Some(index) => (rest[..index + 1].to_owned(), rest[index + 1..].to_owned()),
+                None => {
+                    // The last character makes room for the hyphen
+                    let end = rest[..end].char_indices().last().map_or(end, |(index, _)| index).max(min_end);
+                    (format!("{}-", &rest[..end]), rest[end..].to_owned())
+                },
+            };
+            lines.push(first);
+            rest = format!(" {last}");
+        }
+        if lines.is_empty() || !rest.trim().is_empty() {
+            lines.push(rest);
+        }
+        return lines;
+    }
+}
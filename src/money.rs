@@ -0,0 +1,697 @@
+use anyhow::{Error, Result, anyhow};
+
+// Monetary amounts. The amounts of a document are Money, read from the text
+// the POS sends when the document is deserialized, and everything pre_pass,
+// the totals and the exports compute (running balances, aging, progress
+// billing, over/short) is computed in Money. Quantities and unit prices stay
+// text, as unit prices can have fractions of a cent.
+
+// Normalizes an amount as typed or printed by a person into a plain decimal
+// number: "$1,234.50" becomes "1234.50", and "(12.00)", "12.00-" and
+// "-$12.00" all become "-12.00". Thousands separators have to be in the right
+// places, anything else which isn't part of an amount is an error.
+pub fn cleanup_amount(text: &str) -> Result<String, Error> {
+    let invalid = || anyhow!(format!("`{text}` is not a valid amount"));
+    let mut rest = text.trim();
+    let mut negative = false;
+    if let Some(inner) = rest.strip_prefix('(').and_then(|rest| rest.strip_suffix(')')) {
+        negative = true;
+        rest = inner.trim();
+    }
+    // The sign may be on either side of the currency symbol, or trail
+    for _ in 0..2 {
+        if let Some(unsigned) = rest.strip_prefix('-') {
+            if negative {
+                return Err(invalid());
+            }
+            negative = true;
+            rest = unsigned.trim_start();
+        } else if let Some(unsigned) = rest.strip_prefix('+') {
+            rest = unsigned.trim_start();
+        }
+        if let Some(amount) = rest.strip_prefix('$') {
+            rest = amount.trim_start();
+        }
+    }
+    if let Some(unsigned) = rest.strip_suffix('-') {
+        if negative {
+            return Err(invalid());
+        }
+        negative = true;
+        rest = unsigned.trim_end();
+    }
+
+    let (whole, fraction) = match rest.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (rest, None),
+    };
+    if whole.contains(',') {
+        let mut groups = whole.split(',');
+        let first = groups.next().unwrap_or("");
+        if first.is_empty() || first.len() > 3 || groups.any(|group| group.len() != 3) {
+            return Err(invalid());
+        }
+    }
+    let whole = whole.replace(',', "");
+    let is_digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+    if !is_digits(&whole) || !fraction.is_none_or(is_digits) || whole.len() + fraction.map_or(0, str::len) == 0 {
+        return Err(invalid());
+    }
+
+    let mut amount = String::new();
+    if negative {
+        amount.push('-');
+    }
+    amount.push_str(if whole.is_empty() { "0" } else { &whole });
+    if let Some(fraction) = fraction.filter(|fraction| !fraction.is_empty()) {
+        amount.push('.');
+        amount.push_str(fraction);
+    }
+    return Ok(amount);
+}
+
+pub fn parse_amount(text: &str) -> Result<f64, Error> {
+    return Ok(str::parse::<f64>(&cleanup_amount(text)?)?);
+}
+
+// An amount in cents, for computing with amounts without the f64 drift of
+// parse_amount. It has no currency of its own: a document is in a single
+// currency, ReceiptInfo::currency, which the POS sends once for the whole
+// document, so a currency on every amount could only ever be that one and
+// would make adding two amounts fail over a mismatch that can't happen.
+// There are no operators, since amounts come from the POS and overflowing
+// them is an error like any other bad input: checked_add, checked_sub and
+// checked_sum return one.
+//
+// Deserialized from the text the POS sends, e.g. "1,234.50" (see
+// cleanup_amount), or from a JSON number, and serialized as text with two
+// decimals. Blank text is zero, except for the fields which are optional,
+// where it's None.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Money {
+    cents: i64,
+}
+
+impl Money {
+    pub const ZERO: Money = Money { cents: 0 };
+
+    pub fn from_cents(cents: i64) -> Self {
+        return Self { cents };
+    }
+
+    pub fn cents(self) -> i64 {
+        return self.cents;
+    }
+
+    // Rounded to the cent with `rounding.mode`
+    pub fn from_f64(value: f64, rounding: &Rounding) -> Self {
+        let rounding = Rounding { precision: 2, ..*rounding };
+        return Self { cents: (rounding.round(value) * 100.0).round() as i64 };
+    }
+
+    pub fn to_f64(self) -> f64 {
+        return self.cents as f64 / 100.0;
+    }
+
+    // Reads anything cleanup_amount accepts. Digits past the cents are
+    // rounded with `rounding.mode`, exactly rather than through an f64.
+    pub fn parse(text: &str, rounding: &Rounding) -> Result<Self, Error> {
+        let cleaned = cleanup_amount(text)?;
+        let (negative, digits) = match cleaned.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, cleaned.as_str()),
+        };
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        let too_large = || anyhow!(format!("`{text}` is too large an amount"));
+        let whole: i64 = if whole.is_empty() { 0 } else { whole.parse().map_err(|_| too_large())? };
+        let cents_digits: String = fraction.chars().chain("00".chars()).take(2).collect();
+        let mut cents = whole.checked_mul(100)
+            .and_then(|cents| cents.checked_add(cents_digits.parse::<i64>().unwrap_or(0)))
+            .ok_or_else(too_large)?;
+        let rest = fraction.get(2..).unwrap_or("");
+        let first_dropped = rest.bytes().next().map_or(0, |byte| byte - b'0');
+        let beyond_half = rest.bytes().skip(1).any(|byte| byte != b'0');
+        let round_up = match rounding.mode {
+            RoundingMode::Truncate => false,
+            _ if first_dropped != 5 => first_dropped > 5,
+            _ if beyond_half => true,
+            RoundingMode::HalfUp => true,
+            RoundingMode::HalfEven => cents % 2 == 1,
+        };
+        if round_up {
+            cents = cents.checked_add(1).ok_or_else(too_large)?;
+        }
+        return Ok(Self { cents: if negative { -cents } else { cents } });
+    }
+
+    // e.g. a quantity times a unit price, rounded to the cent
+    pub fn times(self, factor: f64, rounding: &Rounding) -> Self {
+        return Self::from_f64(self.to_f64() * factor, rounding);
+    }
+
+    pub fn abs(self) -> Self {
+        return Self { cents: self.cents.abs() };
+    }
+
+    pub fn is_negative(self) -> bool {
+        return self.cents < 0;
+    }
+
+    pub fn checked_neg(self) -> Result<Money, Error> {
+        return self.cents.checked_neg().map(Money::from_cents).ok_or_else(|| anyhow!(format!("-{self} is too large an amount")));
+    }
+
+    pub fn checked_add(self, other: Money) -> Result<Money, Error> {
+        return self.cents.checked_add(other.cents).map(Money::from_cents).ok_or_else(|| overflow(self, "+", other));
+    }
+
+    pub fn checked_sub(self, other: Money) -> Result<Money, Error> {
+        return self.cents.checked_sub(other.cents).map(Money::from_cents).ok_or_else(|| overflow(self, "-", other));
+    }
+
+    pub fn checked_sum<I: IntoIterator<Item = Money>>(amounts: I) -> Result<Money, Error> {
+        let mut sum = Money::ZERO;
+        for amount in amounts {
+            sum = sum.checked_add(amount)?;
+        }
+        return Ok(sum);
+    }
+
+    // With the sign even when positive, for differences, e.g. "+1.50"
+    pub fn signed(self) -> String {
+        return if self.cents < 0 { self.to_string() } else { format!("+{self}") };
+    }
+}
+
+fn overflow(a: Money, operator: &str, b: Money) -> Error {
+    return anyhow!(format!("{a} {operator} {b} is too large an amount"));
+}
+
+// An amount field as it's printed, always with two decimals: "12.5" becomes
+// "12.50". Text which isn't an amount, like "TBD" or "10%", is left as sent,
+// and so is an explicit "+" on variances.
+pub(crate) fn two_decimals(text: &str) -> String {
+    let Ok(amount) = Money::parse(text, &Rounding::default()) else { return text.to_owned() };
+    if text.trim_start().starts_with('+') {
+        return amount.signed();
+    }
+    return amount.to_string();
+}
+
+// Always two decimals, e.g. "-12.50", the way amounts are sent by the POS
+impl std::fmt::Display for Money {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.cents < 0 { "-" } else { "" };
+        let cents = self.cents.unsigned_abs();
+        return write!(f, "{sign}{}.{:02}", cents / 100, cents % 100);
+    }
+}
+
+impl std::str::FromStr for Money {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self, Error> {
+        return Money::parse(text, &Rounding::default());
+    }
+}
+
+// A line discount: an amount taken off the line, or text which isn't one
+// and is printed as sent, e.g. "10%"
+#[derive(Debug, Clone, PartialEq)]
+pub enum Discount {
+    Amount(Money),
+    Text(String),
+}
+
+impl Discount {
+    // Text which is an amount is read as one
+    pub fn from_text(text: &str) -> Self {
+        return match text.parse() {
+            Ok(amount) => Discount::Amount(amount),
+            Err(_) => Discount::Text(text.to_owned()),
+        };
+    }
+
+    // None for text
+    pub fn amount(&self) -> Option<Money> {
+        return match self {
+            Discount::Amount(amount) => Some(*amount),
+            Discount::Text(_) => None,
+        };
+    }
+}
+
+impl std::fmt::Display for Discount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            Discount::Amount(amount) => write!(f, "{amount}"),
+            Discount::Text(text) => write!(f, "{text}"),
+        };
+    }
+}
+
+impl crate::ReceiptInfo {
+    // The "Total:" row, None without one
+    pub fn total(&self) -> Option<Money> {
+        return self.totals.iter().find(|total| total.name.eq("Total:")).map(|total| total.value);
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impls {
+    use std::fmt;
+    use serde::{de, Deserializer, Serializer};
+    use super::{Discount, Money};
+
+    impl serde::Serialize for Money {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            return serializer.serialize_str(&self.to_string());
+        }
+    }
+
+    // Text, a number or nothing, the latter for optional amounts only
+    struct MoneyVisitor {
+        optional: bool,
+    }
+
+    impl<'de> de::Visitor<'de> for MoneyVisitor {
+        type Value = Option<Money>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            return formatter.write_str("an amount");
+        }
+
+        fn visit_str<E: de::Error>(self, text: &str) -> Result<Option<Money>, E> {
+            if text.trim().is_empty() {
+                return Ok(if self.optional { None } else { Some(Money::ZERO) });
+            }
+            return text.parse().map(Some).map_err(E::custom);
+        }
+
+        fn visit_i64<E: de::Error>(self, value: i64) -> Result<Option<Money>, E> {
+            let cents = value.checked_mul(100).ok_or_else(|| E::custom(format!("`{value}` is too large an amount")))?;
+            return Ok(Some(Money::from_cents(cents)));
+        }
+
+        fn visit_u64<E: de::Error>(self, value: u64) -> Result<Option<Money>, E> {
+            let value = i64::try_from(value).map_err(|_| E::custom(format!("`{value}` is too large an amount")))?;
+            return self.visit_i64(value);
+        }
+
+        fn visit_f64<E: de::Error>(self, value: f64) -> Result<Option<Money>, E> {
+            if !value.is_finite() || value.abs() * 100.0 >= i64::MAX as f64 {
+                return Err(E::custom(format!("`{value}` is not a valid amount")));
+            }
+            return Ok(Some(Money::from_f64(value, &super::Rounding::default())));
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<Option<Money>, E> {
+            return if self.optional { Ok(None) } else { Err(E::custom("an amount is required")) };
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<Option<Money>, E> {
+            return self.visit_none();
+        }
+    }
+
+    impl<'de> serde::Deserialize<'de> for Money {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let amount = deserializer.deserialize_any(MoneyVisitor { optional: false })?;
+            return Ok(amount.unwrap_or(Money::ZERO));
+        }
+    }
+
+    // For `#[serde(with = "money::optional")]` on the Option<Money> fields,
+    // which the POS leaves blank
+    pub(crate) mod optional {
+        use serde::{Deserializer, Serializer};
+        use super::{Money, MoneyVisitor};
+
+        pub(crate) fn serialize<S: Serializer>(amount: &Option<Money>, serializer: S) -> Result<S::Ok, S::Error> {
+            return match amount {
+                Some(amount) => serializer.serialize_str(&amount.to_string()),
+                None => serializer.serialize_str(""),
+            };
+        }
+
+        pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Money>, D::Error> {
+            return deserializer.deserialize_any(MoneyVisitor { optional: true });
+        }
+    }
+
+    impl serde::Serialize for Discount {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            return serializer.serialize_str(&self.to_string());
+        }
+    }
+
+    struct DiscountVisitor;
+
+    impl<'de> de::Visitor<'de> for DiscountVisitor {
+        type Value = Discount;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            return formatter.write_str("an amount or text");
+        }
+
+        fn visit_str<E: de::Error>(self, text: &str) -> Result<Discount, E> {
+            return Ok(Discount::from_text(text));
+        }
+
+        fn visit_i64<E: de::Error>(self, value: i64) -> Result<Discount, E> {
+            return MoneyVisitor { optional: false }.visit_i64(value).map(|amount| Discount::Amount(amount.unwrap_or(Money::ZERO)));
+        }
+
+        fn visit_u64<E: de::Error>(self, value: u64) -> Result<Discount, E> {
+            return MoneyVisitor { optional: false }.visit_u64(value).map(|amount| Discount::Amount(amount.unwrap_or(Money::ZERO)));
+        }
+
+        fn visit_f64<E: de::Error>(self, value: f64) -> Result<Discount, E> {
+            return MoneyVisitor { optional: false }.visit_f64(value).map(|amount| Discount::Amount(amount.unwrap_or(Money::ZERO)));
+        }
+    }
+
+    impl<'de> serde::Deserialize<'de> for Discount {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            return deserializer.deserialize_any(DiscountVisitor);
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+pub(crate) use serde_impls::optional;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingMode {
+    // Ties away from zero
+    HalfUp,
+    // Ties to the even digit, i.e. banker's rounding
+    HalfEven,
+    // Drops the extra digits
+    Truncate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rounding {
+    pub mode: RoundingMode,
+    // Digits after the decimal point
+    pub precision: usize,
+}
+
+impl Default for Rounding {
+    fn default() -> Self {
+        return Self {
+            mode: RoundingMode::HalfUp,
+            precision: 2,
+        };
+    }
+}
+
+// f64 can't hold most decimal ties exactly (2.675 is 2.67499999...), so
+// anything this close to a half is treated as one
+const TIE_TOLERANCE: f64 = 1e-7;
+
+impl Rounding {
+    pub fn round(&self, value: f64) -> f64 {
+        let scale = 10f64.powi(self.precision as i32);
+        let scaled = value.abs() * scale;
+        let whole = scaled.floor();
+        let fraction = scaled - whole;
+        // Large amounts lose precision in the fraction as well
+        let tolerance = TIE_TOLERANCE.max(scaled * f64::EPSILON * 4.0);
+        let is_tie = (fraction - 0.5).abs() < tolerance;
+        let rounded = match self.mode {
+            RoundingMode::Truncate => {
+                // Don't truncate 0.29 to 0.28 because it's stored as 0.28999...
+                if 1.0 - fraction < tolerance { whole + 1.0 } else { whole }
+            },
+            RoundingMode::HalfUp if is_tie => whole + 1.0,
+            RoundingMode::HalfEven if is_tie => if whole % 2.0 == 0.0 { whole } else { whole + 1.0 },
+            RoundingMode::HalfUp | RoundingMode::HalfEven => scaled.round(),
+        };
+        if rounded == 0.0 {
+            // Avoids printing "-0.00"
+            return 0.0;
+        }
+        return rounded.copysign(value) / scale;
+    }
+
+    pub fn format(&self, value: f64) -> String {
+        return format!("{:.*}", self.precision, self.round(value));
+    }
+
+    // Always shows the sign, for differences
+    pub fn format_signed(&self, value: f64) -> String {
+        return format!("{:+.*}", self.precision, self.round(value));
+    }
+}
+
+// Unit prices, e.g. $/ton for aggregates, keep up to four decimals while
+// amounts have two
+const UNIT_PRICE_PRECISION: usize = 4;
+const UNIT_PRICE_MIN_PRECISION: usize = 2;
+
+pub(crate) fn format_unit_price(value: f64) -> String {
+    let rounding = Rounding { mode: RoundingMode::HalfUp, precision: UNIT_PRICE_PRECISION };
+    let mut price = rounding.format(value);
+    let min_length = price.len() - (UNIT_PRICE_PRECISION - UNIT_PRICE_MIN_PRECISION);
+    while price.len() > min_length && price.ends_with('0') {
+        price.pop();
+    }
+    return price;
+}
+
+// A unit price from the POS with two to four decimals. Text which isn't an
+// amount is left untouched.
+pub(crate) fn normalize_unit_price(text: &str) -> String {
+    return match parse_amount(text) {
+        Ok(value) => format_unit_price(value),
+        Err(_) => text.to_owned(),
+    };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NegativeStyle {
+    // "-1,234.56"
+    Minus,
+    // "(1,234.56)", the accounting convention
+    Parentheses,
+    // "-1,234.56" printed in red
+    Red,
+}
+
+// How amounts are printed. This only changes their appearance, the digits
+// after the decimal point are kept as the POS sent them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AmountFormat {
+    // None prints amounts without grouping
+    pub thousands_separator: Option<char>,
+    pub decimal_separator: char,
+    pub negative: NegativeStyle,
+}
+
+impl Default for AmountFormat {
+    fn default() -> Self {
+        return Self {
+            thousands_separator: Some(','),
+            decimal_separator: '.',
+            negative: NegativeStyle::Minus,
+        };
+    }
+}
+
+impl AmountFormat {
+    // Separators used for a language, e.g. "fr-CA" prints "1 234,56".
+    // Unknown languages get the English separators.
+    pub fn for_locale(locale: &str) -> Self {
+        let language = locale.split(['-', '_']).next().unwrap_or("").to_ascii_lowercase();
+        let (thousands_separator, decimal_separator) = match language.as_str() {
+            "fr" | "sv" | "nb" | "fi" | "pl" | "cs" => (' ', ','),
+            "de" | "es" | "it" | "pt" | "nl" | "da" => ('.', ','),
+            _ => (',', '.'),
+        };
+        return Self {
+            thousands_separator: Some(thousands_separator),
+            decimal_separator,
+            ..Self::default()
+        };
+    }
+
+    // Reads an amount typed with these separators into the plain form
+    // cleanup_amount gives, e.g. "1.234,56 €" with the German separators
+    // becomes "1234.56". The currency symbol may lead or trail.
+    pub fn cleanup(&self, text: &str) -> Result<String, Error> {
+        let mut rest = text.trim();
+        for symbol in ['€', '£'] {
+            if let Some(amount) = rest.strip_suffix(symbol) {
+                rest = amount.trim_end();
+            }
+        }
+        if let Some(amount) = rest.strip_suffix('$') {
+            rest = amount.trim_end();
+        }
+        let is_grouping = |c: char| match self.thousands_separator {
+            // Spaces are often typed as non-breaking ones
+            Some(' ') => c == ' ' || c == '\u{a0}' || c == '\u{202f}',
+            Some(separator) => c == separator,
+            None => false,
+        };
+        let mut normalized = String::new();
+        for c in rest.chars() {
+            normalized.push(match c {
+                '€' | '£' => '$',
+                c if c == self.decimal_separator => '.',
+                c if is_grouping(c) => ',',
+                // A separator of the other convention, which can't be read
+                // unambiguously
+                ',' | '.' => return Err(anyhow!(format!("`{text}` is not a valid amount"))),
+                c => c,
+            });
+        }
+        return cleanup_amount(&normalized).map_err(|_| anyhow!(format!("`{text}` is not a valid amount")));
+    }
+
+    // The amount as it should be printed. Text which isn't an amount, e.g.
+    // an empty field, is left untouched. An explicit "+" is kept, as on
+    // variances.
+    pub fn display(&self, amount: &str) -> String {
+        let cleaned = match cleanup_amount(amount) {
+            Ok(cleaned) => cleaned,
+            Err(_) => return amount.to_owned(),
+        };
+        let (negative, digits) = match cleaned.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, cleaned.as_str()),
+        };
+        let (whole, fraction) = match digits.split_once('.') {
+            Some((whole, fraction)) => (whole, Some(fraction)),
+            None => (digits, None),
+        };
+        let mut magnitude = String::new();
+        for (i, digit) in whole.chars().enumerate() {
+            if let Some(separator) = self.thousands_separator {
+                if i > 0 && (whole.len() - i) % 3 == 0 {
+                    magnitude.push(separator);
+                }
+            }
+            magnitude.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            magnitude.push(self.decimal_separator);
+            magnitude.push_str(fraction);
+        }
+        // Zero has no sign, even when the POS sends "-0.00"
+        let negative = negative && digits.bytes().any(|byte| byte.is_ascii_digit() && byte != b'0');
+        return match (negative, self.negative) {
+            (true, NegativeStyle::Parentheses) => format!("({magnitude})"),
+            (true, _) => format!("-{magnitude}"),
+            // Keeps the decimals lined up with the ones in parentheses
+            (false, NegativeStyle::Parentheses) => format!("{magnitude} "),
+            (false, _) if amount.trim_start().starts_with('+') => format!("+{magnitude}"),
+            (false, _) => magnitude,
+        };
+    }
+
+    // Whether `amount` should be printed in red
+    pub fn is_red(&self, amount: &str) -> bool {
+        return self.negative == NegativeStyle::Red
+            && parse_amount(amount).is_ok_and(|value| value < 0.0);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum SymbolPosition {
+    // "$1,234.56"
+    Before,
+    // "1.234,56 €"
+    After,
+}
+
+// The currency a document is in, for companies billing in more than one.
+// Documents without one are printed in dollars with GenOptions::amount_format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CurrencyFormat {
+    // e.g. "$", "B$" or "€"
+    pub symbol: String,
+    // ISO 4217 code, printed below the item table when not empty
+    pub code: String,
+    pub symbol_position: SymbolPosition,
+    pub thousands_separator: Option<char>,
+    pub decimal_separator: char,
+}
+
+impl Default for CurrencyFormat {
+    fn default() -> Self {
+        return Self {
+            symbol: String::from("$"),
+            code: String::new(),
+            symbol_position: SymbolPosition::Before,
+            thousands_separator: Some(','),
+            decimal_separator: '.',
+        };
+    }
+}
+
+// Characters of the printed amount columns, the symbol included
+pub(crate) const AMOUNT_WIDTH: usize = 12;
+
+impl CurrencyFormat {
+    // Bahamian dollars, which are at par with US ones and circulate together
+    pub fn bahamian() -> Self {
+        return Self { symbol: String::from("B$"), code: String::from("BSD"), ..Self::default() };
+    }
+
+    // Euros with the separators of a language, e.g. "de"
+    pub fn euros(locale: &str) -> Self {
+        let separators = AmountFormat::for_locale(locale);
+        return Self {
+            symbol: String::from("€"),
+            code: String::from("EUR"),
+            symbol_position: SymbolPosition::After,
+            thousands_separator: separators.thousands_separator,
+            decimal_separator: separators.decimal_separator,
+        };
+    }
+
+    pub(crate) fn amount_format(&self, negative: NegativeStyle) -> AmountFormat {
+        return AmountFormat {
+            thousands_separator: self.thousands_separator,
+            decimal_separator: self.decimal_separator,
+            negative,
+        };
+    }
+
+    // An amount from AmountFormat::display with the symbol, e.g. "$12.00"
+    pub(crate) fn with_symbol(&self, amount: &str) -> String {
+        return match self.symbol_position {
+            SymbolPosition::Before => format!("{}{amount}", self.symbol),
+            SymbolPosition::After => format!("{amount} {}", self.symbol),
+        };
+    }
+}
+
+impl crate::ReceiptInfo {
+    // Rewrites the quantities, unit prices and discounts typed in the input
+    // format as plain amounts, so the rest of pre_pass can read them. Text
+    // which isn't an amount in that format, e.g. a percentage discount, is
+    // left for the later checks.
+    pub(crate) fn normalize_amounts(&mut self, input: &AmountFormat) {
+        let normalize = |field: &mut String| {
+            if let Ok(amount) = input.cleanup(field) {
+                *field = amount;
+            }
+        };
+        for line in &mut self.item_lines {
+            normalize(&mut line.quantity);
+            normalize(&mut line.unit_price);
+            if let Some(Discount::Text(text)) = &line.discount {
+                if let Ok(amount) = input.cleanup(text) {
+                    line.discount = Some(Discount::from_text(&amount));
+                }
+            }
+        }
+    }
+}
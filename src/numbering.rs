@@ -0,0 +1,89 @@
+use anyhow::{Error, Result, anyhow};
+use crate::{date, DocType, ReceiptInfo};
+
+// Document number formats some jurisdictions require, e.g. "F2026-000123":
+// a prefix, the fiscal year, and a zero padded sequence number which never
+// restarts within the year. The POS only keeps the sequence number, so
+// pre_pass rebuilds the number from the last run of digits in it, or rejects
+// numbers which aren't already in the format where the tax authority doesn't
+// allow them to be rewritten.
+
+#[derive(Debug, Clone)]
+pub struct NumberingFormat {
+    pub prefix: String,
+    // Month the fiscal year starts in, 1 for calendar years. The year is the
+    // one it starts in, from the document date. No year part when None.
+    pub fiscal_year_start: Option<u32>,
+    // Between the year and the sequence number
+    pub separator: String,
+    // Sequence numbers are zero padded to this many digits
+    pub digits: usize,
+    pub mode: NumberingMode,
+    // Document types it applies to, invoices when empty
+    pub doc_types: Vec<DocType>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumberingMode {
+    Reformat,
+    Reject,
+}
+
+impl NumberingFormat {
+    fn applies_to(&self, doc_type: &DocType) -> bool {
+        if self.doc_types.is_empty() {
+            return *doc_type == DocType::Invoice;
+        }
+        return self.doc_types.contains(doc_type);
+    }
+
+    // The number in this format for the sequence number in `doc_number`,
+    // dated `doc_date`
+    pub fn format(&self, doc_number: &str, doc_date: &str) -> Result<String, Error> {
+        let sequence = last_digits(doc_number)
+            .ok_or_else(|| anyhow!(format!("The document number `{doc_number}` has no sequence number")))?;
+        // Leading zeros aren't part of the sequence number
+        let sequence = match sequence.trim_start_matches('0') {
+            "" => "0",
+            sequence => sequence,
+        };
+        if sequence.len() > self.digits {
+            return Err(anyhow!(format!("The sequence number of `{doc_number}` is longer than {} digits", self.digits)));
+        }
+        let year = match self.fiscal_year_start {
+            Some(start) => {
+                let (year, month) = date::parse_year_month(doc_date)
+                    .ok_or_else(|| anyhow!(format!("Can't tell the fiscal year of `{doc_number}` from the date `{doc_date}`")))?;
+                let year = if (month as u32) < start { year - 1 } else { year };
+                format!("{year}{}", self.separator)
+            },
+            None => String::new(),
+        };
+        return Ok(format!("{}{year}{sequence:0>width$}", self.prefix, width = self.digits));
+    }
+}
+
+fn last_digits(text: &str) -> Option<&str> {
+    let end = text.rfind(|c: char| c.is_ascii_digit())? + 1;
+    let start = text[..end].rfind(|c: char| !c.is_ascii_digit()).map_or(0, |i| i + 1);
+    return Some(&text[start..end]);
+}
+
+impl ReceiptInfo {
+    pub(crate) fn apply_numbering(&mut self, formats: &[NumberingFormat]) -> Result<(), Error> {
+        let Some(format) = formats.iter().find(|format| format.applies_to(&self.doc_type)) else { return Ok(()) };
+        let formatted = format.format(&self.doc_number, &self.date)?;
+        if formatted == self.doc_number {
+            return Ok(());
+        }
+        return match format.mode {
+            NumberingMode::Reformat => {
+                self.doc_number = formatted;
+                Ok(())
+            },
+            NumberingMode::Reject => Err(anyhow!(format!(
+                "The document number `{}` isn't in the required format, it should be `{formatted}`", self.doc_number,
+            ))),
+        };
+    }
+}
@@ -0,0 +1,89 @@
+use printpdf::lopdf::{dictionary, Dictionary, Document, Object, Stream};
+use anyhow::{Error, Result, anyhow};
+use crate::stamp::{inherited, real, rect_from};
+
+// 2-up printing: the pages of several documents placed two to a landscape
+// Letter sheet, side by side with a dashed cut line between them. Each page
+// becomes a form XObject drawn scaled down, so links and form fields on the
+// original pages are dropped; this is meant for printed copies.
+
+// All in points
+const SHEET_WIDTH: f64 = 792.0;
+const SHEET_HEIGHT: f64 = 612.0;
+const SLOT_WIDTH: f64 = SHEET_WIDTH / 2.0;
+
+pub fn two_up_pdf(pdfs: &[Vec<u8>]) -> Result<Vec<u8>, Error> {
+    let mut doc = Document::with_version("1.5");
+    // (XObject, media box) of every page, in order
+    let mut pages = Vec::new();
+    for pdf in pdfs {
+        let mut other = Document::load_mem(pdf)?;
+        other.renumber_objects_with(doc.max_id + 1);
+        for page_id in other.get_pages().into_values() {
+            let media_box = inherited(&other, page_id, b"MediaBox")
+                .and_then(|media_box| rect_from(&media_box))
+                .unwrap_or([0.0, 0.0, 612.0, 792.0]);
+            let resources = match inherited(&other, page_id, b"Resources") {
+                Some(resources) => resources,
+                None => Object::Dictionary(Dictionary::new()),
+            };
+            let content = other.get_page_content(page_id)?;
+            let form = other.add_object(Stream::new(dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Form",
+                "BBox" => media_box.iter().map(|value| real(*value)).collect::<Vec<_>>(),
+                "Resources" => resources,
+            }, content));
+            pages.push((form, media_box));
+        }
+        doc.max_id = other.max_id;
+        doc.objects.extend(other.objects);
+    }
+    if pages.is_empty() {
+        return Err(anyhow!("There are no pages to print 2-up"));
+    }
+
+    let pages_id = doc.new_object_id();
+    let mut sheets = Vec::new();
+    for pair in pages.chunks(2) {
+        let mut xobjects = Dictionary::new();
+        let mut content = String::new();
+        for (slot, (form, media_box)) in pair.iter().enumerate() {
+            let [llx, lly, urx, ury] = *media_box;
+            let (width, height) = (urx - llx, ury - lly);
+            let scale = (SLOT_WIDTH / width).min(SHEET_HEIGHT / height);
+            let x = slot as f64 * SLOT_WIDTH + (SLOT_WIDTH - width * scale) / 2.0 - llx * scale;
+            let y = (SHEET_HEIGHT - height * scale) / 2.0 - lly * scale;
+            let name = format!("AccuTwoUp{slot}");
+            content.push_str(&format!("q {scale} 0 0 {scale} {x} {y} cm /{name} Do Q\n"));
+            xobjects.set(name, *form);
+        }
+        content.push_str(&format!("q [3 2] 0 d 0.5 w {SLOT_WIDTH} 0 m {SLOT_WIDTH} {SHEET_HEIGHT} l S Q\n"));
+        let contents = doc.add_object(Stream::new(Dictionary::new(), content.into_bytes()));
+        sheets.push(Object::Reference(doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "MediaBox" => vec![0.into(), 0.into(), real(SHEET_WIDTH), real(SHEET_HEIGHT)],
+            "Resources" => dictionary! { "XObject" => xobjects },
+            "Contents" => contents,
+        })));
+    }
+    let count = sheets.len() as i64;
+    doc.objects.insert(pages_id, Object::Dictionary(dictionary! {
+        "Type" => "Pages",
+        "Kids" => sheets,
+        "Count" => count,
+    }));
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    doc.trailer.set("Root", catalog_id);
+    // Drops the page trees of the source documents
+    doc.prune_objects();
+    doc.compress();
+
+    let mut bytes = Vec::new();
+    doc.save_to(&mut bytes)?;
+    return Ok(bytes);
+}
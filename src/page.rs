@@ -0,0 +1,93 @@
+use printpdf::lopdf::Document;
+use printpdf::{Mm, Pt};
+use anyhow::{Error, Result, anyhow};
+use crate::calibration::transform_page;
+use crate::stamp::{inherited, real, rect_from};
+
+// Paper sizes other than US Letter. The layout is drawn for Letter's width:
+// on wider or narrower paper each page is drawn at the layout width with the
+// height the paper's proportions give it, and scaled onto the paper in the
+// saved file. The header and the top of the item table move up with the top
+// of the page, so taller paper fits more lines before the totals, and the
+// rest of the document stays at the bottom.
+
+const LAYOUT_WIDTH: f64 = 612.0;
+const LETTER_HEIGHT: f64 = 792.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PageSize {
+    // 8.5" x 11"
+    Letter,
+    // 210mm x 297mm
+    A4,
+    // 8.5" x 14"
+    Legal,
+    // Width and height
+    Custom(Mm, Mm),
+}
+
+impl Default for PageSize {
+    fn default() -> Self {
+        return PageSize::Letter;
+    }
+}
+
+impl PageSize {
+    // Width and height in points
+    pub(crate) fn points(&self) -> (f64, f64) {
+        return match self {
+            PageSize::Letter => (612.0, 792.0),
+            PageSize::A4 => (Pt::from(Mm(210.0)).0, Pt::from(Mm(297.0)).0),
+            PageSize::Legal => (612.0, 1008.0),
+            PageSize::Custom(width, height) => (Pt::from(*width).0, Pt::from(*height).0),
+        };
+    }
+
+    // Height of the pages as they are drawn, in points at the layout width
+    pub(crate) fn layout_height(&self) -> f64 {
+        let (width, height) = self.points();
+        return height * LAYOUT_WIDTH / width;
+    }
+
+    // How far up the top of the page is from where it is on Letter, in points
+    pub(crate) fn top_offset(&self) -> f64 {
+        return self.layout_height() - LETTER_HEIGHT;
+    }
+
+    pub(crate) fn check(&self, table_top: f64, table_bottom: f64) -> Result<(), Error> {
+        let (width, height) = self.points();
+        if !(width > 0.0 && height > 0.0) {
+            return Err(anyhow!(format!("Invalid page size {width} x {height} pt")));
+        }
+        // Room for the column headers and a few lines
+        if table_top + self.top_offset() - table_bottom < 80.0 {
+            return Err(anyhow!(format!("The page size {width:.0} x {height:.0} pt is too short for the item table")));
+        }
+        return Ok(());
+    }
+}
+
+// Scales every page of a saved PDF onto the paper, keeping its proportions,
+// centered across and against the top. The pages of the document itself
+// fill the paper, appended Letter pages like the terms may not.
+pub(crate) fn fit_to_paper(pdf: &[u8], page_size: &PageSize) -> Result<Vec<u8>, Error> {
+    let (paper_width, paper_height) = page_size.points();
+    let mut doc = Document::load_mem(pdf)?;
+    for (_, page_id) in doc.get_pages() {
+        let [llx, lly, urx, ury] = inherited(&doc, page_id, b"MediaBox")
+            .and_then(|media_box| rect_from(&media_box))
+            .unwrap_or([0.0, 0.0, LAYOUT_WIDTH, LETTER_HEIGHT]);
+        let (width, height) = (urx - llx, ury - lly);
+        let scale = (paper_width / width).min(paper_height / height);
+        let x = (paper_width - width * scale) / 2.0 - llx * scale;
+        let y = paper_height - height * scale - lly * scale;
+        transform_page(&mut doc, page_id, [scale, 0.0, 0.0, scale, x, y])?;
+        let page = doc.get_object_mut(page_id)?.as_dict_mut()?;
+        page.set("MediaBox", vec![real(0.0), real(0.0), real(paper_width), real(paper_height)]);
+        // A crop box would still be in the old size
+        page.remove(b"CropBox");
+    }
+    let mut bytes = Vec::new();
+    doc.save_to(&mut bytes)?;
+    return Ok(bytes);
+}
@@ -0,0 +1,244 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use printpdf::lopdf::{dictionary, Dictionary, Document, Object, Stream, StringFormat};
+use sha2::{Digest, Sha256};
+use anyhow::{Error, Result, anyhow};
+use crate::{date, doc_type_label, ReceiptInfo};
+
+// PDF/A-3b for invoices which are archived for years. The saved document is
+// given an sRGB output intent and XMP metadata matching its document info,
+// and the things PDF/A forbids are taken out: interpolated images, hidden
+// annotations and optional content settings it doesn't allow. printpdf
+// embeds the fonts it draws with in full, fonts which aren't embedded are an
+// error rather than something to fix up. PDF/A-3 is also what lets the
+// document carry files for software to read, like Factur-X XML.
+
+const PRODUCER: &str = "accutools-core";
+const OUTPUT_CONDITION: &str = "sRGB IEC61966-2.1";
+
+// A file the document carries for software to read, e.g. Factur-X XML
+pub(crate) struct AssociatedFile {
+    pub name: &'static str,
+    pub mime_type: &'static str,
+    pub description: &'static str,
+    // How the file relates to the document, e.g. "Alternative" for the same
+    // content in another form
+    pub relationship: &'static str,
+    pub content: Vec<u8>,
+    // rdf:Description elements for the XMP metadata
+    pub metadata: String,
+}
+
+pub(crate) fn make_pdfa(pdf: &[u8], receipt: &ReceiptInfo, icc_profile: &[u8], file: Option<&AssociatedFile>) -> Result<Vec<u8>, Error> {
+    let mut doc = Document::load_mem(pdf)?;
+    doc.version = String::from("1.7");
+    let catalog_id = doc.trailer.get(b"Root")?.as_reference()?;
+    if doc.get_object(catalog_id)?.as_dict()?.has(b"AcroForm") {
+        // Their text fields are drawn by the viewer, in Helvetica
+        return Err(anyhow!("Fillable quotes can't be made PDF/A, turn off fillable_quote"));
+    }
+    check_fonts(&doc)?;
+    for object in doc.objects.values_mut() {
+        let dict = match object {
+            Object::Dictionary(dict) => dict,
+            Object::Stream(stream) => &mut stream.dict,
+            _ => continue,
+        };
+        if dict.get(b"Subtype").and_then(Object::as_name).ok() == Some(&b"Image"[..]) {
+            dict.remove(b"Interpolate");
+        }
+        if dict.get(b"Type").and_then(Object::as_name).ok() == Some(&b"Annot"[..]) {
+            // Printable, and nothing else
+            dict.set("F", 4);
+        }
+    }
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+    let seconds = now.as_secs() as i64;
+    let (day, time) = (date::iso_from_days(seconds / 86400), seconds % 86400);
+    let (hours, minutes, seconds) = (time / 3600, time / 60 % 60, time % 60);
+    let xmp_date = format!("{day}T{hours:02}:{minutes:02}:{seconds:02}+00:00");
+    let pdf_date = format!("D:{}{hours:02}{minutes:02}{seconds:02}+00'00'", day.replace('-', ""));
+    let title = format!("{} {}", doc_type_label(receipt.doc_type).text(false), receipt.doc_number);
+    // The same document keeps its id, every rendering of it is a new instance
+    let checksum = receipt.checksum();
+    let document_id = Sha256::digest(&checksum)[..16].to_vec();
+    let instance_id = Sha256::digest(format!("{checksum}{}", now.as_nanos()))[..16].to_vec();
+
+    let info = doc.add_object(dictionary! {
+        "Title" => text_string(&title),
+        "Producer" => text_string(PRODUCER),
+        "CreationDate" => Object::string_literal(pdf_date.as_str()),
+        "ModDate" => Object::string_literal(pdf_date.as_str()),
+    });
+    doc.trailer.set("Info", info);
+    doc.trailer.set("ID", vec![
+        Object::String(document_id.clone(), StringFormat::Hexadecimal),
+        Object::String(instance_id.clone(), StringFormat::Hexadecimal),
+    ]);
+
+    let xmp = format!(
+        r#"<?xpacket begin="{bom}" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/">
+<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+<rdf:Description rdf:about=""
+ xmlns:pdfaid="http://www.aiim.org/pdfa/ns/id/"
+ xmlns:dc="http://purl.org/dc/elements/1.1/"
+ xmlns:xmp="http://ns.adobe.com/xap/1.0/"
+ xmlns:pdf="http://ns.adobe.com/pdf/1.3/"
+ xmlns:xmpMM="http://ns.adobe.com/xap/1.0/mm/">
+<pdfaid:part>3</pdfaid:part>
+<pdfaid:conformance>B</pdfaid:conformance>
+<dc:format>application/pdf</dc:format>
+<dc:title><rdf:Alt><rdf:li xml:lang="x-default">{title}</rdf:li></rdf:Alt></dc:title>
+<xmp:CreateDate>{xmp_date}</xmp:CreateDate>
+<xmp:ModifyDate>{xmp_date}</xmp:ModifyDate>
+<xmp:MetadataDate>{xmp_date}</xmp:MetadataDate>
+<pdf:Producer>{PRODUCER}</pdf:Producer>
+<xmpMM:DocumentID>uuid:{document_id}</xmpMM:DocumentID>
+<xmpMM:InstanceID>uuid:{instance_id}</xmpMM:InstanceID>
+</rdf:Description>
+{extra_metadata}</rdf:RDF>
+</x:xmpmeta>
+<?xpacket end="w"?>"#,
+        bom = '\u{feff}',
+        title = escape_xml(&title),
+        document_id = uuid(&document_id),
+        instance_id = uuid(&instance_id),
+        extra_metadata = file.map_or("", |file| file.metadata.as_str()),
+    );
+    // The metadata has to stay readable without decoding, so it isn't
+    // compressed
+    let metadata = doc.add_object(Stream::new(dictionary! {
+        "Type" => "Metadata",
+        "Subtype" => "XML",
+    }, xmp.into_bytes()));
+    let profile = doc.add_object(Stream::new(dictionary! { "N" => 3 }, icc_profile.to_vec()));
+
+    let mut catalog = doc.get_object(catalog_id)?.as_dict()?.clone();
+    catalog.set("Metadata", metadata);
+    catalog.set("OutputIntents", vec![Object::Dictionary(dictionary! {
+        "Type" => "OutputIntent",
+        "S" => "GTS_PDFA1",
+        "OutputConditionIdentifier" => Object::string_literal(OUTPUT_CONDITION),
+        "Info" => Object::string_literal(OUTPUT_CONDITION),
+        "DestOutputProfile" => profile,
+    })]);
+    if let Some(file) = file {
+        let embedded = doc.add_object(Stream::new(dictionary! {
+            "Type" => "EmbeddedFile",
+            "Subtype" => file.mime_type,
+            "Params" => dictionary! {
+                "ModDate" => Object::string_literal(pdf_date.as_str()),
+                "Size" => file.content.len() as i64,
+            },
+        }, file.content.clone()));
+        let file_spec = doc.add_object(dictionary! {
+            "Type" => "Filespec",
+            "F" => Object::string_literal(file.name),
+            "UF" => text_string(file.name),
+            "Desc" => text_string(file.description),
+            "AFRelationship" => file.relationship,
+            "EF" => dictionary! {
+                "F" => embedded,
+                "UF" => embedded,
+            },
+        });
+        let mut names = match catalog.get(b"Names") {
+            Ok(names) => resolved_dict(&doc, names)?,
+            Err(_) => Dictionary::new(),
+        };
+        names.set("EmbeddedFiles", dictionary! {
+            "Names" => vec![Object::string_literal(file.name), Object::Reference(file_spec)],
+        });
+        catalog.set("Names", names);
+        catalog.set("AF", vec![Object::Reference(file_spec)]);
+    }
+    if let Ok(properties) = catalog.get(b"OCProperties") {
+        let mut properties = resolved_dict(&doc, properties)?;
+        if let Ok(config) = properties.get(b"D") {
+            let config = optional_content_config(resolved_dict(&doc, config)?, "Default");
+            properties.set("D", config);
+        }
+        if let Ok(configs) = properties.get(b"Configs") {
+            let mut cleaned = Vec::new();
+            for (index, config) in resolved(&doc, configs)?.as_array()?.iter().enumerate() {
+                cleaned.push(Object::Dictionary(optional_content_config(resolved_dict(&doc, config)?, &format!("Configuration {}", index + 1))));
+            }
+            properties.set("Configs", cleaned);
+        }
+        catalog.set("OCProperties", properties);
+    }
+    *doc.get_object_mut(catalog_id)? = Object::Dictionary(catalog);
+
+    let mut bytes = Vec::new();
+    doc.save_to(&mut bytes)?;
+    return Ok(bytes);
+}
+
+// Every font but Type 3 ones, whose glyphs are in the document, has to have
+// its font program embedded. Type 0 fonts are checked through their
+// descendant fonts.
+fn check_fonts(doc: &Document) -> Result<(), Error> {
+    for object in doc.objects.values() {
+        let Ok(font) = object.as_dict() else { continue };
+        if font.get(b"Type").and_then(Object::as_name).ok() != Some(&b"Font"[..]) {
+            continue;
+        }
+        let subtype = font.get(b"Subtype").and_then(Object::as_name).unwrap_or(b"");
+        if subtype == b"Type0" || subtype == b"Type3" {
+            continue;
+        }
+        let embedded = font.get(b"FontDescriptor")
+            .ok()
+            .and_then(|descriptor| resolved_dict(doc, descriptor).ok())
+            .is_some_and(|descriptor| [&b"FontFile"[..], b"FontFile2", b"FontFile3"].iter().any(|key| descriptor.has(key)));
+        if !embedded {
+            let name = font.get(b"BaseFont").and_then(Object::as_name).unwrap_or(b"unnamed");
+            return Err(anyhow!(format!("PDF/A needs every font embedded, the font `{}` isn't", String::from_utf8_lossy(name))));
+        }
+    }
+    return Ok(());
+}
+
+// Configurations need a name, and can't change visibility on their own
+fn optional_content_config(mut config: Dictionary, name: &str) -> Dictionary {
+    if !config.has(b"Name") {
+        config.set("Name", Object::string_literal(name));
+    }
+    config.remove(b"AS");
+    return config;
+}
+
+fn resolved<'a>(doc: &'a Document, object: &'a Object) -> Result<&'a Object, Error> {
+    return match object {
+        Object::Reference(id) => Ok(doc.get_object(*id)?),
+        other => Ok(other),
+    };
+}
+
+fn resolved_dict(doc: &Document, object: &Object) -> Result<Dictionary, Error> {
+    return Ok(resolved(doc, object)?.as_dict()?.clone());
+}
+
+// PDFDocEncoding only covers ASCII the same as Unicode, anything else is
+// written as UTF-16 with a byte order mark
+fn text_string(text: &str) -> Object {
+    if text.is_ascii() {
+        return Object::string_literal(text);
+    }
+    let mut bytes = vec![0xFE, 0xFF];
+    for unit in text.encode_utf16() {
+        bytes.extend_from_slice(&unit.to_be_bytes());
+    }
+    return Object::String(bytes, StringFormat::Hexadecimal);
+}
+
+pub(crate) fn escape_xml(text: &str) -> String {
+    return text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;");
+}
+
+// 16 bytes as 8-4-4-4-12 hex
+fn uuid(bytes: &[u8]) -> String {
+    let hex: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    return format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32]);
+}
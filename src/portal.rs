@@ -0,0 +1,49 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use anyhow::{Error, Result, anyhow};
+
+// Signed download links for the customer self-service portal. The portal
+// recomputes the signature from the document number and expiry with the
+// shared secret, so customers don't need an account to fetch their copy.
+
+pub struct PortalLink {
+    // e.g. "https://portal.example.com/documents"
+    pub base_url: String,
+    // Shared with the portal
+    pub secret: Vec<u8>,
+    // Unix timestamp after which the portal refuses the link
+    pub expires: u64,
+}
+
+impl std::fmt::Debug for PortalLink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return f.debug_struct("PortalLink")
+            .field("base_url", &self.base_url)
+            .field("secret", &"<redacted>")
+            .field("expires", &self.expires)
+            .finish();
+    }
+}
+
+impl PortalLink {
+    // The signature covers "{doc_number}.{expires}"
+    pub fn signature(&self, doc_number: &str) -> Result<String, Error> {
+        let mut mac = match Hmac::<Sha256>::new_from_slice(&self.secret) {
+            Ok(mac) => mac,
+            Err(e) => return Err(anyhow!(format!("Invalid portal secret. Reason: `{e}`"))),
+        };
+        mac.update(format!("{doc_number}.{}", self.expires).as_bytes());
+        let signature = mac.finalize().into_bytes();
+        return Ok(signature.iter().map(|byte| format!("{byte:02x}")).collect());
+    }
+
+    pub fn url(&self, doc_number: &str) -> Result<String, Error> {
+        return Ok(format!(
+            "{}/{}?expires={}&signature={}",
+            self.base_url.trim_end_matches('/'),
+            crate::percent_encode(doc_number),
+            self.expires,
+            self.signature(doc_number)?,
+        ));
+    }
+}
@@ -0,0 +1,53 @@
+use printpdf::{Mm, PdfLayerReference, Point, Line};
+use qrcode::{Color, EcLevel, QrCode};
+use anyhow::{Error, Result, anyhow};
+
+// QR codes drawn as filled rectangles, so they stay sharp at any zoom and
+// don't need the image support.
+
+// Light modules required around the code for scanners to find it
+const QUIET_ZONE: usize = 4;
+
+// Draws a QR code for `data` filling the square with its lower left corner at
+// (x, y), quiet zone included
+pub(crate) fn draw_qr(layer: &PdfLayerReference, data: &str, x: Mm, y: Mm, size: Mm) -> Result<(), Error> {
+    let code = match QrCode::with_error_correction_level(data, EcLevel::M) {
+        Ok(code) => code,
+        Err(e) => return Err(anyhow!(format!("Could not encode `{data}` as a QR code. Reason: `{e}`"))),
+    };
+    let width = code.width();
+    let module = size / (width + QUIET_ZONE * 2) as f64;
+    let colors = code.to_colors();
+    for row in 0..width {
+        // One rectangle per run of dark modules in the row
+        let mut column = 0;
+        while column < width {
+            if colors[row * width + column] != Color::Dark {
+                column += 1;
+                continue;
+            }
+            let start = column;
+            while column < width && colors[row * width + column] == Color::Dark {
+                column += 1;
+            }
+            let x1 = x + module * (QUIET_ZONE + start) as f64;
+            let x2 = x + module * (QUIET_ZONE + column) as f64;
+            // Rows count down from the top
+            let y2 = y + size - module * (QUIET_ZONE + row) as f64;
+            let y1 = y2 - module;
+            layer.add_shape(Line {
+                points: vec![
+                    (Point::new(x1, y1), false),
+                    (Point::new(x2, y1), false),
+                    (Point::new(x2, y2), false),
+                    (Point::new(x1, y2), false),
+                ],
+                is_closed: true,
+                has_fill: true,
+                has_stroke: false,
+                is_clipping_path: false,
+            });
+        }
+    }
+    return Ok(());
+}
@@ -0,0 +1,100 @@
+use anyhow::{Error, Result, anyhow};
+use crate::money::{self, AmountFormat, CurrencyFormat, Money, Rounding};
+use crate::{date, labels, ItemLine, ReceiptInfo};
+
+// Equipment rentals billed by the time out, e.g. a skid steer at $85.00 a
+// day. The rental period is printed below the description, and pre_pass
+// warns when the amount of the line isn't the billed duration times the
+// rate. Started periods are billed in full, so 25 hours are 2 days.
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rental {
+    // Date and time, e.g. "2026-03-02 08:00" or "03/02/2026 8:00 AM"
+    pub start: String,
+    pub end: String,
+    // Per period
+    pub rate: String,
+    pub period: RentalPeriod,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum RentalPeriod {
+    Hour,
+    Day,
+}
+
+impl RentalPeriod {
+    fn minutes(self) -> i64 {
+        return match self {
+            RentalPeriod::Hour => 60,
+            RentalPeriod::Day => 24 * 60,
+        };
+    }
+
+    fn label(self) -> &'static labels::Label {
+        return match self {
+            RentalPeriod::Hour => &labels::HOURS,
+            RentalPeriod::Day => &labels::DAYS,
+        };
+    }
+}
+
+impl Rental {
+    // Number of periods billed, at least one
+    pub fn duration(&self) -> Result<i64, Error> {
+        let parse = |text: &str| {
+            return date::parse_minutes(text).ok_or_else(|| anyhow!(format!("`{text}` is not a valid rental date and time")));
+        };
+        let (start, end) = (parse(&self.start)?, parse(&self.end)?);
+        if end < start {
+            return Err(anyhow!(format!("The rental ending {} starts after it ends, on {}", self.end, self.start)));
+        }
+        let period = self.period.minutes();
+        return Ok(((end - start + period - 1) / period).max(1));
+    }
+
+    // e.g. "Rental: 2026-03-02 08:00 - 2026-03-04 17:00, 3 days @ $85.00"
+    pub(crate) fn describe(&self, bilingual: bool, format: &AmountFormat, currency: &CurrencyFormat) -> Result<String, Error> {
+        let rate = currency.with_symbol(&format.display(&money::normalize_unit_price(&self.rate)));
+        return Ok(format!(
+            "{} {} - {}, {} {} @ {rate}",
+            labels::RENTAL.text(bilingual), self.start.trim(), self.end.trim(), self.duration()?, self.period.label().text(bilingual),
+        ));
+    }
+
+    fn expected_amount(&self, rounding: &Rounding) -> Result<Money, Error> {
+        // The rate is a unit price and can have fractions of a cent
+        return Ok(Money::from_f64(self.duration()? as f64 * money::parse_amount(&self.rate)?, rounding));
+    }
+}
+
+impl ReceiptInfo {
+    // Unlike the other line checks, dates which can't be read are errors:
+    // the period couldn't be printed either
+    pub(crate) fn check_rentals(&mut self, rounding: &Rounding) -> Result<(), Error> {
+        let mut warnings = Vec::new();
+        for line in &self.item_lines {
+            let Some(rental) = &line.rental else { continue };
+            let expected = rental.expected_amount(rounding)?;
+            let Some(amount) = line.amount else { continue };
+            if self.draft || line.discount.is_some() {
+                continue;
+            }
+            if expected != amount {
+                warnings.push(rental_warning(line, rental, amount, expected)?);
+            }
+        }
+        self.warnings.append(&mut warnings);
+        return Ok(());
+    }
+}
+
+fn rental_warning(line: &ItemLine, rental: &Rental, amount: Money, expected: Money) -> Result<String, Error> {
+    return Ok(format!(
+        "Rental line `{}` ({}) totals {amount}, but {} {} x {} comes to {expected}",
+        line.code, line.description, rental.duration()?, rental.period.label().en, rental.rate,
+    ));
+}
@@ -0,0 +1,237 @@
+use printpdf::{PdfDocument, PdfDocumentReference, PdfLayerReference, IndirectFontRef, Mm, Pt};
+use std::collections::BTreeMap;
+use anyhow::{Error, Result, anyhow};
+use crate::{date, export, is_cash, DocType, Money, PdfResources, ReceiptInfo};
+use crate::drawing::QuickShapes;
+
+// Sales summary over a date range, built from documents the caller has kept
+// (e.g. the daemon's archive): totals per day or week, per document type,
+// per tender and per tax.
+
+const PAGE_WIDTH: Pt = Pt(612.0);
+const PAGE_HEIGHT: Pt = Pt(792.0);
+const LEFT_MARGIN: Pt = Pt(54.0);
+const RIGHT_MARGIN: Pt = Pt(558.0);
+const TOP_MARGIN: Pt = Pt(738.0);
+const BOTTOM_MARGIN: Pt = Pt(54.0);
+const TABLE_SIZE: f64 = 9.0;
+const LINE_HEIGHT: Pt = Pt(12.0);
+// Monospace columns, in characters
+const LABEL_WIDTH: usize = 24;
+const COLUMN_WIDTH: usize = 14;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportPeriod {
+    Daily,
+    // Weeks start on Monday
+    Weekly,
+}
+
+#[derive(Default)]
+struct Sums {
+    count: usize,
+    sales: Money,
+    tax: Money,
+}
+
+impl Sums {
+    fn add(&mut self, count: usize, sales: Money, tax: Money) -> Result<(), Error> {
+        self.count += count;
+        self.sales = self.sales.checked_add(sales)?;
+        self.tax = self.tax.checked_add(tax)?;
+        return Ok(());
+    }
+
+    fn total(&self) -> Result<String, Error> {
+        return Ok(self.sales.checked_add(self.tax)?.to_string());
+    }
+}
+
+// Total and tax of a document, taken from its totals
+fn document_amounts(document: &ReceiptInfo) -> Result<(Money, Vec<(String, Money)>), Error> {
+    let total = document.total().or(document.amount_due).unwrap_or(Money::ZERO);
+    let mut taxes = Vec::new();
+    for amount in &document.totals {
+        if export::is_tax_total(&amount.name) {
+            taxes.push((amount.name.trim_end_matches(':').to_owned(), amount.value));
+        }
+    }
+    return Ok((total, taxes));
+}
+
+pub fn gen_sales_summary(documents: &[ReceiptInfo], from: &str, to: &str, period: ReportPeriod, resources: &PdfResources) -> Result<PdfDocumentReference, Error> {
+    let (first_day, last_day) = match (date::parse_days(from), date::parse_days(to)) {
+        (Some(first_day), Some(last_day)) => (first_day, last_day),
+        _ => return Err(anyhow!(format!("Could not read the report range `{from}` to `{to}`"))),
+    };
+
+    let mut by_period: BTreeMap<i64, Sums> = BTreeMap::new();
+    let mut by_type: BTreeMap<&'static str, Sums> = BTreeMap::new();
+    let mut by_tender: BTreeMap<String, (usize, Money)> = BTreeMap::new();
+    let mut by_tax: BTreeMap<String, Money> = BTreeMap::new();
+    let mut unreadable_dates = 0;
+    for document in documents {
+        let day = match date::parse_days(&document.date) {
+            Some(day) => day,
+            None => {
+                unreadable_dates += 1;
+                continue;
+            },
+        };
+        if day < first_day || day > last_day {
+            continue;
+        }
+        let (total, taxes) = document_amounts(document)?;
+        let tax = Money::checked_sum(taxes.iter().map(|(_, amount)| *amount))?;
+        let sales = total.checked_sub(tax)?;
+        by_type.entry(document.doc_type.slug()).or_default().add(1, sales, tax)?;
+        // Only invoices and receipts are sales
+        if !matches!(document.doc_type, DocType::Invoice | DocType::Receipt) {
+            continue;
+        }
+        let period_start = match period {
+            ReportPeriod::Daily => day,
+            // 1970-01-01 was a Thursday
+            ReportPeriod::Weekly => day - (day + 3).rem_euclid(7),
+        };
+        by_period.entry(period_start).or_default().add(1, sales, tax)?;
+        for (name, amount) in taxes {
+            let sum = by_tax.entry(name).or_default();
+            *sum = sum.checked_add(amount)?;
+        }
+        // Change comes out of the cash drawer, or the last tender, as in export.rs
+        let change_tender = document.payments
+            .iter()
+            .position(|tender| is_cash(&tender.name))
+            .or(document.payments.len().checked_sub(1));
+        for (index, tender) in document.payments.iter().enumerate() {
+            let value = match document.change_due {
+                Some(change_due) if Some(index) == change_tender => tender.value.checked_sub(change_due)?,
+                _ => tender.value,
+            };
+            let entry = by_tender.entry(tender.name.clone()).or_default();
+            entry.0 += 1;
+            entry.1 = entry.1.checked_add(value)?;
+        }
+    }
+
+    let doc = PdfDocument::empty("Sales Summary");
+    let mut page = Page::new(&doc, resources)?;
+    let company_name = documents.first().map(|document| document.company_name.as_str()).unwrap_or("");
+    page.title(&format!("{company_name} Sales Summary"));
+    page.text(&format!("{} to {}", date::iso_from_days(first_day), date::iso_from_days(last_day)));
+    if unreadable_dates > 0 {
+        page.text(&format!("{unreadable_dates} documents were left out because their date could not be read"));
+    }
+
+    let period_name = match period {
+        ReportPeriod::Daily => "Day",
+        ReportPeriod::Weekly => "Week of",
+    };
+    page.heading(&[period_name, "Documents", "Sales", "Tax", "Total"]);
+    let mut grand_total = Sums::default();
+    for (day, sums) in &by_period {
+        page.row(&date::iso_from_days(*day), &[sums.count.to_string(), sums.sales.to_string(), sums.tax.to_string(), sums.total()?]);
+        grand_total.add(sums.count, sums.sales, sums.tax)?;
+    }
+    page.rule();
+    page.row("Total", &[grand_total.count.to_string(), grand_total.sales.to_string(), grand_total.tax.to_string(), grand_total.total()?]);
+
+    page.heading(&["Document Type", "Documents", "Total"]);
+    for (doc_type, sums) in &by_type {
+        page.row(doc_type, &[sums.count.to_string(), sums.total()?]);
+    }
+
+    page.heading(&["Tender", "Payments", "Amount"]);
+    for (tender, (count, amount)) in &by_tender {
+        page.row(tender, &[count.to_string(), amount.to_string()]);
+    }
+
+    page.heading(&["Tax", "Amount"]);
+    for (tax, amount) in &by_tax {
+        page.row(tax, &[amount.to_string()]);
+    }
+    return Ok(doc);
+}
+
+// Top-to-bottom cursor over as many pages as the report needs
+struct Page<'a> {
+    doc: &'a PdfDocumentReference,
+    layer: PdfLayerReference,
+    cursor_y: Mm,
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    mono: IndirectFontRef,
+}
+
+impl<'a> Page<'a> {
+    fn new(doc: &'a PdfDocumentReference, resources: &PdfResources) -> Result<Self, Error> {
+        return Ok(Self {
+            doc,
+            layer: Self::new_layer(doc),
+            cursor_y: TOP_MARGIN.into(),
+            regular: doc.add_external_font(resources.font_regular.as_ref())?,
+            bold: doc.add_external_font(resources.font_bold.as_ref())?,
+            mono: doc.add_external_font(resources.font_mono.as_ref())?,
+        });
+    }
+
+    fn new_layer(doc: &PdfDocumentReference) -> PdfLayerReference {
+        let (page, layer) = doc.add_page(PAGE_WIDTH.into(), PAGE_HEIGHT.into(), "Layer 1");
+        return doc.get_page(page).get_layer(layer);
+    }
+
+    // Starts a new page unless `height` still fits on this one
+    fn reserve(&mut self, height: Mm) {
+        if self.cursor_y - height < BOTTOM_MARGIN.into() {
+            self.layer = Self::new_layer(self.doc);
+            self.cursor_y = TOP_MARGIN.into();
+        }
+    }
+
+    fn advance(&mut self, height: Mm) {
+        self.reserve(height);
+        self.cursor_y -= height;
+    }
+
+    fn title(&mut self, text: &str) {
+        self.advance(Pt(16.0).into());
+        self.layer.use_text(text, 16.0, LEFT_MARGIN.into(), self.cursor_y, &self.bold);
+    }
+
+    fn text(&mut self, text: &str) {
+        self.advance(Pt(14.0).into());
+        self.layer.use_text(text, 10.0, LEFT_MARGIN.into(), self.cursor_y, &self.regular);
+    }
+
+    // Column headings of a new table, kept together with its first row
+    fn heading(&mut self, columns: &[&str]) {
+        let spacing: Mm = Pt(8.0).into();
+        self.reserve(spacing + Into::<Mm>::into(LINE_HEIGHT) * 2.0);
+        self.cursor_y -= spacing;
+        self.advance(LINE_HEIGHT.into());
+        self.layer.use_text(columns[0], TABLE_SIZE, LEFT_MARGIN.into(), self.cursor_y, &self.bold);
+        for (i, column) in columns[1..].iter().enumerate() {
+            self.layer.use_text(format!("{column:>width$}", width = COLUMN_WIDTH - 2), TABLE_SIZE, self.column_x(i), self.cursor_y, &self.mono);
+        }
+        self.rule();
+    }
+
+    fn row(&mut self, label: &str, values: &[String]) {
+        self.advance(LINE_HEIGHT.into());
+        self.layer.use_text(label, TABLE_SIZE, LEFT_MARGIN.into(), self.cursor_y, &self.regular);
+        for (i, value) in values.iter().enumerate() {
+            self.layer.use_text(format!("{value:>width$}", width = COLUMN_WIDTH - 2), TABLE_SIZE, self.column_x(i), self.cursor_y, &self.mono);
+        }
+    }
+
+    fn rule(&mut self) {
+        let y = self.cursor_y - Pt(3.0).into();
+        self.layer.add_line(LEFT_MARGIN.into(), y, RIGHT_MARGIN.into(), y);
+    }
+
+    fn column_x(&self, column: usize) -> Mm {
+        let advance = TABLE_SIZE * 0.6;
+        return Pt(LEFT_MARGIN.0 + advance * (LABEL_WIDTH + COLUMN_WIDTH * column) as f64).into();
+    }
+}
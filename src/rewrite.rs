@@ -0,0 +1,49 @@
+use anyhow::{Error, Result, anyhow};
+use regex::Regex;
+use crate::ReceiptInfo;
+
+// Rewrites of the item descriptions, for the abbreviations the POS is full
+// of, e.g. "3/4 CRU GR" becomes "3/4\" Crushed Gravel". pre_pass applies the
+// rules to every description in order, so a later rule sees what the
+// earlier ones made of it.
+
+#[derive(Debug, Clone)]
+pub enum DescriptionRule {
+    // Every occurrence of the text, matching case
+    Replace { find: String, with: String },
+    // Every match of the pattern. The replacement may refer to groups, e.g.
+    // "$1 ft".
+    Regex { pattern: Regex, with: String },
+}
+
+impl DescriptionRule {
+    pub fn replace(find: &str, with: &str) -> Self {
+        return DescriptionRule::Replace { find: find.to_owned(), with: with.to_owned() };
+    }
+
+    pub fn regex(pattern: &str, with: &str) -> Result<Self, Error> {
+        let pattern = Regex::new(pattern).map_err(|err| anyhow!(format!("Invalid description pattern `{pattern}`: {err}")))?;
+        return Ok(DescriptionRule::Regex { pattern, with: with.to_owned() });
+    }
+
+    fn apply(&self, description: &str) -> String {
+        return match self {
+            DescriptionRule::Replace { find, with } if !find.is_empty() => description.replace(find.as_str(), with),
+            DescriptionRule::Replace { .. } => description.to_owned(),
+            DescriptionRule::Regex { pattern, with } => pattern.replace_all(description, with.as_str()).into_owned(),
+        };
+    }
+}
+
+impl ReceiptInfo {
+    pub(crate) fn rewrite_descriptions(&mut self, rules: &[DescriptionRule]) {
+        if rules.is_empty() {
+            return;
+        }
+        for line in &mut self.item_lines {
+            for rule in rules {
+                line.description = rule.apply(&line.description);
+            }
+        }
+    }
+}
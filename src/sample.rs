@@ -0,0 +1,203 @@
+use crate::{Amount, DocType, Discount, ItemLine, Money, ReceiptInfo, Rounding};
+
+// Made up documents for demos, load tests and checking layouts, so no real
+// customer's data has to leave the archive. The same seed always gives the
+// same documents. Besides an ordinary mix they include the cases layouts get
+// wrong: long names and descriptions, accents, returns, zero priced lines,
+// large amounts and documents long enough to continue on further pages.
+
+const FIRST_NAMES: [&str; 14] = [
+    "Alice", "Marcus", "Priya", "Tom", "Zoë", "Kwame", "Siobhán", "Luis", "Mei", "Olu", "Anne-Marie", "Dmitri", "Grace", "Rafael",
+];
+const LAST_NAMES: [&str; 14] = [
+    "Rolle", "Ferguson", "O'Neil", "Ångström", "Nguyen", "Bethel", "Smith", "Knowles", "Cartwright-Williamson", "Dubois", "Moss",
+    "Kowalczyk", "Pinder", "Sawyer",
+];
+const STREETS: [&str; 8] = [
+    "Harbour Road", "Old Fort Lane", "Chestnut Street", "Queen's Highway", "Marsh End", "Rue des Érables", "Hillside Avenue",
+    "Unit 4, Industrial Park Way",
+];
+const TOWNS: [&str; 6] = ["Nassau", "Freeport", "Springfield", "Lakeside", "Montréal", "Port Royal"];
+const BUSINESSES: [&str; 5] = ["Construction Ltd.", "Roofing & Repairs", "Developments", "Landscaping Co.", "Property Management"];
+const EMPLOYEES: [&str; 5] = ["JD", "Sam", "Keisha", "Mike R.", "Front Counter"];
+
+// Code, description, unit of measure and unit price in cents
+const PRODUCTS: [(&str, &str, &str, i64); 16] = [
+    ("2X4-8", "2x4x8 SPF Stud", "EA", 649),
+    ("2X6-12", "2x6x12 Pressure Treated #2", "EA", 1899),
+    ("PLY-34", "3/4\" CDX Plywood 4x8", "SHT", 5495),
+    ("CEM-94", "Portland Cement Type I/II 94 lb", "BAG", 1675),
+    ("SAND", "Washed Sand", "TON", 4200),
+    ("REBAR-4", "#4 Rebar 20'", "EA", 1150),
+    ("NAIL-16", "16d Common Nails", "LB", 289),
+    ("SHNG-AR", "Architectural Shingles, Weathered Wood", "BDL", 3849),
+    ("PVC-3", "3\" PVC DWV Pipe Schedule 40 x 10'", "EA", 2675),
+    ("WIRE-12", "12/2 NM-B Romex with Ground", "FT", 89),
+    ("BLK-8", "8\" Concrete Block", "EA", 315),
+    ("PAINT-EXT", "Exterior Acrylic Latex Paint Satin Finish, Tinted to Customer Sample (Non-Returnable)", "GAL", 5299),
+    ("SCRW-3", "#9 x 3\" Exterior Deck Screws, 5 lb Box", "BOX", 3999),
+    ("INS-R13", "R-13 Kraft Faced Insulation Batts", "BDL", 6150),
+    ("DEL", "Delivery Charge", "EA", 7500),
+    ("GEN-20K", "Standby Generator 20kW, Installed", "EA", 1_249_999),
+];
+
+pub struct SampleGenerator {
+    state: u64,
+    next_number: u64,
+}
+
+impl SampleGenerator {
+    pub fn new(seed: u64) -> Self {
+        let mut generator = Self { state: seed, next_number: 0 };
+        generator.next_number = 100_000 + generator.below(800_000);
+        return generator;
+    }
+
+    // SplitMix64, plenty for made up data and needs no dependency
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        return z ^ (z >> 31);
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        return self.next() % bound.max(1);
+    }
+
+    // True `percent` times in a hundred
+    fn chance(&mut self, percent: u64) -> bool {
+        return self.below(100) < percent;
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        return &items[self.below(items.len() as u64) as usize];
+    }
+
+    fn customer(&mut self) -> String {
+        let mut name = format!("{} {}", self.pick(&FIRST_NAMES), self.pick(&LAST_NAMES));
+        if self.chance(30) {
+            name = format!("{} {}", self.pick(&LAST_NAMES), self.pick(&BUSINESSES));
+        }
+        let street = format!("{} {}", 1 + self.below(400), self.pick(&STREETS));
+        let phone = format!("555-{:04}", self.below(10_000));
+        return format!("{name}\n{street}\n{}\n{phone}", self.pick(&TOWNS));
+    }
+
+    fn item_line(&mut self, doc_type: DocType, rounding: &Rounding) -> ItemLine {
+        let (code, description, uom, price) = *self.pick(&PRODUCTS);
+        let mut price = Money::from_cents(price);
+        let mut quantity = 1 + self.below(if uom == "FT" || uom == "LB" { 250 } else { 24 }) as i64;
+        if self.chance(4) {
+            price = Money::ZERO;
+        } else if self.chance(2) {
+            price = Money::from_cents(9_999_999);
+        }
+        // Returns only happen at the till
+        if doc_type == DocType::Receipt && self.chance(5) {
+            quantity = -quantity.min(3);
+        }
+        let quantity_text = if uom == "TON" && self.chance(50) {
+            format!("{quantity}.5")
+        } else {
+            quantity.to_string()
+        };
+        let factor = quantity_text.parse::<f64>().unwrap_or(1.0);
+        let gross = price.times(factor, rounding);
+        let discount = if doc_type != DocType::Receipt && !gross.is_negative() && self.chance(15) {
+            Some(gross.times(0.1, rounding))
+        } else {
+            None
+        };
+        // Prices are at most 99,999.99 and quantities small, so sample amounts
+        // can't overflow
+        let amount = gross.checked_sub(discount.unwrap_or(Money::ZERO)).expect("sample amounts are small");
+        return ItemLine {
+            code: code.to_owned(),
+            description: description.to_owned(),
+            quantity: quantity_text,
+            unit_price: price.to_string(),
+            amount: Some(amount),
+            uom: uom.to_owned(),
+            discount: discount.map(Discount::Amount),
+            taxable: code != "DEL" && self.chance(85),
+            price_override: self.chance(3),
+            ..ItemLine::default()
+        };
+    }
+
+    pub fn document(&mut self) -> ReceiptInfo {
+        let rounding = Rounding::default();
+        let doc_type = match self.below(10) {
+            0..=4 => DocType::Invoice,
+            5..=7 => DocType::Receipt,
+            _ => DocType::Quote,
+        };
+        // Now and then one long enough to continue on further pages
+        let line_count = if self.chance(10) { 25 + self.below(40) } else { 1 + self.below(8) };
+        let item_lines: Vec<ItemLine> = (0..line_count).map(|_| self.item_line(doc_type, &rounding)).collect();
+
+        let subtotal = Money::checked_sum(item_lines.iter().filter_map(|line| line.amount)).expect("sample amounts are small");
+        let taxable = Money::checked_sum(item_lines.iter().filter(|line| line.taxable).filter_map(|line| line.amount)).expect("sample amounts are small");
+        let vat = taxable.times(0.1, &rounding);
+        let total = subtotal.checked_add(vat).expect("sample amounts are small");
+        let amount = |name: &str, value: Money| Amount { name: name.to_owned(), value };
+        let totals = vec![amount("Subtotal:", subtotal), amount("VAT 10%:", vat), amount("Total:", total)];
+        let (payments, amount_due) = match doc_type {
+            DocType::Receipt if !total.is_negative() => {
+                // Cash rounded up to the next $20 leaves change
+                let tender = if self.chance(50) {
+                    amount("Card", total)
+                } else {
+                    amount("Cash", Money::from_cents((total.cents() / 2000 + 1) * 2000))
+                };
+                (vec![tender], None)
+            },
+            DocType::Receipt => (vec![amount("Refund to Card", total)], None),
+            _ => (Vec::new(), Some(total)),
+        };
+
+        self.next_number += 1;
+        let date = format!("{:02}/{:02}/{}", 1 + self.below(12), 1 + self.below(28), 2024 + self.below(3));
+        let title = match doc_type {
+            DocType::Invoice => "Invoice",
+            DocType::Quote => "Quote",
+            _ => "Receipt",
+        };
+        let notes = if self.chance(20) {
+            String::from("Please call ahead before delivery. Gate code is **4417**; leave materials by the side entrance.")
+        } else {
+            String::new()
+        };
+        let employee = self.pick(&EMPLOYEES).to_string();
+        return ReceiptInfo {
+            title: title.to_owned(),
+            date,
+            company_name: String::from("Sample Building Supply"),
+            company_info_line: String::from("1 Example Road · 555-0100"),
+            customer_info: self.customer(),
+            transaction_number: format!("T{}", self.next_number),
+            order_id: if self.chance(25) { format!("PO-{}", self.below(100_000)) } else { String::new() },
+            vat_number: if self.chance(40) { format!("VAT{:09}", self.below(1_000_000_000)) } else { String::new() },
+            doc_number: self.next_number.to_string(),
+            doc_type,
+            item_lines,
+            delivery_tickets: if doc_type == DocType::Invoice && self.chance(30) { format!("D{}", self.below(10_000)) } else { String::new() },
+            weigh_tickets: String::new(),
+            totals,
+            payments,
+            amount_due,
+            employee,
+            slogan: String::from("Sample data, not a real document"),
+            notes,
+            draft: doc_type == DocType::Quote && self.chance(20),
+            ..ReceiptInfo::default()
+        };
+    }
+}
+
+pub fn sample_documents(seed: u64, count: usize) -> Vec<ReceiptInfo> {
+    let mut generator = SampleGenerator::new(seed);
+    return (0..count).map(|_| generator.document()).collect();
+}
@@ -0,0 +1,89 @@
+use anyhow::{Error, Result, anyhow};
+use crate::{date, PdfStore, ReceiptInfo};
+
+// Search over documents the caller has kept (e.g. the daemon's archive) by
+// customer, item description, total and date. Text matches ignore case and
+// every word of it has to appear, in any order. Empty fields match anything.
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchQuery {
+    // Anywhere in the printed text of the document
+    pub text: String,
+    pub customer: String,
+    // Any of the item lines
+    pub description: String,
+    // Of the document total, inclusive
+    pub min_amount: String,
+    pub max_amount: String,
+    // Document dates, inclusive
+    pub from: String,
+    pub to: String,
+}
+
+fn contains_words(text: &str, words: &str) -> bool {
+    let text = text.to_lowercase();
+    return words.to_lowercase().split_whitespace().all(|word| text.contains(word));
+}
+
+impl SearchQuery {
+    pub fn matches(&self, document: &ReceiptInfo) -> Result<bool, Error> {
+        if !self.text.trim().is_empty() && !contains_words(&document.printed_text().join("\n"), &self.text) {
+            return Ok(false);
+        }
+        if !contains_words(&document.customer_info, &self.customer) {
+            return Ok(false);
+        }
+        if !self.description.trim().is_empty() && !document.item_lines.iter().any(|line| contains_words(&line.description, &self.description)) {
+            return Ok(false);
+        }
+
+        if !self.from.is_empty() || !self.to.is_empty() {
+            let bound = |text: &str, open: i64| match text {
+                "" => Ok(open),
+                text => date::parse_days(text).ok_or_else(|| anyhow!(format!("Could not read the search date `{text}`"))),
+            };
+            let (first_day, last_day) = (bound(&self.from, i64::MIN)?, bound(&self.to, i64::MAX)?);
+            // Documents without a readable date can't be in a range
+            match date::parse_days(&document.date) {
+                Some(day) if first_day <= day && day <= last_day => {},
+                _ => return Ok(false),
+            }
+        }
+
+        if !self.min_amount.is_empty() || !self.max_amount.is_empty() {
+            let Some(total) = document.total().or(document.amount_due) else {
+                return Ok(false);
+            };
+            if !self.min_amount.is_empty() && total < self.min_amount.parse()? {
+                return Ok(false);
+            }
+            if !self.max_amount.is_empty() && total > self.max_amount.parse()? {
+                return Ok(false);
+            }
+        }
+        return Ok(true);
+    }
+}
+
+pub fn search<'a>(documents: &'a [ReceiptInfo], query: &SearchQuery) -> Result<Vec<&'a ReceiptInfo>, Error> {
+    let mut found = Vec::new();
+    for document in documents {
+        if query.matches(document)? {
+            found.push(document);
+        }
+    }
+    return Ok(found);
+}
+
+impl PdfStore {
+    // The matching documents with the path of their latest PDF in the store,
+    // None for documents which were never stored
+    pub fn search<'a>(&self, documents: &'a [ReceiptInfo], query: &SearchQuery) -> Result<Vec<(&'a ReceiptInfo, Option<String>)>, Error> {
+        let mut found = Vec::new();
+        for document in search(documents, query)? {
+            let path = self.latest(&document.doc_number)?.map(|stored| self.object_path(&stored.hash));
+            found.push((document, path));
+        }
+        return Ok(found);
+    }
+}
@@ -0,0 +1,143 @@
+use anyhow::{Error, Result, anyhow};
+
+// The table directory of TrueType font files, read into the raw tables and
+// written back out with the checksums the format wants. Whatever changes in
+// a font is patched into its tables in place, the fields are at fixed
+// offsets in the tables that matter here.
+
+pub(crate) type Table = ([u8; 4], Vec<u8>);
+
+pub(crate) fn read_tables(data: &[u8]) -> Result<Vec<Table>, Error> {
+    match data.get(0..4) {
+        Some([0, 1, 0, 0]) | Some(b"true") => {},
+        Some(b"OTTO") => return Err(anyhow!("The font has CFF outlines, only TrueType outlines are supported")),
+        Some(b"ttcf") => return Err(anyhow!("The file is a font collection, not a single font")),
+        _ => return Err(anyhow!("The file is not a TrueType font")),
+    }
+    let count = read_u16(data, 4)? as usize;
+    let mut tables = Vec::with_capacity(count);
+    for index in 0..count {
+        let record = 12 + index * 16;
+        let Some(tag) = data.get(record..record + 4) else { return Err(anyhow!("The font's table directory is cut short")) };
+        let offset = read_u32(data, record + 8)? as usize;
+        let length = read_u32(data, record + 12)? as usize;
+        let Some(bytes) = data.get(offset..offset.saturating_add(length)) else {
+            return Err(anyhow!(format!("The font's `{}` table is cut short", String::from_utf8_lossy(tag))));
+        };
+        tables.push(([tag[0], tag[1], tag[2], tag[3]], bytes.to_vec()));
+    }
+    return Ok(tables);
+}
+
+pub(crate) fn table<'a>(tables: &'a [Table], tag: &[u8; 4]) -> Result<&'a [u8], Error> {
+    return match tables.iter().find(|(table_tag, _)| table_tag == tag) {
+        Some((_, bytes)) => Ok(bytes),
+        None => Err(anyhow!(format!("The font has no `{}` table", String::from_utf8_lossy(tag)))),
+    };
+}
+
+pub(crate) fn write_tables(mut tables: Vec<Table>) -> Vec<u8> {
+    tables.sort_by_key(|(tag, _)| *tag);
+    // checkSumAdjustment is worked out over the whole file with it zeroed
+    if let Some((_, head)) = tables.iter_mut().find(|(tag, _)| tag == b"head") {
+        if head.len() >= 12 {
+            head[8..12].fill(0);
+        }
+    }
+    let count = tables.len() as u16;
+    let entry_selector = if count == 0 { 0 } else { 15 - count.leading_zeros() as u16 };
+    let search_range = (1u16 << entry_selector) * 16;
+    let mut font = Vec::new();
+    font.extend_from_slice(&[0, 1, 0, 0]);
+    for value in [count, search_range, entry_selector, count * 16 - search_range] {
+        font.extend_from_slice(&value.to_be_bytes());
+    }
+    let mut offset = 12 + tables.len() * 16;
+    for (tag, bytes) in &tables {
+        font.extend_from_slice(tag);
+        font.extend_from_slice(&checksum(bytes).to_be_bytes());
+        font.extend_from_slice(&(offset as u32).to_be_bytes());
+        font.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+        offset += padded(bytes.len());
+    }
+    let mut head_offset = None;
+    for (tag, bytes) in &tables {
+        if tag == b"head" {
+            head_offset = Some(font.len());
+        }
+        font.extend_from_slice(bytes);
+        font.resize(padded(font.len()), 0);
+    }
+    if let Some(head_offset) = head_offset {
+        let adjustment = 0xB1B0AFBAu32.wrapping_sub(checksum(&font));
+        font[head_offset + 8..head_offset + 12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+    return font;
+}
+
+// The glyf and loca tables for the glyphs, with long offsets. head's
+// indexToLocFormat has to be set to match.
+pub(crate) fn write_glyphs(glyphs: &[Vec<u8>]) -> (Vec<u8>, Vec<u8>) {
+    let mut glyf = Vec::new();
+    let mut loca = Vec::with_capacity((glyphs.len() + 1) * 4);
+    for glyph in glyphs {
+        loca.extend_from_slice(&(glyf.len() as u32).to_be_bytes());
+        glyf.extend_from_slice(glyph);
+        glyf.resize(padded(glyf.len()), 0);
+    }
+    loca.extend_from_slice(&(glyf.len() as u32).to_be_bytes());
+    return (glyf, loca);
+}
+
+// Where each glyph starts in glyf, and where the last one ends
+pub(crate) fn glyph_offsets(tables: &[Table]) -> Result<Vec<usize>, Error> {
+    let long = read_u16(table(tables, b"head")?, 50)? == 1;
+    let count = read_u16(table(tables, b"maxp")?, 4)? as usize;
+    let loca = table(tables, b"loca")?;
+    let mut offsets = Vec::with_capacity(count + 1);
+    for index in 0..=count {
+        offsets.push(match long {
+            true => read_u32(loca, index * 4)? as usize,
+            false => read_u16(loca, index * 2)? as usize * 2,
+        });
+    }
+    return Ok(offsets);
+}
+
+pub(crate) fn read_u16(data: &[u8], offset: usize) -> Result<u16, Error> {
+    return match data.get(offset..offset + 2) {
+        Some(bytes) => Ok(u16::from_be_bytes([bytes[0], bytes[1]])),
+        None => Err(anyhow!("The font is cut short")),
+    };
+}
+
+pub(crate) fn read_u32(data: &[u8], offset: usize) -> Result<u32, Error> {
+    return match data.get(offset..offset + 4) {
+        Some(bytes) => Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])),
+        None => Err(anyhow!("The font is cut short")),
+    };
+}
+
+pub(crate) fn write_u16(data: &mut [u8], offset: usize, value: u16) -> Result<(), Error> {
+    return match data.get_mut(offset..offset + 2) {
+        Some(bytes) => {
+            bytes.copy_from_slice(&value.to_be_bytes());
+            Ok(())
+        },
+        None => Err(anyhow!("The font is cut short")),
+    };
+}
+
+fn checksum(bytes: &[u8]) -> u32 {
+    return bytes.chunks(4)
+        .map(|chunk| {
+            let mut word = [0; 4];
+            word[..chunk.len()].copy_from_slice(chunk);
+            u32::from_be_bytes(word)
+        })
+        .fold(0u32, u32::wrapping_add);
+}
+
+fn padded(length: usize) -> usize {
+    return (length + 3) & !3;
+}
@@ -0,0 +1,125 @@
+use printpdf::{IndirectFontRef, Mm, PdfLayerReference, Pt};
+use anyhow::{Error, Result, anyhow};
+use crate::drawing::QuickShapes;
+use crate::{labels, metrics};
+
+// The customer signature area: lines for the signature, printed name and the
+// date signed, with captions underneath. The fillable quote fields use the
+// same geometry so they sit exactly on the printed lines.
+
+const CAPTION_SIZE: f64 = 7.0;
+// All in points
+const CAPTION_GAP: f64 = 6.0;
+const ROW_HEIGHT: f64 = 16.0;
+const DATE_WIDTH: f64 = 78.0;
+const COLUMN_GAP: f64 = 10.0;
+const CHECKBOX_SIZE: f64 = 8.0;
+const CHECKBOX_GAP: f64 = 4.0;
+const CHECKLIST_ROW: f64 = 14.0;
+const CHECKLIST_SIZE: f64 = 8.0;
+
+// A rectangle in points, as [llx, lly, urx, ury]
+pub(crate) type Rect = [f64; 4];
+
+pub(crate) struct SignatureBlock {
+    pub x1: f64,
+    pub x2: f64,
+    // Baseline of the lowest captions
+    pub bottom: f64,
+}
+
+impl SignatureBlock {
+    fn name_y(&self) -> f64 {
+        return self.bottom + CAPTION_GAP;
+    }
+
+    fn signature_y(&self) -> f64 {
+        return self.name_y() + ROW_HEIGHT;
+    }
+
+    fn date_x(&self) -> f64 {
+        return self.x2 - DATE_WIDTH;
+    }
+
+    pub fn draw(&self, layer: &PdfLayerReference, bilingual: bool, font: &IndirectFontRef) {
+        let x1: Mm = Pt(self.x1).into();
+        let x2: Mm = Pt(self.x2).into();
+        let date_x: Mm = Pt(self.date_x()).into();
+        let signature_y: Mm = Pt(self.signature_y()).into();
+        let name_y: Mm = Pt(self.name_y()).into();
+        let bottom: Mm = Pt(self.bottom).into();
+        let caption_gap: Mm = Pt(CAPTION_GAP).into();
+
+        layer.add_line(x1, signature_y, x2, signature_y);
+        layer.use_text(labels::RECEIVED_BY.text(bilingual), CAPTION_SIZE, x1, signature_y - caption_gap, font);
+
+        layer.add_line(x1, name_y, date_x - Pt(COLUMN_GAP).into(), name_y);
+        layer.use_text(labels::PRINT_NAME.text(bilingual), CAPTION_SIZE, x1, bottom, font);
+        layer.add_line(date_x, name_y, x2, name_y);
+        layer.use_text(labels::DATE.text(bilingual), CAPTION_SIZE, date_x, bottom, font);
+    }
+
+    // Box and caption for the quote acceptance checkbox, above the signature
+    pub fn draw_acceptance(&self, layer: &PdfLayerReference, bilingual: bool, font: &IndirectFontRef) {
+        let [llx, lly, urx, ury] = self.acceptance_rect();
+        layer.add_box(Pt(llx).into(), Pt(lly).into(), Pt(urx).into(), Pt(ury).into());
+        layer.use_text(labels::ACCEPT_QUOTE.text(bilingual), 8.0, Pt(urx + CHECKBOX_GAP).into(), Pt(lly + 1.0).into(), font);
+    }
+
+    // Checkbox rows above the signature, e.g. the driver's checklist on
+    // delivery tickets. The first item is the top row. Items are wrapped to
+    // the width of the signature block, and the rows have to fit below top,
+    // the bottom of the item table.
+    pub fn draw_checklist(
+        &self,
+        layer: &PdfLayerReference,
+        items: &[String],
+        top: f64,
+        metrics: &metrics::FontMetrics,
+        font: &IndirectFontRef,
+    ) -> Result<(), Error> {
+        let text_x = self.x1 + CHECKBOX_SIZE + CHECKBOX_GAP;
+        let mut rows = Vec::new();
+        for item in items {
+            for (i, line) in metrics.wrap(item, CHECKLIST_SIZE, Pt(self.x2 - text_x).into(), None).into_iter().enumerate() {
+                rows.push((i == 0, line));
+            }
+        }
+        let bottom = self.signature_y() + ROW_HEIGHT;
+        // A table reaching down past the checklist leaves no room at all
+        let room = (((top - CHECKBOX_SIZE - bottom) / CHECKLIST_ROW).floor() as i64 + 1).max(0) as usize;
+        if rows.len() > room {
+            return Err(anyhow!(format!(
+                "The delivery checklist takes {} lines, only {room} fit between the item table and the signature",
+                rows.len(),
+            )));
+        }
+        let mut lly = bottom + CHECKLIST_ROW * (rows.len() as f64 - 1.0);
+        for (has_box, line) in rows {
+            if has_box {
+                layer.add_box(Pt(self.x1).into(), Pt(lly).into(), Pt(self.x1 + CHECKBOX_SIZE).into(), Pt(lly + CHECKBOX_SIZE).into());
+            }
+            layer.use_text(line, CHECKLIST_SIZE, Pt(text_x).into(), Pt(lly + 1.0).into(), font);
+            lly -= CHECKLIST_ROW;
+        }
+        return Ok(());
+    }
+
+    pub fn acceptance_rect(&self) -> Rect {
+        let lly = self.signature_y() + ROW_HEIGHT;
+        return [self.x1, lly, self.x1 + CHECKBOX_SIZE, lly + CHECKBOX_SIZE];
+    }
+
+    // The space above each line, up to the caption of the row above
+    pub fn signature_rect(&self) -> Rect {
+        return [self.x1, self.signature_y(), self.x2, self.signature_y() + ROW_HEIGHT - CAPTION_GAP];
+    }
+
+    pub fn name_rect(&self) -> Rect {
+        return [self.x1, self.name_y(), self.date_x() - COLUMN_GAP, self.signature_y() - CAPTION_GAP - CAPTION_SIZE];
+    }
+
+    pub fn date_rect(&self) -> Rect {
+        return [self.date_x(), self.name_y(), self.x2, self.signature_y() - CAPTION_GAP - CAPTION_SIZE];
+    }
+}
@@ -0,0 +1,68 @@
+use printpdf::{IndirectFontRef, PdfLayerReference, LinkAnnotation, Rect, Actions, Mm, Pt};
+use anyhow::{Error, Result};
+use crate::drawing::QuickShapes;
+use crate::{labels, percent_encode, qr};
+
+// Where a delivery goes, for drivers finding rural job sites. Delivery
+// tickets get a box with the address, the coordinates, a blank space for
+// sketching the site and a QR code opening the location in a maps app.
+
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeliverySite {
+    pub address: String,
+    // Decimal degrees. The QR code searches for the address without them.
+    pub coordinates: Option<(f64, f64)>,
+}
+
+// All in points
+const X1: f64 = 54.0;
+const X2: f64 = 330.0;
+const BOTTOM: f64 = 150.0;
+const TOP: f64 = 244.0;
+const QR_SIZE: f64 = 54.0;
+
+impl DeliverySite {
+    pub fn maps_url(&self) -> String {
+        let query = match self.coordinates {
+            Some((latitude, longitude)) => format!("{latitude:.6},{longitude:.6}"),
+            None => self.address.split('\n').map(str::trim).collect::<Vec<_>>().join(", "),
+        };
+        return format!("https://www.google.com/maps/search/?api=1&query={}", percent_encode(&query));
+    }
+
+    pub(crate) fn draw(&self, layer: &PdfLayerReference, bilingual: bool, label_font: &IndirectFontRef, font: &IndirectFontRef) -> Result<(), Error> {
+        let pt = |value: f64| -> Mm { Pt(value).into() };
+        layer.add_box(pt(X1), pt(BOTTOM), pt(X2), pt(TOP));
+        layer.use_text(labels::DELIVERY_SITE.text(bilingual), 8.0, pt(X1 + 5.0), pt(TOP - 11.0), label_font);
+        let mut y = TOP - 24.0;
+        for line in self.address.lines().take(5) {
+            layer.use_text(line, 9.0, pt(X1 + 5.0), pt(y), font);
+            y -= 11.0;
+        }
+        if let Some((latitude, longitude)) = self.coordinates {
+            layer.use_text(format!("{latitude:.5}, {longitude:.5}"), 8.0, pt(X1 + 5.0), pt(BOTTOM + 6.0), font);
+        }
+
+        // Left blank for the dispatcher to sketch the way in
+        let (map_x1, map_x2) = (X2 - QR_SIZE - 78.0, X2 - QR_SIZE - 12.0);
+        layer.add_dashed_line(pt(map_x1), pt(BOTTOM + 8.0), pt(map_x2), pt(BOTTOM + 8.0));
+        layer.add_dashed_line(pt(map_x1), pt(TOP - 8.0), pt(map_x2), pt(TOP - 8.0));
+        layer.add_dashed_line(pt(map_x1), pt(BOTTOM + 8.0), pt(map_x1), pt(TOP - 8.0));
+        layer.add_dashed_line(pt(map_x2), pt(BOTTOM + 8.0), pt(map_x2), pt(TOP - 8.0));
+        layer.use_text(labels::SITE_MAP.text(bilingual), 6.5, pt(map_x1 + 3.0), pt(TOP - 16.0), label_font);
+
+        let url = self.maps_url();
+        let (qr_x, qr_y) = (X2 - QR_SIZE - 6.0, BOTTOM + 16.0);
+        qr::draw_qr(layer, &url, pt(qr_x), pt(qr_y), pt(QR_SIZE))?;
+        layer.use_text(labels::DIRECTIONS.text(bilingual), 6.0, pt(qr_x - 8.0), pt(BOTTOM + 6.0), font);
+        layer.add_link_annotation(LinkAnnotation::new(
+            Rect::new(pt(qr_x), pt(qr_y), pt(qr_x + QR_SIZE), pt(qr_y + QR_SIZE)),
+            None,
+            None,
+            Actions::Uri(url),
+            None,
+        ));
+        return Ok(());
+    }
+}
@@ -0,0 +1,159 @@
+use printpdf::lopdf::{dictionary, Dictionary, Document, Object, ObjectId, Stream};
+use printpdf::lopdf::content::{Content, Operation};
+use anyhow::{Error, Result, anyhow};
+use crate::{labels, ReceiptInfo};
+
+// Brands an existing PDF (e.g. a vendor weigh ticket) with our company
+// header and document number at the top of every page and a page footer at
+// the bottom. The original content is left untouched underneath; the header
+// and footer bands are painted white first so they stay legible.
+
+const FONT_NAME: &str = "AccuStampHelv";
+const FONT_NAME_BOLD: &str = "AccuStampHelvBold";
+// All in points
+const MARGIN: f64 = 36.0;
+const HEADER_HEIGHT: f64 = 30.0;
+const FOOTER_HEIGHT: f64 = 22.0;
+
+pub fn stamp_pdf(pdf: &[u8], receipt: &ReceiptInfo, bilingual: bool) -> Result<Vec<u8>, Error> {
+    let mut doc = Document::load_mem(pdf)?;
+    let pages = doc.get_pages();
+    if pages.is_empty() {
+        return Err(anyhow!("The PDF to stamp has no pages"));
+    }
+
+    let regular = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+        "Encoding" => "WinAnsiEncoding",
+    });
+    let bold = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica-Bold",
+        "Encoding" => "WinAnsiEncoding",
+    });
+
+    let page_count = pages.len();
+    for (number, page_id) in pages {
+        let media_box = inherited(&doc, page_id, b"MediaBox")
+            .and_then(|media_box| rect_from(&media_box))
+            .unwrap_or([0.0, 0.0, 612.0, 792.0]);
+        let mut resources = match inherited(&doc, page_id, b"Resources") {
+            Some(Object::Dictionary(resources)) => resources,
+            Some(Object::Reference(id)) => doc.get_object(id)?.as_dict()?.clone(),
+            _ => Dictionary::new(),
+        };
+        let mut fonts = match resources.get(b"Font") {
+            Ok(Object::Dictionary(fonts)) => fonts.clone(),
+            Ok(Object::Reference(id)) => doc.get_object(*id)?.as_dict()?.clone(),
+            _ => Dictionary::new(),
+        };
+        fonts.set(FONT_NAME, regular);
+        fonts.set(FONT_NAME_BOLD, bold);
+        resources.set("Font", fonts);
+
+        let footer = format!("{} {number} / {page_count}", labels::PAGE.text(bilingual));
+        let overlay = overlay_content(receipt, &footer, media_box).encode()?;
+        // Wrap the original content in q/Q so whatever state it leaves behind
+        // doesn't affect the overlay
+        let save = doc.add_object(Stream::new(Dictionary::new(), b"q".to_vec()));
+        let overlay = doc.add_object(Stream::new(Dictionary::new(), overlay));
+
+        let page = doc.get_object_mut(page_id)?.as_dict_mut()?;
+        let mut contents = vec![Object::Reference(save)];
+        match page.get(b"Contents") {
+            Ok(Object::Array(existing)) => contents.extend(existing.iter().cloned()),
+            Ok(existing) => contents.push(existing.clone()),
+            Err(_) => {},
+        }
+        contents.push(Object::Reference(overlay));
+        page.set("Contents", contents);
+        page.set("Resources", resources);
+    }
+
+    let mut bytes = Vec::new();
+    doc.save_to(&mut bytes)?;
+    return Ok(bytes);
+}
+
+fn overlay_content(receipt: &ReceiptInfo, footer: &str, media_box: [f64; 4]) -> Content {
+    let [llx, lly, urx, ury] = media_box;
+    let header_baseline = ury - HEADER_HEIGHT + 10.0;
+    let footer_baseline = lly + 8.0;
+    let doc_number = format!("{} {}", receipt.title, receipt.doc_number);
+
+    let mut operations = vec![
+        Operation::new("Q", vec![]),
+        Operation::new("q", vec![]),
+        // White bands behind the header and footer
+        Operation::new("rg", vec![1.into(), 1.into(), 1.into()]),
+        Operation::new("re", vec![real(llx), real(ury - HEADER_HEIGHT), real(urx - llx), real(HEADER_HEIGHT)]),
+        Operation::new("re", vec![real(llx), real(lly), real(urx - llx), real(FOOTER_HEIGHT)]),
+        Operation::new("f", vec![]),
+        Operation::new("RG", vec![0.into(), 0.into(), 0.into()]),
+        Operation::new("w", vec![real(0.5)]),
+        Operation::new("m", vec![real(llx + MARGIN), real(ury - HEADER_HEIGHT)]),
+        Operation::new("l", vec![real(urx - MARGIN), real(ury - HEADER_HEIGHT)]),
+        Operation::new("S", vec![]),
+        Operation::new("rg", vec![0.into(), 0.into(), 0.into()]),
+    ];
+    operations.extend(text(FONT_NAME_BOLD, 14.0, llx + MARGIN, header_baseline, &receipt.company_name));
+    // Right aligned using the average Helvetica advance, which is close
+    // enough for a short document number
+    let doc_number_width = doc_number.chars().count() as f64 * 11.0 * 0.56;
+    operations.extend(text(FONT_NAME_BOLD, 11.0, urx - MARGIN - doc_number_width, header_baseline, &doc_number));
+    operations.extend(text(FONT_NAME, 8.0, llx + MARGIN, footer_baseline, &receipt.company_info_line));
+    let footer_width = footer.chars().count() as f64 * 8.0 * 0.56;
+    operations.extend(text(FONT_NAME, 8.0, urx - MARGIN - footer_width, footer_baseline, footer));
+    operations.push(Operation::new("Q", vec![]));
+    return Content { operations };
+}
+
+fn text(font: &str, size: f64, x: f64, y: f64, text: &str) -> Vec<Operation> {
+    return vec![
+        Operation::new("BT", vec![]),
+        Operation::new("Tf", vec![Object::Name(font.as_bytes().to_vec()), real(size)]),
+        Operation::new("Td", vec![real(x), real(y)]),
+        Operation::new("Tj", vec![Object::string_literal(win_ansi(text))]),
+        Operation::new("ET", vec![]),
+    ];
+}
+
+pub(crate) fn real(value: f64) -> Object {
+    return Object::Real(value as _);
+}
+
+// The standard fonts only cover Latin-1, anything else is replaced
+fn win_ansi(text: &str) -> Vec<u8> {
+    return text.chars().map(|c| if (c as u32) < 256 { c as u8 } else { b'?' }).collect();
+}
+
+// Looks up a page attribute which may be inherited from the page tree
+pub(crate) fn inherited(doc: &Document, page_id: ObjectId, key: &[u8]) -> Option<Object> {
+    let mut node_id = page_id;
+    loop {
+        let node = doc.get_object(node_id).ok()?.as_dict().ok()?;
+        if let Ok(value) = node.get(key) {
+            return Some(value.clone());
+        }
+        node_id = node.get(b"Parent").ok()?.as_reference().ok()?;
+    }
+}
+
+pub(crate) fn rect_from(object: &Object) -> Option<[f64; 4]> {
+    let values = object.as_array().ok()?;
+    if values.len() != 4 {
+        return None;
+    }
+    let mut rect = [0.0; 4];
+    for (i, value) in values.iter().enumerate() {
+        rect[i] = match value {
+            Object::Integer(value) => *value as f64,
+            Object::Real(value) => *value,
+            _ => return None,
+        };
+    }
+    return Some(rect);
+}
@@ -0,0 +1,132 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use sha2::{Digest, Sha256};
+use anyhow::{Error, Result, anyhow};
+use crate::ReceiptInfo;
+
+// Content addressed storage for generated documents. Each PDF is stored once
+// under the SHA-256 of its bytes in dir/objects, and dir/index.tsv records
+// which file each document number was given, oldest first:
+//
+//     doc_number	content checksum	SHA-256 of the PDF
+//
+// Files are never changed once written, so replicating the store is copying
+// what the backup doesn't have yet and the new lines of the index.
+//
+// Every distinct PDF of a document is kept, since the same content can be
+// rendered differently (GenOptions aren't part of ReceiptInfo::checksum).
+// Storing bytes the document already has only returns their hash.
+
+pub struct PdfStore {
+    dir: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredDocument {
+    pub doc_number: String,
+    pub checksum: String,
+    // SHA-256 of the PDF, as hex
+    pub hash: String,
+}
+
+impl PdfStore {
+    pub fn open(dir: &str) -> Result<Self, Error> {
+        let objects = format!("{dir}/objects");
+        if let Err(e) = fs::create_dir_all(&objects) {
+            return Err(anyhow!(format!("Could not create the document store in: `{objects}`. Reason: `{e}`")));
+        }
+        return Ok(Self { dir: dir.to_owned() });
+    }
+
+    // Returns the hash the document is stored under
+    pub fn put(&self, receipt: &ReceiptInfo, pdf: &[u8]) -> Result<String, Error> {
+        let checksum = receipt.checksum();
+        let hash: String = Sha256::digest(pdf).iter().map(|byte| format!("{byte:02x}")).collect();
+        if self.history(&receipt.doc_number)?.iter().any(|stored| stored.hash == hash) {
+            return Ok(hash);
+        }
+        let path = self.object_path(&hash);
+        if fs::metadata(&path).is_err() {
+            let prefix_dir = format!("{}/objects/{}", self.dir, &hash[..2]);
+            // Written next to where it goes and renamed, so a file with the
+            // name of a hash always has all of its bytes
+            let temporary = format!("{prefix_dir}/.{hash}.tmp");
+            let written = fs::create_dir_all(&prefix_dir)
+                .and_then(|_| fs::write(&temporary, pdf))
+                .and_then(|_| fs::rename(&temporary, &path));
+            if let Err(e) = written {
+                return Err(anyhow!(format!("Could not store the document `{}` in: `{path}`. Reason: `{e}`", receipt.doc_number)));
+            }
+        }
+        let index = format!("{}/index.tsv", self.dir);
+        let line = format!("{}\t{checksum}\t{hash}\n", escape(&receipt.doc_number));
+        let appended = OpenOptions::new().create(true).append(true).open(&index)
+            .and_then(|mut file| file.write_all(line.as_bytes()));
+        if let Err(e) = appended {
+            return Err(anyhow!(format!("Could not update the document index: `{index}`. Reason: `{e}`")));
+        }
+        return Ok(hash);
+    }
+
+    pub fn get(&self, hash: &str) -> Result<Vec<u8>, Error> {
+        if hash.len() != 64 || !hash.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return Err(anyhow!(format!("`{hash}` is not a document hash")));
+        }
+        let path = self.object_path(&hash.to_ascii_lowercase());
+        return match fs::read(&path) {
+            Ok(pdf) => Ok(pdf),
+            Err(e) => Err(anyhow!(format!("Could not read the stored document: `{path}`. Reason: `{e}`"))),
+        };
+    }
+
+    // Every version of a document, oldest first
+    pub fn history(&self, doc_number: &str) -> Result<Vec<StoredDocument>, Error> {
+        let index = format!("{}/index.tsv", self.dir);
+        let text = match fs::read_to_string(&index) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(anyhow!(format!("Could not read the document index: `{index}`. Reason: `{e}`"))),
+        };
+        let doc_number = escape(doc_number);
+        let mut history = Vec::new();
+        for line in text.lines() {
+            let mut columns = line.split('\t');
+            let (Some(number), Some(checksum), Some(hash)) = (columns.next(), columns.next(), columns.next()) else { continue };
+            if number == doc_number {
+                history.push(StoredDocument { doc_number: unescape(number), checksum: checksum.to_owned(), hash: hash.to_owned() });
+            }
+        }
+        return Ok(history);
+    }
+
+    pub fn latest(&self, doc_number: &str) -> Result<Option<StoredDocument>, Error> {
+        return Ok(self.history(doc_number)?.pop());
+    }
+
+    pub(crate) fn object_path(&self, hash: &str) -> String {
+        return format!("{}/objects/{}/{hash}.pdf", self.dir, &hash[..2]);
+    }
+}
+
+// Document numbers come from the POS and could hold anything
+fn escape(doc_number: &str) -> String {
+    return doc_number.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n");
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some(other) => unescaped.push(other),
+            None => {},
+        }
+    }
+    return unescaped;
+}
@@ -0,0 +1,231 @@
+use std::collections::{BTreeMap, BTreeSet};
+use printpdf::lopdf::{Document, Object, ObjectId};
+use printpdf::lopdf::content::Content;
+use anyhow::{Error, Result};
+use crate::extract::resolve;
+use crate::sfnt;
+use crate::stamp::inherited;
+
+// Cuts the embedded fonts down to the glyphs the document draws. printpdf
+// embeds whole font files and writes the text in them as two byte glyph ids
+// (Identity-H), so the glyphs are read off the content streams and every
+// other glyph in the font file is emptied. Glyph ids stay where they are,
+// which leaves the text, the widths and the ToUnicode CMaps as they were.
+// The layout tables go too, PDF viewers only draw the glyphs. Subset fonts
+// are named with a tag, e.g. "KJQLBA+NotoSans-Regular", as the PDF spec
+// asks for.
+
+// Tables a PDF viewer draws an embedded TrueType font with
+const KEPT: [&[u8; 4]; 14] = [
+    b"OS/2", b"cmap", b"cvt ", b"fpgm", b"gasp", b"glyf", b"head",
+    b"hhea", b"hmtx", b"loca", b"maxp", b"name", b"post", b"prep",
+];
+
+// Flags of the components of composite glyphs
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+
+// The font dictionaries embedding one font file
+struct EmbeddedFont {
+    // The Type0 font and its descendant CIDFont, which both carry the name
+    fonts: Vec<ObjectId>,
+    descriptor: ObjectId,
+}
+
+pub(crate) fn subset_fonts(pdf: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut doc = Document::load_mem(pdf)?;
+    let embedded = embedded_fonts(&doc);
+    if embedded.is_empty() {
+        return Ok(pdf.to_vec());
+    }
+    // .notdef is drawn for anything missing from the font
+    let mut used: BTreeMap<ObjectId, BTreeSet<u16>> = embedded.keys().map(|file| (*file, BTreeSet::from([0]))).collect();
+    for (content, resources) in content_streams(&doc) {
+        // Without all the text the glyphs that are needed aren't known
+        let Ok(content) = Content::decode(&content) else { return Ok(pdf.to_vec()) };
+        let fonts = resource_fonts(&doc, resources.as_ref());
+        let mut font = None;
+        for operation in &content.operations {
+            match operation.operator.as_str() {
+                "Tf" => if let Some(Object::Name(name)) = operation.operands.first() {
+                    font = fonts.get(name).copied();
+                },
+                "Tj" | "'" | "\"" | "TJ" => {
+                    let Some(glyphs) = font.and_then(|file| used.get_mut(&file)) else { continue };
+                    for operand in &operation.operands {
+                        let strings = match operand {
+                            Object::Array(items) => items.iter().collect(),
+                            other => vec![other],
+                        };
+                        for string in strings {
+                            if let Object::String(bytes, _) = string {
+                                glyphs.extend(bytes.chunks(2).map(|pair| u16::from_be_bytes([pair[0], *pair.get(1).unwrap_or(&0)])));
+                            }
+                        }
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+
+    for (file, glyphs) in &used {
+        let Ok(stream) = doc.get_object(*file).and_then(Object::as_stream) else { continue };
+        let data = stream.decompressed_content().unwrap_or_else(|_| stream.content.clone());
+        // Fonts that can't be read are left whole
+        let Ok(subset) = subset_font(&data, glyphs) else { continue };
+        let stream = doc.get_object_mut(*file).and_then(Object::as_stream_mut)?;
+        stream.dict.set("Length1", subset.len() as i64);
+        stream.set_plain_content(subset);
+
+        let font = &embedded[file];
+        let tag = subset_tag(glyphs);
+        for (id, key) in font.fonts.iter().map(|id| (*id, &b"BaseFont"[..])).chain([(font.descriptor, &b"FontName"[..])]) {
+            let Ok(dict) = doc.get_object_mut(id).and_then(Object::as_dict_mut) else { continue };
+            let Ok(name) = dict.get(key).and_then(Object::as_name) else { continue };
+            if name.get(6) == Some(&b'+') {
+                continue;
+            }
+            let name = [tag.as_bytes(), b"+", name].concat();
+            dict.set(key, Object::Name(name));
+        }
+    }
+    let mut bytes = Vec::new();
+    doc.save_to(&mut bytes)?;
+    return Ok(bytes);
+}
+
+// The Type0 fonts with an Identity-H encoding by the font file they embed,
+// the only ones whose text is written in glyph ids
+fn embedded_fonts(doc: &Document) -> BTreeMap<ObjectId, EmbeddedFont> {
+    let mut embedded: BTreeMap<ObjectId, EmbeddedFont> = BTreeMap::new();
+    for id in doc.objects.keys() {
+        let Some((descendant, descriptor, file)) = font_file(doc, &Object::Reference(*id)) else { continue };
+        let entry = embedded.entry(file).or_insert_with(|| EmbeddedFont { fonts: Vec::new(), descriptor });
+        entry.fonts.push(*id);
+        if let Some(descendant) = descendant {
+            entry.fonts.push(descendant);
+        }
+    }
+    return embedded;
+}
+
+// The descendant font, the font descriptor and the FontFile2 of a Type0 font
+fn font_file(doc: &Document, font: &Object) -> Option<(Option<ObjectId>, ObjectId, ObjectId)> {
+    let dict = resolve(doc, font)?.as_dict().ok()?;
+    if dict.get(b"Subtype").and_then(Object::as_name).ok()? != b"Type0" {
+        return None;
+    }
+    if dict.get(b"Encoding").and_then(Object::as_name).ok()? != b"Identity-H" {
+        return None;
+    }
+    let descendant_object = dict.get(b"DescendantFonts").ok().and_then(|fonts| resolve(doc, fonts))?.as_array().ok()?.first()?;
+    let descendant = resolve(doc, descendant_object)?.as_dict().ok()?;
+    let descriptor = descendant.get(b"FontDescriptor").and_then(Object::as_reference).ok()?;
+    let file = doc.get_object(descriptor).and_then(Object::as_dict).ok()?.get(b"FontFile2").and_then(Object::as_reference).ok()?;
+    return Some((descendant_object.as_reference().ok(), descriptor, file));
+}
+
+// Every page and form XObject (stamps, two-up pages, form field
+// appearances) with the resources it's drawn with
+fn content_streams(doc: &Document) -> Vec<(Vec<u8>, Option<Object>)> {
+    let mut streams = Vec::new();
+    for page_id in doc.get_pages().values() {
+        if let Ok(content) = doc.get_page_content(*page_id) {
+            streams.push((content, inherited(doc, *page_id, b"Resources")));
+        }
+    }
+    for object in doc.objects.values() {
+        let Object::Stream(stream) = object else { continue };
+        if stream.dict.get(b"Subtype").and_then(Object::as_name).ok() != Some(&b"Form"[..]) {
+            continue;
+        }
+        let content = stream.decompressed_content().unwrap_or_else(|_| stream.content.clone());
+        streams.push((content, stream.dict.get(b"Resources").ok().cloned()));
+    }
+    return streams;
+}
+
+// Font resource name to the font file it draws with
+fn resource_fonts(doc: &Document, resources: Option<&Object>) -> BTreeMap<Vec<u8>, ObjectId> {
+    let mut fonts = BTreeMap::new();
+    let Some(Ok(resources)) = resources.and_then(|resources| resolve(doc, resources)).map(Object::as_dict) else { return fonts };
+    let Some(Ok(font_dict)) = resources.get(b"Font").ok().and_then(|fonts| resolve(doc, fonts)).map(Object::as_dict) else { return fonts };
+    for (name, font) in font_dict.iter() {
+        if let Some((_, _, file)) = font_file(doc, font) {
+            fonts.insert(name.clone(), file);
+        }
+    }
+    return fonts;
+}
+
+// The font with only the glyphs given and the ones they're built from
+fn subset_font(data: &[u8], glyphs: &BTreeSet<u16>) -> Result<Vec<u8>, Error> {
+    let tables = sfnt::read_tables(data)?;
+    let offsets = sfnt::glyph_offsets(&tables)?;
+    let glyf = sfnt::table(&tables, b"glyf")?;
+    let glyph = |id: u16| -> &[u8] {
+        let id = id as usize;
+        return match (offsets.get(id), offsets.get(id + 1)) {
+            (Some(start), Some(end)) => glyf.get(*start..*end).unwrap_or(&[]),
+            _ => &[],
+        };
+    };
+    let mut kept = glyphs.clone();
+    let mut pending: Vec<u16> = glyphs.iter().copied().collect();
+    while let Some(id) = pending.pop() {
+        for component in components(glyph(id))? {
+            if kept.insert(component) {
+                pending.push(component);
+            }
+        }
+    }
+    let subset: Vec<Vec<u8>> = (0..offsets.len().saturating_sub(1) as u16)
+        .map(|id| if kept.contains(&id) { glyph(id).to_vec() } else { Vec::new() })
+        .collect();
+    let (glyf, loca) = sfnt::write_glyphs(&subset);
+
+    let mut head = sfnt::table(&tables, b"head")?.to_vec();
+    sfnt::write_u16(&mut head, 50, 1)?;
+    let mut subset_tables: Vec<sfnt::Table> = tables.into_iter()
+        .filter(|(tag, _)| KEPT.contains(&tag) && !matches!(tag, b"glyf" | b"loca" | b"head"))
+        .collect();
+    subset_tables.extend([(*b"glyf", glyf), (*b"loca", loca), (*b"head", head)]);
+    return Ok(sfnt::write_tables(subset_tables));
+}
+
+// The glyphs a composite glyph is drawn from, none for simple glyphs
+fn components(glyph: &[u8]) -> Result<Vec<u16>, Error> {
+    let mut components = Vec::new();
+    if glyph.is_empty() || (sfnt::read_u16(glyph, 0)? as i16) >= 0 {
+        return Ok(components);
+    }
+    let mut offset = 10;
+    loop {
+        let flags = sfnt::read_u16(glyph, offset)?;
+        components.push(sfnt::read_u16(glyph, offset + 2)?);
+        offset += 4 + if flags & ARG_1_AND_2_ARE_WORDS != 0 { 4 } else { 2 };
+        if flags & WE_HAVE_A_SCALE != 0 {
+            offset += 2;
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            offset += 4;
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            offset += 8;
+        }
+        if flags & MORE_COMPONENTS == 0 {
+            return Ok(components);
+        }
+    }
+}
+
+// Six capital letters that tell subsets of the same font apart
+fn subset_tag(glyphs: &BTreeSet<u16>) -> String {
+    let mut hash: u32 = 0x811C9DC5;
+    for byte in glyphs.iter().flat_map(|glyph| glyph.to_be_bytes()) {
+        hash = (hash ^ byte as u32).wrapping_mul(0x01000193);
+    }
+    return (0..6).map(|i| (b'A' + ((hash >> (i * 5)) % 26) as u8) as char).collect();
+}
@@ -0,0 +1,92 @@
+use anyhow::{Error, Result};
+use crate::{export, is_subtotal, Amount, DocType, Money, ReceiptInfo, Rounding};
+
+// Surcharges the POS doesn't know about, e.g. a fuel surcharge on deliveries
+// or an environmental fee on tires. pre_pass computes them from the item
+// lines and adds them to the totals between the subtotal and the taxes. Tax
+// isn't computed on them, so they should only be configured for fees which
+// aren't taxable. A surcharge already in the totals, sent by the POS or
+// added by an earlier pre_pass, isn't added again.
+
+#[derive(Debug, Clone)]
+pub enum SurchargeAmount {
+    // Percentage of the eligible lines, e.g. 3.5 for 3.5%
+    Percent(f64),
+    // Once per document with at least one eligible line
+    Flat(f64),
+}
+
+#[derive(Debug, Clone)]
+pub struct Surcharge {
+    // As printed in the totals, e.g. "Fuel Surcharge:"
+    pub name: String,
+    pub amount: SurchargeAmount,
+    // Item code prefixes of the eligible lines, every line when empty
+    pub item_codes: Vec<String>,
+    // Document types it's added to, invoices and receipts when empty
+    pub doc_types: Vec<DocType>,
+}
+
+impl Surcharge {
+    fn applies_to(&self, doc_type: &DocType) -> bool {
+        if self.doc_types.is_empty() {
+            return matches!(doc_type, DocType::Invoice | DocType::Receipt);
+        }
+        return self.doc_types.contains(doc_type);
+    }
+
+    fn is_eligible(&self, code: &str) -> bool {
+        return self.item_codes.is_empty() || self.item_codes.iter().any(|prefix| code.starts_with(prefix.as_str()));
+    }
+}
+
+impl ReceiptInfo {
+    pub(crate) fn add_surcharges(&mut self, surcharges: &[Surcharge], rounding: &Rounding) -> Result<(), Error> {
+        let mut added = Vec::new();
+        for surcharge in surcharges {
+            let already_billed = self.totals.iter().any(|total| total.name.eq(&surcharge.name));
+            if already_billed || !surcharge.applies_to(&self.doc_type) {
+                continue;
+            }
+            let mut eligible = None;
+            for line in &self.item_lines {
+                let Some(amount) = line.amount.filter(|_| surcharge.is_eligible(&line.code)) else {
+                    continue;
+                };
+                let sum = eligible.unwrap_or(Money::ZERO).checked_add(amount)?;
+                eligible = Some(sum);
+            }
+            let amount = match (&surcharge.amount, eligible) {
+                (_, None) => continue,
+                (SurchargeAmount::Percent(percent), Some(eligible)) => eligible.times(percent / 100.0, rounding),
+                (SurchargeAmount::Flat(fee), Some(_)) => Money::from_f64(*fee, rounding),
+            };
+            if amount == Money::ZERO {
+                continue;
+            }
+            added.push((surcharge.name.clone(), amount));
+        }
+        if added.is_empty() {
+            return Ok(());
+        }
+
+        // After the subtotal, otherwise before the first tax or the total
+        let position = match self.totals.iter().position(|total| is_subtotal(&total.name)) {
+            Some(subtotal) => subtotal + 1,
+            None => self.totals
+                .iter()
+                .position(|total| export::is_tax_total(&total.name) || total.name.eq("Total:"))
+                .unwrap_or(self.totals.len()),
+        };
+        let sum = Money::checked_sum(added.iter().map(|(_, amount)| *amount))?;
+        let rows: Vec<Amount> = added
+            .into_iter()
+            .map(|(name, value)| Amount { name, value })
+            .collect();
+        self.totals.splice(position..position, rows.iter().cloned());
+        self.surcharges.extend(rows);
+
+        self.add_to_totals(sum, false)?;
+        return Ok(());
+    }
+}
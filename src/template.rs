@@ -0,0 +1,190 @@
+use std::{collections::BTreeMap, fs};
+use anyhow::{Error, Result, anyhow};
+use serde::Deserialize;
+
+// Placement of the parts of a document which companies want to move around:
+// the header text, the logo, the item table and its columns, and the slogan.
+// Templates live in data_dir/templates as TOML files, e.g.
+//
+//     [company_name]
+//     x = 225
+//     y = 712
+//     size = 28
+//
+//     [columns.standard]
+//     lines = [95, 302, 339, 408, 488]
+//     desc_chars = 30
+//
+// Anything left out keeps the default layout, which is the one in
+// templates/default.toml: a template is laid over it key by key before it's
+// read. Positions are in points from the bottom left.
+
+const DEFAULT_TEMPLATE: &str = include_str!("../templates/default.toml");
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TextPlacement {
+    pub x: f64,
+    pub y: f64,
+    pub size: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LogoPlacement {
+    pub x: f64,
+    pub y: f64,
+    pub scale: f64,
+}
+
+// Column dividers of one table layout, left to right, without the left edge
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ColumnLayout {
+    pub lines: Vec<f64>,
+    // At most this many characters of description per row, 0 wraps it at
+    // the width of its column only
+    pub desc_chars: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Template {
+    pub title: TextPlacement,
+    pub company_name: TextPlacement,
+    pub company_info: TextPlacement,
+    pub logo: LogoPlacement,
+    pub table_top: f64,
+    pub table_bottom: f64,
+    pub slogan: TextPlacement,
+    // Printed instead of the slogan the POS sends
+    pub slogan_text: Option<String>,
+    // Printed instead of the terms in data_dir, read from a file next to the
+    // template
+    pub terms: Option<String>,
+    // By layout name, see LAYOUTS. Parsed templates have all of them, a
+    // layout left out of one built by hand keeps its built in columns.
+    pub columns: Vec<(String, ColumnLayout)>,
+}
+
+// Layout names, how many column dividers they have and which column is the
+// description, the one left of the first divider being 0
+const LAYOUTS: [(&str, usize, Option<usize>); 6] = [
+    ("standard", 5, Some(1)),
+    ("standard-with-discounts", 6, Some(1)),
+    ("receipt", 1, Some(0)),
+    ("statement", 4, Some(2)),
+    ("z-report", 3, None),
+    ("delivery-ticket", 3, Some(1)),
+];
+
+// Edges of the item table
+const LEFT_EDGE: f64 = 54.0;
+const RIGHT_EDGE: f64 = 558.0;
+// Room for one character of the 8pt monospace item font, 0.6 em wide,
+// between the 5pt paddings of the description column
+const MIN_DESC_WIDTH: f64 = 2.0 * 5.0 + 0.6 * 8.0;
+
+// The keys of the template file, all of them after it was laid over the
+// default one
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TemplateFile {
+    title: TextPlacement,
+    company_name: TextPlacement,
+    company_info: TextPlacement,
+    logo: LogoPlacement,
+    table_top: f64,
+    table_bottom: f64,
+    slogan: TextPlacement,
+    slogan_text: Option<String>,
+    terms_file: Option<String>,
+    columns: BTreeMap<String, ColumnLayout>,
+}
+
+impl Default for Template {
+    fn default() -> Self {
+        return Self::parse("").expect("templates/default.toml is a valid template");
+    }
+}
+
+impl Template {
+    // Reads data_dir/templates/{name}.toml
+    pub fn load(data_dir: &str, name: &str) -> Result<Self, Error> {
+        let dir = format!("{data_dir}/templates");
+        let file = format!("{dir}/{name}.toml");
+        let text = match fs::read_to_string(&file) {
+            Ok(text) => text,
+            Err(e) => return Err(anyhow!(format!("Could not read the template from the file: `{file}`. Reason: `{e}`"))),
+        };
+        let (mut template, terms_file) = Self::parse_with_terms_file(&text)?;
+        if let Some(terms_file) = terms_file {
+            let terms_file = format!("{dir}/{terms_file}");
+            template.terms = match fs::read_to_string(&terms_file) {
+                Ok(terms) => Some(terms),
+                Err(e) => return Err(anyhow!(format!("Could not read the terms from the file: `{terms_file}`. Reason: `{e}`"))),
+            };
+        }
+        return Ok(template);
+    }
+
+    // Terms files can only be used through load
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        return Ok(Self::parse_with_terms_file(text)?.0);
+    }
+
+    fn parse_with_terms_file(text: &str) -> Result<(Self, Option<String>), Error> {
+        let invalid = |e: toml::de::Error| anyhow!(format!("The template isn't valid: {e}"));
+        let mut table: toml::Table = toml::from_str(DEFAULT_TEMPLATE).map_err(invalid)?;
+        overlay(&mut table, toml::from_str(text).map_err(invalid)?);
+        let file: TemplateFile = toml::Value::Table(table).try_into().map_err(invalid)?;
+        if let Some(layout) = file.columns.keys().find(|layout| !LAYOUTS.iter().any(|(name, _, _)| name == layout)) {
+            return Err(anyhow!(format!("The template has columns for an unknown table layout: `{layout}`")));
+        }
+        let template = Self {
+            title: file.title,
+            company_name: file.company_name,
+            company_info: file.company_info,
+            logo: file.logo,
+            table_top: file.table_top,
+            table_bottom: file.table_bottom,
+            slogan: file.slogan,
+            slogan_text: file.slogan_text,
+            terms: None,
+            columns: file.columns.into_iter().collect(),
+        };
+        for (layout, columns) in &template.columns {
+            let (_, count, desc_column) = LAYOUTS.iter().find(|(name, _, _)| name == layout).copied().unwrap_or(("", 0, None));
+            if columns.lines.len() != count {
+                return Err(anyhow!(format!("The `{layout}` columns of the template need {count} lines, not {}", columns.lines.len())));
+            }
+            if columns.lines.windows(2).any(|pair| pair[0] >= pair[1]) {
+                return Err(anyhow!(format!("The `{layout}` column lines of the template aren't in order from left to right")));
+            }
+            if let Some(column) = desc_column {
+                let left = if column == 0 { LEFT_EDGE } else { columns.lines[column - 1] };
+                let right = columns.lines.get(column).copied().unwrap_or(RIGHT_EDGE);
+                if right - left < MIN_DESC_WIDTH {
+                    return Err(anyhow!(format!("The `{layout}` description column of the template is {}pt wide, it needs at least {MIN_DESC_WIDTH}pt", right - left)));
+                }
+            }
+        }
+        return Ok((template, file.terms_file));
+    }
+
+    pub(crate) fn columns(&self, layout: &str) -> Option<&ColumnLayout> {
+        return self.columns.iter().find(|(name, _)| name == layout).map(|(_, columns)| columns);
+    }
+}
+
+// Keys of the template replace the ones of the default, tables are laid
+// over the default table of the same name
+fn overlay(base: &mut toml::Table, template: toml::Table) {
+    for (key, value) in template {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(template)) => overlay(base, template),
+            (_, value) => {
+                base.insert(key, value);
+            },
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use printpdf::{PdfDocumentReference, PdfLayerReference, IndirectFontRef, Mm, Pt};
+use crate::markdown::{self, Block, Run, Style};
+
+// Simple top-to-bottom text flow used for the appendix pages (terms &
+// conditions) and the notes block. Input is the Markdown subset understood by
+// the markdown module. Text is wrapped on word boundaries and a new page is
+// started whenever the cursor reaches the bottom margin.
+
+const PAGE_WIDTH: Pt = Pt(612.0);
+const PAGE_HEIGHT: Pt = Pt(792.0);
+const LEFT_MARGIN: Pt = Pt(54.0);
+const TOP_MARGIN: Pt = Pt(738.0);
+const BOTTOM_MARGIN: Pt = Pt(54.0);
+
+const TITLE_SIZE: f64 = 14.0;
+const HEADING_SIZE: f64 = 11.0;
+const BODY_SIZE: f64 = 9.0;
+const BODY_LINE_HEIGHT: Pt = Pt(12.0);
+const BULLET_INDENT: Pt = Pt(12.0);
+// Noto Sans averages a little over half an em per character, so this keeps
+// body text inside the 504pt between the margins.
+const BODY_MAX_CHARS: usize = 100;
+const HEADING_MAX_CHARS: usize = 80;
+
+// The faces used for each markdown style. Styles without a dedicated face
+// fall back to the closest one available.
+pub(crate) struct RunFonts<'a> {
+    pub regular: &'a IndirectFontRef,
+    pub bold: &'a IndirectFontRef,
+    pub italic: &'a IndirectFontRef,
+    pub bold_italic: &'a IndirectFontRef,
+}
+
+impl<'a> RunFonts<'a> {
+    fn get(&self, style: Style) -> &'a IndirectFontRef {
+        match style {
+            Style::Regular => self.regular,
+            Style::Bold => self.bold,
+            Style::Italic => self.italic,
+            Style::BoldItalic => self.bold_italic,
+        }
+    }
+}
+
+// Draws a single line of styled runs. The runs are written into one text
+// section so the viewer advances the cursor by the real glyph widths.
+pub(crate) fn draw_runs(layer: &PdfLayerReference, runs: &[Run], font_size: f64, x: Mm, y: Mm, fonts: &RunFonts) {
+    layer.begin_text_section();
+    layer.set_text_cursor(x, y);
+    for run in runs {
+        let font = fonts.get(run.style);
+        layer.set_font(font, font_size);
+        layer.write_text(run.text.clone(), font);
+    }
+    layer.end_text_section();
+}
+
+struct Flow<'a> {
+    doc: &'a PdfDocumentReference,
+    title: &'a str,
+    fonts: &'a RunFonts<'a>,
+    layer: PdfLayerReference,
+    cursor_y: Mm,
+}
+
+impl<'a> Flow<'a> {
+    fn new(doc: &'a PdfDocumentReference, title: &'a str, fonts: &'a RunFonts<'a>) -> Self {
+        let layer = Self::new_page(doc);
+        let mut flow = Self {
+            doc,
+            title,
+            fonts,
+            layer,
+            cursor_y: TOP_MARGIN.into(),
+        };
+        flow.add_title();
+        return flow;
+    }
+
+    fn new_page(doc: &PdfDocumentReference) -> PdfLayerReference {
+        let (page, layer) = doc.add_page(PAGE_WIDTH.into(), PAGE_HEIGHT.into(), "Layer 1");
+        return doc.get_page(page).get_layer(layer);
+    }
+
+    fn add_title(&mut self) {
+        self.layer.use_text(self.title, TITLE_SIZE, LEFT_MARGIN.into(), self.cursor_y, self.fonts.bold);
+        self.cursor_y -= Pt(TITLE_SIZE * 2.0).into();
+    }
+
+    // Make room for `height` on the current page, breaking to a new page if
+    // it doesn't fit.
+    fn reserve(&mut self, height: Mm) {
+        if self.cursor_y - height < BOTTOM_MARGIN.into() {
+            self.layer = Self::new_page(self.doc);
+            self.cursor_y = TOP_MARGIN.into();
+            self.add_title();
+        }
+    }
+
+    fn add_lines(&mut self, lines: &[Vec<Run>], font_size: f64, line_height: Mm, x: Mm) {
+        for line in lines {
+            self.reserve(line_height);
+            self.cursor_y -= line_height;
+            draw_runs(&self.layer, line, font_size, x, self.cursor_y, self.fonts);
+        }
+    }
+}
+
+// Appends the given markdown text to the document as one or more pages, each
+// headed with `title`.
+pub(crate) fn add_text_pages(doc: &PdfDocumentReference, title: &str, text: &str, fonts: &RunFonts) {
+    let mut flow = Flow::new(doc, title, fonts);
+    let paragraph_spacing: Mm = Pt(BODY_SIZE).into();
+    let left_margin: Mm = LEFT_MARGIN.into();
+    let mut previous_was_bullet = false;
+    for block in markdown::parse(text) {
+        match &block {
+            Block::Heading(runs) => {
+                let lines = markdown::wrap_runs(runs, HEADING_MAX_CHARS);
+                let heading_line_height: Mm = Pt(HEADING_SIZE * 1.5).into();
+                // Keep a heading together with the first line of its paragraph
+                flow.reserve(heading_line_height * lines.len() as f64 + BODY_LINE_HEIGHT.into());
+                flow.add_lines(&lines, HEADING_SIZE, heading_line_height, left_margin);
+            },
+            Block::Paragraph(runs) => {
+                let lines = markdown::wrap_runs(runs, BODY_MAX_CHARS);
+                flow.add_lines(&lines, BODY_SIZE, BODY_LINE_HEIGHT.into(), left_margin);
+            },
+            Block::Bullet(runs) => {
+                // List items are packed together, only the list as a whole
+                // gets paragraph spacing
+                if previous_was_bullet {
+                    flow.cursor_y += paragraph_spacing;
+                }
+                let lines = markdown::wrap_runs(runs, BODY_MAX_CHARS - 4);
+                flow.reserve(BODY_LINE_HEIGHT.into());
+                flow.layer.use_text("•", BODY_SIZE, left_margin + Pt(3.0).into(), flow.cursor_y - BODY_LINE_HEIGHT.into(), fonts.regular);
+                flow.add_lines(&lines, BODY_SIZE, BODY_LINE_HEIGHT.into(), left_margin + BULLET_INDENT.into());
+            },
+        }
+        previous_was_bullet = matches!(block, Block::Bullet(_));
+        flow.cursor_y -= paragraph_spacing;
+    }
+}
+
+// Draws markdown text inside a fixed area on an existing page. Lines that
+// don't fit above `bottom` are dropped.
+pub(crate) fn draw_text_box(layer: &PdfLayerReference, text: &str, x: Mm, top: Mm, bottom: Mm, max_chars: usize, fonts: &RunFonts) {
+    let font_size = 8.0;
+    let line_height: Mm = Pt(10.0).into();
+    let bullet_indent: Mm = Pt(8.0).into();
+    let mut cursor_y = top;
+    for block in markdown::parse(text) {
+        let (lines, indent) = match &block {
+            Block::Heading(runs) | Block::Paragraph(runs) => (markdown::wrap_runs(runs, max_chars), Mm(0.0)),
+            Block::Bullet(runs) => (markdown::wrap_runs(runs, max_chars - 2), bullet_indent),
+        };
+        for (i, line) in lines.iter().enumerate() {
+            cursor_y -= line_height;
+            if cursor_y < bottom {
+                return;
+            }
+            if i == 0 && matches!(block, Block::Bullet(_)) {
+                layer.use_text("•", font_size, x, cursor_y, fonts.regular);
+            }
+            draw_runs(layer, line, font_size, x + indent, cursor_y, fonts);
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use printpdf::{Color, Rgb};
+
+// Typographic and visual choices which can differ between deployments without
+// changing the layout itself.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FontWeight {
+    Regular,
+    SemiBold,
+    Bold,
+}
+
+impl FontWeight {
+    // Picks the closest available weight for a CSS style numeric weight,
+    // e.g. 400, 600 or 700
+    pub fn from_numeric(weight: u16) -> Self {
+        return match weight {
+            0..=499 => FontWeight::Regular,
+            500..=649 => FontWeight::SemiBold,
+            _ => FontWeight::Bold,
+        };
+    }
+}
+
+// An RGB color with components between 0.0 and 1.0
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThemeColor {
+    pub r: f64,
+    pub g: f64,
+    pub b: f64,
+}
+
+impl ThemeColor {
+    pub fn rgb(r: f64, g: f64, b: f64) -> Self {
+        return Self { r, g, b };
+    }
+
+    pub fn gray(level: f64) -> Self {
+        return Self { r: level, g: level, b: level };
+    }
+
+    pub(crate) fn to_pdf(self) -> Color {
+        return Color::Rgb(Rgb::new(self.r, self.g, self.b, None));
+    }
+}
+
+// How a stroked line is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineStyle {
+    Solid,
+    Dashed,
+}
+
+// Which sides of a box get a border
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edges {
+    pub top: bool,
+    pub right: bool,
+    pub bottom: bool,
+    pub left: bool,
+}
+
+impl Edges {
+    pub const ALL: Edges = Edges { top: true, right: true, bottom: true, left: true };
+    pub const NONE: Edges = Edges { top: false, right: false, bottom: false, left: false };
+
+    pub fn all(&self) -> bool {
+        return *self == Edges::ALL;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoxStyle {
+    // In points. Only used when every edge is drawn.
+    pub corner_radius: f64,
+    pub edges: Edges,
+    pub line: LineStyle,
+}
+
+impl Default for BoxStyle {
+    fn default() -> Self {
+        return Self {
+            corner_radius: 0.0,
+            edges: Edges::ALL,
+            line: LineStyle::Solid,
+        };
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Theme {
+    // Document title, company name and document number
+    pub title_weight: FontWeight,
+    // The small captions above header fields ("Sold to:", "Clerk:", ...)
+    pub label_weight: FontWeight,
+    // The grand total, amount due and summary headings
+    pub total_weight: FontWeight,
+    // Background of the item table header row, None for no shading
+    pub header_fill: Option<ThemeColor>,
+    // Background of every other item row, None for no shading
+    pub row_stripe_fill: Option<ThemeColor>,
+    // Outline of the header, customer info and item table boxes
+    pub box_style: BoxStyle,
+    // The lines separating columns and rows inside a box
+    pub rule_style: LineStyle,
+    // Negative amounts, when GenOptions asks for them in red
+    pub negative_color: ThemeColor,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        return Self {
+            title_weight: FontWeight::Bold,
+            label_weight: FontWeight::Bold,
+            total_weight: FontWeight::Bold,
+            header_fill: Some(ThemeColor::gray(0.85)),
+            row_stripe_fill: Some(ThemeColor::gray(0.94)),
+            box_style: BoxStyle::default(),
+            rule_style: LineStyle::Solid,
+            negative_color: ThemeColor::rgb(0.8, 0.0, 0.0),
+        };
+    }
+}
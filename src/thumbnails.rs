@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+use std::fs;
+use std::sync::{Arc, Mutex};
+use printpdf::{PdfLayerReference, Image, ImageTransform, Mm, Pt};
+use printpdf::image_crate::{self, DynamicImage};
+use anyhow::{Error, Result, anyhow};
+
+// Product images for the item table, from the files the product catalog
+// names in data_dir/products. The same few products show up on most
+// documents, so images are decoded once, shrunk to thumbnail size, and kept
+// for as long as the resources are.
+
+// Side of the square a thumbnail is fitted in, in points
+pub(crate) const THUMBNAIL_SIZE: f64 = 26.0;
+// Pixels on the longer side, enough for 200 dpi
+const THUMBNAIL_PIXELS: u32 = 72;
+
+pub(crate) struct ThumbnailCache {
+    // No images at all when None
+    dir: Option<String>,
+    // None for files which don't exist, so they're only looked for once
+    images: Mutex<HashMap<String, Option<Arc<DynamicImage>>>>,
+}
+
+impl ThumbnailCache {
+    pub(crate) fn new(dir: Option<String>) -> Self {
+        return Self { dir, images: Mutex::new(HashMap::new()) };
+    }
+
+    pub(crate) fn get(&self, file: &str) -> Result<Option<Arc<DynamicImage>>, Error> {
+        let Some(dir) = &self.dir else { return Ok(None) };
+        // A panic elsewhere can't leave the map half updated
+        let mut images = self.images.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(image) = images.get(file) {
+            return Ok(image.clone());
+        }
+        let path = format!("{dir}/{file}");
+        let image = match fs::read(&path) {
+            Ok(bytes) => match image_crate::load_from_memory(&bytes) {
+                Ok(decoded) => Some(Arc::new(decoded.thumbnail(THUMBNAIL_PIXELS, THUMBNAIL_PIXELS))),
+                Err(e) => return Err(anyhow!(format!("Could not decode the product image `{path}`. Reason: `{e}`"))),
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(anyhow!(format!("Could not read the product image from the file: `{path}`. Reason: `{e}`"))),
+        };
+        images.insert(file.to_owned(), image.clone());
+        return Ok(image);
+    }
+}
+
+// Fits the image in the thumbnail square hanging down from (x, top)
+pub(crate) fn draw_thumbnail(layer: &PdfLayerReference, image: &DynamicImage, x: Mm, top: Mm) {
+    let (x, top) = (Into::<Pt>::into(x).0, Into::<Pt>::into(top).0);
+    let width = image.width() as f64;
+    let height = image.height() as f64;
+    if width == 0.0 || height == 0.0 {
+        return;
+    }
+    // At 72 dpi one pixel is one point
+    let scale = (THUMBNAIL_SIZE / width).min(THUMBNAIL_SIZE / height);
+    let x = x + (THUMBNAIL_SIZE - width * scale) / 2.0;
+    Image::from_dynamic_image(image).add_to_layer(layer.clone(), ImageTransform {
+        translate_x: Some(Pt(x).into()),
+        translate_y: Some(Pt(top - height * scale).into()),
+        scale_x: Some(scale),
+        scale_y: Some(scale),
+        dpi: Some(72.0),
+        ..Default::default()
+    });
+}
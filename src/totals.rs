@@ -0,0 +1,142 @@
+use std::fmt;
+use anyhow::{Error, Result};
+use crate::money::{Discount, Money, Rounding};
+use crate::{export, is_subtotal, PrePassOptions, ReceiptInfo};
+
+// Recomputes the totals of a document from its item lines and compares them
+// with the ones the POS sent, which are occasionally wrong. Taxes can only be
+// computed for the rates configured, other tax totals are taken as sent, and
+// so is any other row between the subtotal and the total (e.g. surcharges).
+// Line amounts are after their discounts, so a discount row in the totals is
+// only checked against the discounts of the lines.
+
+#[derive(Debug, Clone)]
+pub struct TaxRate {
+    // Start of the name of the tax total, e.g. "GST"
+    pub name: String,
+    // e.g. 7.5 for 7.5%, of the taxable lines
+    pub percent: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComputedTotals {
+    pub subtotal: Money,
+    // Of the lines with an amount in the discount column
+    pub discounts: Money,
+    // By the name of the tax total
+    pub taxes: Vec<(String, Money)>,
+    pub total: Money,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MismatchedTotal {
+    pub name: String,
+    pub supplied: Money,
+    pub computed: Money,
+    // Supplied minus computed
+    pub delta: Money,
+}
+
+// Returned inside the anyhow::Error, like PayloadError
+#[derive(Debug)]
+pub struct TotalsMismatch {
+    pub doc_number: String,
+    pub totals: Vec<MismatchedTotal>,
+}
+
+impl fmt::Display for TotalsMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "The totals of document {} don't add up:", self.doc_number)?;
+        for total in &self.totals {
+            write!(f, " {} is {} but comes to {} (off by {});", total.name, total.supplied, total.computed, total.delta)?;
+        }
+        return Ok(());
+    }
+}
+
+impl std::error::Error for TotalsMismatch {}
+
+// Taxes computed on the whole taxable amount may be a cent off the POS's,
+// which rounds them line by line
+const TAX_TOLERANCE: i64 = 1;
+
+impl ReceiptInfo {
+    // The totals as they should be, or a TotalsMismatch error with the ones
+    // which aren't
+    pub fn compute_totals(&self, tax_rates: &[TaxRate], rounding: &Rounding) -> Result<ComputedTotals, Error> {
+        let computed = self.derive_totals(tax_rates, rounding)?;
+        let mut mismatched = Vec::new();
+        for row in &self.totals {
+            let name = row.name.trim_end_matches(':').trim();
+            let (expected, tolerance) = if is_subtotal(&row.name) {
+                (computed.subtotal, 0)
+            } else if row.name.eq("Total:") {
+                (computed.total, TAX_TOLERANCE * computed.taxes.len() as i64)
+            } else if is_discount(&row.name) {
+                (computed.discounts, 0)
+            } else if let Some((_, tax)) = computed.taxes.iter().find(|(tax, _)| tax == name) {
+                (*tax, TAX_TOLERANCE)
+            } else {
+                continue;
+            };
+            // Discounts are sent either way round
+            let supplied = if is_discount(&row.name) { row.value.abs() } else { row.value };
+            let delta = supplied.checked_sub(expected)?;
+            if delta.abs().cents() > tolerance {
+                mismatched.push(MismatchedTotal { name: name.to_owned(), supplied, computed: expected, delta });
+            }
+        }
+        if !mismatched.is_empty() {
+            return Err(TotalsMismatch { doc_number: self.doc_number.clone(), totals: mismatched }.into());
+        }
+        return Ok(computed);
+    }
+
+    // Run by pre_pass before it adds anything to the totals itself
+    pub(crate) fn verify_totals(&self, options: &PrePassOptions) -> Result<(), Error> {
+        if !options.verify_totals || self.draft || self.item_lines.is_empty() {
+            return Ok(());
+        }
+        self.compute_totals(&options.tax_rates, &options.rounding)?;
+        return Ok(());
+    }
+
+    fn derive_totals(&self, tax_rates: &[TaxRate], rounding: &Rounding) -> Result<ComputedTotals, Error> {
+        let (mut subtotal, mut taxable, mut discounts) = (Money::ZERO, Money::ZERO, Money::ZERO);
+        for line in &self.item_lines {
+            let Some(amount) = line.amount else { continue };
+            subtotal = subtotal.checked_add(amount)?;
+            if line.taxable {
+                taxable = taxable.checked_add(amount)?;
+            }
+            // Percentages aren't amounts
+            if let Some(discount) = line.discount.as_ref().and_then(Discount::amount) {
+                discounts = discounts.checked_add(discount.abs())?;
+            }
+        }
+
+        let mut taxes = Vec::new();
+        let mut total = subtotal;
+        for row in &self.totals {
+            if row.name.trim().is_empty() || is_subtotal(&row.name) || is_discount(&row.name) || row.name.eq("Total:") {
+                continue;
+            }
+            let name = row.name.trim_end_matches(':').trim();
+            let rate = tax_rates.iter().find(|rate| name.to_uppercase().starts_with(&rate.name.to_uppercase()));
+            let amount = match rate {
+                Some(rate) => taxable.times(rate.percent / 100.0, rounding),
+                None => row.value,
+            };
+            if rate.is_some() || export::is_tax_total(&row.name) {
+                taxes.push((name.to_owned(), amount));
+            }
+            total = total.checked_add(amount)?;
+        }
+        return Ok(ComputedTotals { subtotal, discounts, taxes, total });
+    }
+}
+
+
+pub(crate) fn is_discount(name: &str) -> bool {
+    return name.trim().to_uppercase().starts_with("DISCOUNT");
+}
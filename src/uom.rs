@@ -0,0 +1,98 @@
+use crate::money;
+
+// Units of measure and how many decimals their quantities are printed with,
+// e.g. none for "EA" but three for tons. Quantities in units the catalog
+// doesn't know are printed as the POS sent them. Units can also convert to
+// a unit of the other measurement system, for showing both.
+
+#[derive(Debug, Clone)]
+pub struct UomCatalog {
+    // Unit to decimal places
+    units: Vec<(String, usize)>,
+    conversions: Vec<Conversion>,
+}
+
+#[derive(Debug, Clone)]
+struct Conversion {
+    from: String,
+    to: String,
+    // Quantity in `to` per `from`
+    factor: f64,
+    // Decimal places of the converted quantity
+    precision: usize,
+}
+
+impl Default for UomCatalog {
+    fn default() -> Self {
+        return Self::new()
+            .with_unit("EA", 0)
+            .with_unit("TN", 3)
+            .with_unit("TON", 3)
+            // Short tons to metric tonnes
+            .with_conversion("TN", "t", 0.90718474, 2)
+            .with_conversion("TON", "t", 0.90718474, 2)
+            .with_conversion("LB", "kg", 0.45359237, 2);
+    }
+}
+
+impl UomCatalog {
+    // A catalog without any units
+    pub fn new() -> Self {
+        return Self { units: Vec::new(), conversions: Vec::new() };
+    }
+
+    // Adds a unit, or changes the precision of one already in the catalog
+    pub fn with_unit(mut self, uom: &str, precision: usize) -> Self {
+        let uom = uom.trim().to_uppercase();
+        match self.units.iter_mut().find(|(unit, _)| unit.eq(&uom)) {
+            Some(unit) => unit.1 = precision,
+            None => self.units.push((uom, precision)),
+        }
+        return self;
+    }
+
+    // e.g. ("TON", "t", 0.90718474, 2). Replaces an earlier conversion of the same unit.
+    pub fn with_conversion(mut self, from: &str, to: &str, factor: f64, precision: usize) -> Self {
+        let from = from.trim().to_uppercase();
+        self.conversions.retain(|conversion| conversion.from != from);
+        self.conversions.push(Conversion { from, to: to.to_owned(), factor, precision });
+        return self;
+    }
+
+    // The quantity in the other unit, e.g. "13.17 t" for 14.52 TON
+    pub fn convert(&self, uom: &str, quantity: &str) -> Option<String> {
+        let uom = uom.trim();
+        let conversion = self.conversions.iter().find(|conversion| conversion.from.eq_ignore_ascii_case(uom))?;
+        let quantity = money::parse_amount(quantity).ok()?;
+        return Some(format!("{:.*} {}", conversion.precision, quantity * conversion.factor, conversion.to));
+    }
+
+    pub fn precision(&self, uom: &str) -> Option<usize> {
+        let uom = uom.trim();
+        return self.units
+            .iter()
+            .find(|(unit, _)| unit.eq_ignore_ascii_case(uom))
+            .map(|(_, precision)| *precision);
+    }
+
+    // The quantity with the unit's decimal places. Digits beyond them are
+    // kept rather than rounded away, so "2.5" EA stays "2.5".
+    pub fn format_quantity(&self, uom: &str, quantity: &str) -> String {
+        let (precision, cleaned) = match (self.precision(uom), money::cleanup_amount(quantity)) {
+            (Some(precision), Ok(cleaned)) => (precision, cleaned),
+            _ => return quantity.to_owned(),
+        };
+        let (whole, fraction) = match cleaned.split_once('.') {
+            Some((whole, fraction)) => (whole, fraction.trim_end_matches('0')),
+            None => (cleaned.as_str(), ""),
+        };
+        let mut fraction = fraction.to_owned();
+        while fraction.len() < precision {
+            fraction.push('0');
+        }
+        if fraction.is_empty() {
+            return whole.to_owned();
+        }
+        return format!("{whole}.{fraction}");
+    }
+}
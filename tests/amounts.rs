@@ -0,0 +1,182 @@
+use accutools_core::{cleanup_amount, parse_amount, AmountFormat, Money, NegativeStyle, Rounding, RoundingMode};
+use proptest::prelude::*;
+use common::{money, receipt_with_payment, total};
+
+mod common;
+
+fn group_thousands(whole: u64) -> String {
+    let digits = whole.to_string();
+    let mut grouped = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    return grouped;
+}
+
+// An amount the way a clerk or a POS export might write it
+fn written_amount(cents: i64, grouped: bool, dollar_sign: bool, negative_style: u8) -> String {
+    let whole = cents.unsigned_abs() / 100;
+    let whole = if grouped { group_thousands(whole) } else { whole.to_string() };
+    let magnitude = format!("{}{whole}.{:02}", if dollar_sign { "$" } else { "" }, cents.unsigned_abs() % 100);
+    if cents >= 0 {
+        return magnitude;
+    }
+    return match negative_style {
+        0 => format!("-{magnitude}"),
+        1 => format!("({magnitude})"),
+        _ => format!("{magnitude}-"),
+    };
+}
+
+proptest! {
+    #[test]
+    fn cleanup_never_panics(text in "\\PC*") {
+        let _ = cleanup_amount(&text);
+    }
+
+    #[test]
+    fn written_amounts_parse_back(
+        cents in -100_000_000_000i64..100_000_000_000,
+        grouped: bool,
+        dollar_sign: bool,
+        negative_style in 0u8..3,
+    ) {
+        let text = written_amount(cents, grouped, dollar_sign, negative_style);
+        let parsed = parse_amount(&text).unwrap();
+        prop_assert!((parsed - cents as f64 / 100.0).abs() < 1e-6, "{} parsed as {}", text, parsed);
+    }
+
+    #[test]
+    fn misplaced_separators_are_rejected(thousands in 1u32..1000, rest in 0u32..100) {
+        prop_assert!(parse_amount(&format!("{thousands},{rest:02}.00")).is_err());
+        prop_assert!(parse_amount(&format!("{thousands},,{rest:02}0")).is_err());
+    }
+
+    #[test]
+    fn double_negatives_are_rejected(cents in 1i64..1_000_000) {
+        let text = written_amount(-cents, true, true, 0);
+        prop_assert!(cleanup_amount(&format!("({text})")).is_err());
+        prop_assert!(cleanup_amount(&format!("{text}-")).is_err());
+    }
+
+    #[test]
+    fn non_numeric_text_is_rejected(text in "[a-zA-Z%#]+") {
+        prop_assert!(parse_amount(&text).is_err());
+    }
+
+    #[test]
+    fn pre_pass_reads_grouped_deposits(cents in 1i64..100_000_000, dollar_sign: bool) {
+        let mut receipt = receipt_with_payment(&written_amount(cents, true, dollar_sign, 0));
+        receipt.pre_pass().unwrap();
+        let expected = Money::from_cents(cents);
+        prop_assert_eq!(receipt.item_lines[0].amount, Some(expected));
+        prop_assert_eq!(receipt.totals[0].value, expected);
+    }
+
+    #[test]
+    fn displayed_amounts_parse_back(cents in -100_000_000_000i64..100_000_000_000, negative_style in 0u8..3) {
+        let negative = [NegativeStyle::Minus, NegativeStyle::Parentheses, NegativeStyle::Red][negative_style as usize];
+        let format = AmountFormat { negative, ..AmountFormat::default() };
+        let text = format.display(&written_amount(cents, false, false, 0));
+        prop_assert_eq!(parse_amount(&text).unwrap(), parse_amount(&written_amount(cents, true, false, 0)).unwrap());
+        prop_assert_eq!(format.is_red(&text), negative == NegativeStyle::Red && cents < 0);
+    }
+
+    #[test]
+    fn rounding_is_idempotent(value in -1e9f64..1e9, mode in 0u8..3, precision in 0usize..5) {
+        let mode = [RoundingMode::HalfUp, RoundingMode::HalfEven, RoundingMode::Truncate][mode as usize];
+        let rounding = Rounding { mode, precision };
+        let once = rounding.round(value);
+        prop_assert_eq!(rounding.round(once), once);
+        prop_assert_eq!(rounding.format(once), rounding.format(value));
+    }
+
+    #[test]
+    fn truncation_never_grows(value in -1e9f64..1e9) {
+        let rounding = Rounding { mode: RoundingMode::Truncate, precision: 2 };
+        prop_assert!(rounding.round(value).abs() <= value.abs() + 1e-6);
+    }
+}
+
+#[test]
+fn half_even_ties() {
+    let rounding = Rounding { mode: RoundingMode::HalfEven, precision: 2 };
+    assert_eq!(rounding.format(2.665), "2.66");
+    assert_eq!(rounding.format(2.675), "2.68");
+    assert_eq!(rounding.format(-2.665), "-2.66");
+    let rounding = Rounding { mode: RoundingMode::HalfUp, precision: 2 };
+    assert_eq!(rounding.format(2.665), "2.67");
+}
+
+#[test]
+fn amount_display() {
+    let format = AmountFormat::default();
+    assert_eq!(format.display("1234567.89"), "1,234,567.89");
+    assert_eq!(format.display("-0.00"), "0.00");
+    assert_eq!(format.display("+12.00"), "+12.00");
+    assert_eq!(format.display("N/C"), "N/C");
+    let format = AmountFormat { negative: NegativeStyle::Parentheses, ..AmountFormat::for_locale("fr-CA") };
+    assert_eq!(format.display("-1234.50"), "(1 234,50)");
+    assert_eq!(format.display("1234.50"), "1 234,50 ");
+}
+
+#[test]
+fn input_format_cleanup() {
+    let german = AmountFormat::for_locale("de");
+    assert_eq!(german.cleanup("1.234,56 €").unwrap(), "1234.56");
+    assert_eq!(german.cleanup("-12,5").unwrap(), "-12.5");
+    assert!(german.cleanup("1234.56").is_err());
+    let french = AmountFormat::for_locale("fr");
+    assert_eq!(french.cleanup("1\u{a0}234,56 $").unwrap(), "1234.56");
+    assert_eq!(AmountFormat::default().cleanup("(1,234.50)").unwrap(), "-1234.50");
+}
+
+#[test]
+fn overpayment_gives_change() {
+    let mut receipt = receipt_with_payment("50.00");
+    receipt.payments[0].name = String::from("Cash");
+    receipt.totals = vec![total("Total:", "41.25")];
+    receipt.amount_due = Some(money("-8.75"));
+    receipt.pre_pass().unwrap();
+    assert_eq!(receipt.change_due, Some(money("8.75")));
+    assert_eq!(receipt.amount_due, Some(Money::ZERO));
+}
+
+#[test]
+fn zero_deposit_is_written_out() {
+    let mut receipt = receipt_with_payment("0.00");
+    receipt.pre_pass().unwrap();
+    assert_eq!(receipt.item_lines[0].description, "Received as cash deposit the sum of zero dollars for materials.");
+    assert_eq!(receipt.total(), Some(Money::ZERO));
+}
+
+#[test]
+fn negative_deposit_is_a_refund() {
+    let mut receipt = receipt_with_payment("-25.00");
+    receipt.pre_pass().unwrap();
+    assert!(receipt.item_lines[0].description.starts_with("Refunded from cash deposit the sum of "));
+    assert_eq!(receipt.item_lines[0].amount, Some(money("-25.00")));
+    assert_eq!(receipt.total(), Some(money("-25.00")));
+}
+
+#[test]
+fn money_in_cents() {
+    let half_even = Rounding { mode: RoundingMode::HalfEven, precision: 2 };
+    assert_eq!(Money::parse("$1,234.567", &Rounding::default()).unwrap().cents(), 123457);
+    assert_eq!(Money::parse("2.665", &half_even).unwrap().to_string(), "2.66");
+    assert_eq!(Money::parse("(0.10)", &half_even).unwrap().to_string(), "-0.10");
+    let total = Money::checked_sum(["0.10", "0.20"].iter().map(|text| text.parse::<Money>().unwrap())).unwrap();
+    assert_eq!(total, Money::from_cents(30));
+    assert_eq!(Money::ZERO.checked_sub(total).unwrap().to_string(), "-0.30");
+    // i64::MAX cents is 92233720368547758.07
+    assert_eq!(Money::parse("92233720368547758.07", &Rounding::default()).unwrap(), Money::from_cents(i64::MAX));
+    assert!(Money::parse("92233720368547758.08", &Rounding::default()).is_err());
+    assert!(Money::parse("92233720368547758.075", &Rounding::default()).is_err());
+    assert!(Money::from_cents(i64::MAX).checked_add(Money::from_cents(1)).is_err());
+    assert!(Money::from_cents(i64::MIN).checked_sub(Money::from_cents(1)).is_err());
+    assert!(Money::checked_sum([Money::from_cents(i64::MAX), Money::from_cents(1)]).is_err());
+    assert!(Money::from_cents(i64::MIN).checked_neg().is_err());
+}
@@ -0,0 +1,36 @@
+// Renders with the compiled in fonts
+#![cfg(feature = "embedded-resources")]
+
+use accutools_core::{extract_text, gen_pdf_bytes_with_options, Amount, DocType, GenOptions, ItemLine, PdfResources, ReceiptInfo};
+use printpdf::lopdf::Document;
+
+fn receipt() -> ReceiptInfo {
+    return ReceiptInfo {
+        date: String::from("01/31/2024"),
+        doc_number: String::from("1"),
+        doc_type: DocType::Receipt,
+        item_lines: vec![ItemLine {
+            description: String::from("Gravel"),
+            amount: Some("12.50".parse().unwrap()),
+            ..ItemLine::default()
+        }],
+        totals: vec![Amount { name: String::from("Total:"), value: "12.50".parse().unwrap() }],
+        ..ReceiptInfo::default()
+    };
+}
+
+#[test]
+fn fonts_are_subset_to_fit() {
+    // The whole faces alone are several times this
+    let options = GenOptions { max_size: Some(100_000), ..GenOptions::default() };
+    let resources = PdfResources::embedded().unwrap();
+    let bytes = gen_pdf_bytes_with_options(&receipt(), &resources, &options).unwrap();
+    assert!(bytes.len() <= 100_000);
+    let doc = Document::load_mem(&bytes).unwrap();
+    let font_names: Vec<&str> = doc.objects.values()
+        .filter_map(|object| object.as_dict().ok()?.get(b"FontName").ok()?.as_name_str().ok())
+        .collect();
+    assert!(!font_names.is_empty());
+    assert!(font_names.iter().all(|name| name.len() > 7 && name.as_bytes()[6] == b'+'), "{font_names:?}");
+    assert!(extract_text(&bytes).unwrap()[0].contains("Gravel"));
+}
@@ -0,0 +1,53 @@
+use accutools_core::{Amount, CurrencyFormat, DeliverySite, DocType, ItemLine, PriceBreak, ReceiptInfo};
+
+fn receipt() -> ReceiptInfo {
+    return ReceiptInfo {
+        date: String::from("01/31/2024"),
+        doc_number: String::from("1"),
+        doc_type: DocType::Quote,
+        item_lines: vec![ItemLine {
+            code: String::from("GRV"),
+            description: String::from("Gravel"),
+            image: String::from("gravel.jpg"),
+            price_breaks: vec![PriceBreak { min_quantity: 10.0, unit_price: String::from("9.50") }],
+            amount: Some("12.50".parse().unwrap()),
+            ..ItemLine::default()
+        }],
+        totals: vec![Amount { name: String::from("Total:"), value: "12.50".parse().unwrap() }],
+        delivery_site: Some(DeliverySite { address: String::from("1 Quarry Rd"), coordinates: Some((45.5, -73.6)) }),
+        currency: Some(CurrencyFormat::default()),
+        ..ReceiptInfo::default()
+    };
+}
+
+#[test]
+fn checksums_verify_their_record() {
+    let record = receipt();
+    let checksum = record.checksum();
+    assert_eq!(checksum.len(), 64);
+    assert!(record.verify_checksum(&checksum));
+    assert!(record.verify_checksum(&record.short_checksum().to_uppercase()));
+    assert!(!record.verify_checksum(&checksum[..4]));
+}
+
+#[test]
+fn checksums_change_with_any_field() {
+    let checksum = receipt().checksum();
+    let alterations: [fn(&mut ReceiptInfo); 6] = [
+        |receipt| receipt.totals[0].value = "12.51".parse().unwrap(),
+        |receipt| receipt.item_lines[0].image = String::from("sand.jpg"),
+        |receipt| receipt.item_lines[0].price_breaks[0].min_quantity = 20.0,
+        |receipt| receipt.delivery_site.as_mut().unwrap().coordinates = Some((45.5, -73.7)),
+        |receipt| receipt.currency.as_mut().unwrap().decimal_separator = ',',
+        // Text moved from one field to the next
+        |receipt| {
+            receipt.item_lines[0].code = String::from("GRVG");
+            receipt.item_lines[0].description = String::from("ravel");
+        },
+    ];
+    for alter in alterations {
+        let mut altered = receipt();
+        alter(&mut altered);
+        assert!(!altered.verify_checksum(&checksum));
+    }
+}
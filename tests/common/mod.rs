@@ -0,0 +1,30 @@
+// Documents shared by the tests. Not every test file uses all of them.
+#![allow(dead_code)]
+
+use accutools_core::{Amount, DocType, ItemLine, Money, ReceiptInfo};
+
+pub fn receipt_with_payment(value: &str) -> ReceiptInfo {
+    return ReceiptInfo {
+        date: String::from("01/31/2024"),
+        doc_number: String::from("1"),
+        doc_type: DocType::Receipt,
+        payments: vec![Amount { name: String::from("Pay on Account"), value: value.parse().unwrap() }],
+        ..ReceiptInfo::default()
+    };
+}
+
+pub fn item_line(amount: &str, taxable: bool) -> ItemLine {
+    return ItemLine {
+        amount: Some(amount.parse().unwrap()),
+        taxable,
+        ..ItemLine::default()
+    };
+}
+
+pub fn total(name: &str, value: &str) -> Amount {
+    return Amount { name: name.to_owned(), value: value.parse().unwrap() };
+}
+
+pub fn money(text: &str) -> Money {
+    return text.parse().unwrap();
+}
@@ -0,0 +1,59 @@
+use accutools_core::{ContainerDeposit, DocType, ItemLine, PrePassOptions, ReceiptInfo};
+use common::{item_line, money, total};
+
+mod common;
+
+fn line(code: &str, quantity: &str, amount: &str, taxable: bool) -> ItemLine {
+    return ItemLine {
+        code: code.to_owned(),
+        quantity: quantity.to_owned(),
+        ..item_line(amount, taxable)
+    };
+}
+
+#[test]
+fn deposits_follow_the_lines_which_carry_them() {
+    let mut receipt = ReceiptInfo {
+        date: String::from("01/31/2024"),
+        doc_number: String::from("1"),
+        doc_type: DocType::Invoice,
+        item_lines: vec![
+            line("WATER-24", "2", "3.00", true),
+            line("GRV", "", "10.00", true),
+            line("WATER-24", "1", "1.50", true),
+            // Sent by the POS
+            line("DEP10", "1", "0.10", false),
+        ],
+        totals: vec![total("Subtotal:", "14.60"), total("Total:", "14.60")],
+        amount_due: Some(money("14.60")),
+        ..ReceiptInfo::default()
+    };
+    let options = PrePassOptions {
+        container_deposits: vec![ContainerDeposit {
+            item_code: String::from("WATER-24"),
+            deposit_code: String::from("DEP10"),
+            description: String::from("Container deposit"),
+            amount: 0.10,
+        }],
+        ..PrePassOptions::default()
+    };
+    // A second pre_pass finds every deposit already billed
+    for _ in 0..2 {
+        receipt.pre_pass_with_options(&options).unwrap();
+        let lines: Vec<(&str, &str, String, bool)> = receipt.item_lines
+            .iter()
+            .map(|line| (line.code.as_str(), line.quantity.as_str(), line.amount.unwrap().to_string(), line.taxable))
+            .collect();
+        assert_eq!(lines, [
+            ("WATER-24", "2", String::from("3.00"), true),
+            ("DEP10", "2", String::from("0.20"), false),
+            ("GRV", "", String::from("10.00"), true),
+            ("WATER-24", "1", String::from("1.50"), true),
+            ("DEP10", "1", String::from("0.10"), false),
+        ]);
+        assert_eq!(receipt.item_lines[1].description, "Container deposit");
+        let totals: Vec<String> = receipt.totals.iter().map(|total| total.value.to_string()).collect();
+        assert_eq!(totals, ["14.80", "14.80"]);
+        assert_eq!(receipt.amount_due, Some(money("14.80")));
+    }
+}
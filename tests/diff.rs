@@ -0,0 +1,55 @@
+use accutools_core::{diff, Change, ItemLine};
+use common::{item_line, money, receipt_with_payment, total};
+
+mod common;
+
+fn line(code: &str, description: &str, quantity: &str, amount: &str) -> ItemLine {
+    return ItemLine {
+        code: code.to_owned(),
+        description: description.to_owned(),
+        quantity: quantity.to_owned(),
+        ..item_line(amount, true)
+    };
+}
+
+#[test]
+fn lines_without_a_code_are_matched_by_description() {
+    let mut before = receipt_with_payment("10.00");
+    before.item_lines = vec![line("", "Delivery", "1", "10.00")];
+    let mut after = receipt_with_payment("10.00");
+    after.item_lines = vec![line("", "Delivery to site", "1", "10.00")];
+    assert_eq!(diff(&before, &after).changes, [
+        Change::LineAdded { code: String::new(), description: String::from("Delivery to site"), amount: Some(money("10.00")) },
+        Change::LineRemoved { code: String::new(), description: String::from("Delivery"), amount: Some(money("10.00")) },
+    ]);
+}
+
+#[test]
+fn duplicate_codes_are_matched_in_order() {
+    let mut before = receipt_with_payment("30.00");
+    before.item_lines = vec![line("GRV", "Gravel", "1", "10.00"), line("GRV", "Gravel", "2", "20.00")];
+    let mut after = receipt_with_payment("40.00");
+    after.item_lines = vec![line("GRV", "Gravel", "1", "10.00"), line("GRV", "Gravel", "3", "30.00")];
+    let changes = diff(&before, &after).changes;
+    let changed_fields: Vec<(&str, &str, &str)> = changes.iter()
+        .filter_map(|change| match change {
+            Change::LineChanged { field, before, after, .. } => Some((*field, before.as_str(), after.as_str())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(changed_fields, [("quantity", "2", "3"), ("amount", "20.00", "30.00")]);
+    assert!(!changes.iter().any(|change| matches!(change, Change::LineAdded { .. } | Change::LineRemoved { .. })));
+}
+
+#[test]
+fn removed_totals_and_payments() {
+    let mut before = receipt_with_payment("10.00");
+    before.totals = vec![total("Subtotal:", "9.00"), total("Tax:", "1.00"), total("Total:", "10.00")];
+    let mut after = receipt_with_payment("10.00");
+    after.payments.clear();
+    after.totals = vec![total("Subtotal:", "9.0"), total("Total:", "10")];
+    assert_eq!(diff(&before, &after).changes, [
+        Change::Total { name: String::from("Tax:"), before: Some(money("1.00")), after: None },
+        Change::Payment { name: String::from("Pay on Account"), before: Some(money("10.00")), after: None },
+    ]);
+}
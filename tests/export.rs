@@ -0,0 +1,22 @@
+use accutools_core::GlAccounts;
+use common::{item_line, receipt_with_payment, total};
+
+mod common;
+
+#[test]
+fn fees_in_the_totals_are_posted() {
+    let mut receipt = receipt_with_payment("120.75");
+    receipt.payments[0].name = String::from("Cash");
+    receipt.item_lines = vec![item_line("100.00", true)];
+    receipt.totals = vec![
+        total("Subtotal:", "100.00"),
+        total("Discount:", "-5.00"),
+        total("Delivery:", "15.00"),
+        total("Tax:", "5.75"),
+        total("Total:", "120.75"),
+    ];
+    let csv = receipt.to_journal_csv(&GlAccounts::default()).unwrap();
+    assert!(csv.contains("\n2024-01-31,1,CASH SALE,Other Income,Delivery,,15.00\n"));
+    assert!(csv.contains("\n2024-01-31,1,CASH SALE,Sales Tax Payable,Tax,,5.75\n"));
+    assert!(!csv.contains("Discount"));
+}
@@ -0,0 +1,40 @@
+// Renders with the compiled in fonts
+#![cfg(feature = "embedded-resources")]
+
+use accutools_core::{extract_text, gen_pdf_bytes_with_options, gen_pdf_bytes_with_text, Calibration, GenOptions, ItemLine, PageSize, PdfResources};
+use common::{item_line, receipt_with_payment, total};
+
+mod common;
+
+#[test]
+fn text_comes_back_top_to_bottom() {
+    let mut receipt = receipt_with_payment("19.75");
+    receipt.payments[0].name = String::from("Cash");
+    receipt.company_name = String::from("Quarry Supply");
+    receipt.item_lines = vec![
+        ItemLine { description: String::from("Gravel"), ..item_line("12.50", true) },
+        ItemLine { description: String::from("Sand"), ..item_line("7.25", true) },
+    ];
+    receipt.totals = vec![total("Total:", "19.75")];
+    let resources = PdfResources::embedded().unwrap();
+    let (_, pages) = gen_pdf_bytes_with_text(&receipt, &resources, &GenOptions::default()).unwrap();
+    assert_eq!(pages.len(), 1);
+    let lines: Vec<&str> = pages[0].lines().collect();
+    let position = |text: &str| lines.iter().position(|line| line.contains(text)).unwrap();
+    assert!(position("Quarry Supply") < position("Gravel"));
+    assert!(position("Gravel") < position("Sand"));
+    assert!(lines[position("Gravel")].contains("12.50"));
+    assert!(lines[position("Sand")].contains("7.25"));
+    assert!(lines.iter().rposition(|line| line.contains("19.75")).unwrap() > position("Sand"));
+
+    // Scaling the page onto other paper or calibrating it moves the text
+    // together, so it reads the same
+    let moved = [
+        GenOptions { page_size: PageSize::A4, ..GenOptions::default() },
+        GenOptions { calibration: Calibration { x_offset: 10.0, y_offset: -5.0, scale: 0.98 }, ..GenOptions::default() },
+    ];
+    for options in moved {
+        let bytes = gen_pdf_bytes_with_options(&receipt, &resources, &options).unwrap();
+        assert_eq!(extract_text(&bytes).unwrap(), pages);
+    }
+}
@@ -0,0 +1,71 @@
+use accutools_core::{facturx_xml, DocType, EInvoice, ItemLine, ReceiptInfo, Rounding, TaxRate};
+use common::{item_line, money, receipt_with_payment, total};
+
+mod common;
+
+fn line(quantity: &str, amount: &str, taxable: bool) -> ItemLine {
+    return ItemLine {
+        description: String::from("Cement"),
+        quantity: quantity.to_owned(),
+        unit_price: String::from("50.00"),
+        uom: String::from("EA"),
+        ..item_line(amount, taxable)
+    };
+}
+
+fn invoice(item_lines: Vec<ItemLine>, totals: [(&str, &str); 3]) -> ReceiptInfo {
+    let mut receipt = receipt_with_payment("0.00");
+    receipt.doc_type = DocType::Invoice;
+    receipt.company_name = String::from("Matériaux & Fils");
+    receipt.customer_info = String::from("Jane Doe\n1 Rue des Érables");
+    receipt.vat_number = String::from("FR12345678901");
+    receipt.item_lines = item_lines;
+    receipt.totals = totals.iter().map(|(name, value)| total(name, value)).collect();
+    return receipt;
+}
+
+fn e_invoice() -> EInvoice {
+    return EInvoice {
+        seller_country: String::from("FR"),
+        buyer_country: String::from("FR"),
+        currency: String::from("EUR"),
+        tax_rate: TaxRate { name: String::from("VAT"), percent: 10.0 },
+        exemption_reason: String::new(),
+        charge_reason: String::from("Handling"),
+        payment_terms: String::from("Net 30"),
+        unit_codes: Vec::new(),
+        rounding: Rounding::default(),
+    };
+}
+
+#[test]
+fn factur_x_has_the_printed_totals() {
+    let mut receipt = invoice(
+        vec![line("2", "90.00", true), line("2", "100.00", false), line("1", "55.00", false)],
+        [("Subtotal:", "245.00"), ("VAT:", "9.00"), ("Total:", "254.00")],
+    );
+    receipt.amount_due = Some(money("254.00"));
+    let e_invoice = e_invoice();
+    let xml = facturx_xml(&receipt, &e_invoice).unwrap();
+    assert!(xml.contains("<ram:TypeCode>380</ram:TypeCode>"));
+    assert!(xml.contains("<ram:Name>Matériaux &amp; Fils</ram:Name>"));
+    assert!(xml.contains("<udt:Indicator>false</udt:Indicator></ram:ChargeIndicator><ram:ActualAmount>10.00</ram:ActualAmount><ram:ReasonCode>95</ram:ReasonCode><ram:Reason>Discount</ram:Reason>"));
+    assert!(xml.contains("<udt:Indicator>true</udt:Indicator></ram:ChargeIndicator><ram:ActualAmount>5.00</ram:ActualAmount><ram:Reason>Handling</ram:Reason>"));
+    assert!(xml.contains("<ram:TaxTotalAmount currencyID=\"EUR\">9.00</ram:TaxTotalAmount>"));
+    assert!(xml.contains("<ram:GrandTotalAmount>254.00</ram:GrandTotalAmount>"));
+    assert!(!xml.contains("TotalPrepaidAmount"));
+
+    receipt.doc_type = DocType::Quote;
+    assert!(facturx_xml(&receipt, &e_invoice).is_err());
+}
+
+#[test]
+fn refunds_are_credit_notes() {
+    let receipt = invoice(vec![line("-2", "-100.00", true)], [("Subtotal:", "-100.00"), ("VAT:", "-10.00"), ("Total:", "-110.00")]);
+    let xml = facturx_xml(&receipt, &e_invoice()).unwrap();
+    assert!(xml.contains("<ram:TypeCode>381</ram:TypeCode>"));
+    assert!(xml.contains("<ram:BilledQuantity unitCode=\"C62\">2</ram:BilledQuantity>"));
+    assert!(xml.contains("<ram:LineTotalAmount>100.00</ram:LineTotalAmount>"));
+    assert!(xml.contains("<ram:GrandTotalAmount>110.00</ram:GrandTotalAmount>"));
+    assert!(!xml.contains("SpecifiedTradeAllowanceCharge"));
+}
@@ -0,0 +1,54 @@
+use accutools_core::{parse_markdown, wrap_runs, MarkdownBlock, MarkdownRun, MarkdownStyle};
+
+fn run(text: &str, style: MarkdownStyle) -> MarkdownRun {
+    return MarkdownRun { text: text.to_owned(), style };
+}
+
+#[test]
+fn unclosed_markers_print_as_is() {
+    assert_eq!(parse_markdown("5 * 2 and **not bold"), [
+        MarkdownBlock::Paragraph(vec![run("5 * 2 and **not bold", MarkdownStyle::Regular)]),
+    ]);
+    assert_eq!(parse_markdown("_open and *closed*"), [
+        MarkdownBlock::Paragraph(vec![run("_open and ", MarkdownStyle::Regular), run("closed", MarkdownStyle::Italic)]),
+    ]);
+}
+
+#[test]
+fn emphasis_nests() {
+    assert_eq!(parse_markdown("**bold *both* bold** plain"), [
+        MarkdownBlock::Paragraph(vec![
+            run("bold ", MarkdownStyle::Bold),
+            run("both", MarkdownStyle::BoldItalic),
+            run(" bold", MarkdownStyle::Bold),
+            run(" plain", MarkdownStyle::Regular),
+        ]),
+    ]);
+}
+
+#[test]
+fn headings_paragraphs_and_bullets() {
+    let text = "# Terms *now*\nPay within\n30 days.\n\n- First __item__\n* Second";
+    assert_eq!(parse_markdown(text), [
+        MarkdownBlock::Heading(vec![run("Terms ", MarkdownStyle::Bold), run("now", MarkdownStyle::BoldItalic)]),
+        MarkdownBlock::Paragraph(vec![run("Pay within 30 days.", MarkdownStyle::Regular)]),
+        MarkdownBlock::Bullet(vec![run("First ", MarkdownStyle::Regular), run("item", MarkdownStyle::Bold)]),
+        MarkdownBlock::Bullet(vec![run("Second", MarkdownStyle::Regular)]),
+    ]);
+}
+
+#[test]
+fn wrapping_keeps_the_styles_of_each_word() {
+    let runs = [
+        run("Pay the ", MarkdownStyle::Regular),
+        run("full amount", MarkdownStyle::Bold),
+        run(", within thirty days", MarkdownStyle::Regular),
+    ];
+    // The comma stays with the word before it although it's another style
+    assert_eq!(wrap_runs(&runs, 12), [
+        vec![run("Pay the", MarkdownStyle::Regular), run(" full", MarkdownStyle::Bold)],
+        vec![run("amount", MarkdownStyle::Bold), run(",", MarkdownStyle::Regular)],
+        vec![run("within", MarkdownStyle::Regular)],
+        vec![run("thirty days", MarkdownStyle::Regular)],
+    ]);
+}
@@ -0,0 +1,16 @@
+use accutools_core::{NumberingFormat, NumberingMode};
+
+#[test]
+fn legal_numbering() {
+    let format = NumberingFormat {
+        prefix: String::from("F"),
+        fiscal_year_start: Some(4),
+        separator: String::from("-"),
+        digits: 6,
+        mode: NumberingMode::Reformat,
+        doc_types: Vec::new(),
+    };
+    assert_eq!(format.format("123", "2026-05-01").unwrap(), "F2026-000123");
+    assert_eq!(format.format("INV 00123", "03/15/2026").unwrap(), "F2025-000123");
+    assert!(format.format("1234567", "2026-05-01").is_err());
+}
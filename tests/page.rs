@@ -0,0 +1,43 @@
+// Renders with the compiled in fonts
+#![cfg(feature = "embedded-resources")]
+
+use accutools_core::{gen_pdf_bytes_with_options, gen_pdf_with_options, Amount, DocType, GenOptions, ItemLine, PageSize, PdfResources, ReceiptInfo};
+use printpdf::lopdf::{Document, Object};
+
+fn receipt() -> ReceiptInfo {
+    return ReceiptInfo {
+        date: String::from("01/31/2024"),
+        doc_number: String::from("1"),
+        doc_type: DocType::Receipt,
+        item_lines: vec![ItemLine {
+            description: String::from("Gravel"),
+            amount: Some("12.50".parse().unwrap()),
+            ..ItemLine::default()
+        }],
+        totals: vec![Amount { name: String::from("Total:"), value: "12.50".parse().unwrap() }],
+        ..ReceiptInfo::default()
+    };
+}
+
+#[test]
+fn a4_is_applied_to_the_saved_file() {
+    let options = GenOptions { page_size: PageSize::A4, ..GenOptions::default() };
+    let resources = PdfResources::embedded().unwrap();
+    let doc = Document::load_mem(&gen_pdf_bytes_with_options(&receipt(), &resources, &options).unwrap()).unwrap();
+    for (_, page_id) in doc.get_pages() {
+        let media_box: Vec<f64> = doc.get_object(page_id).unwrap().as_dict().unwrap()
+            .get(b"MediaBox").unwrap()
+            .as_array().unwrap()
+            .iter()
+            .map(|value| match value {
+                Object::Integer(value) => *value as f64,
+                Object::Real(value) => *value,
+                _ => panic!("MediaBox entry {value:?} isn't a number"),
+            })
+            .collect();
+        // 210mm x 297mm
+        assert_eq!(media_box.iter().map(|value| value.round()).collect::<Vec<_>>(), [0.0, 0.0, 595.0, 842.0]);
+    }
+    // The unsaved document can only be Letter
+    assert!(gen_pdf_with_options(&receipt(), &resources, &options).is_err());
+}
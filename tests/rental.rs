@@ -0,0 +1,10 @@
+use accutools_core::{Rental, RentalPeriod};
+
+#[test]
+fn rental_duration_rounds_up() {
+    let rental = |start: &str, end: &str, period| Rental { start: start.into(), end: end.into(), rate: String::from("85.00"), period };
+    assert_eq!(rental("2026-03-02 08:00", "2026-03-04 17:00", RentalPeriod::Day).duration().unwrap(), 3);
+    assert_eq!(rental("03/02/2026 8:00 AM", "03/02/2026 1:30 PM", RentalPeriod::Hour).duration().unwrap(), 6);
+    assert_eq!(rental("2026-03-02 08:00", "2026-03-02 08:00", RentalPeriod::Hour).duration().unwrap(), 1);
+    assert!(rental("2026-03-04", "2026-03-02", RentalPeriod::Day).duration().is_err());
+}
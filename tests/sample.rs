@@ -0,0 +1,11 @@
+#![cfg(feature = "sample-data")]
+
+use accutools_core::{sample_documents, Rounding, TaxRate};
+
+#[test]
+fn sample_documents_add_up() {
+    let rates = [TaxRate { name: String::from("VAT"), percent: 10.0 }];
+    for document in sample_documents(7, 50) {
+        document.compute_totals(&rates, &Rounding::default()).unwrap();
+    }
+}
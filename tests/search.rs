@@ -0,0 +1,21 @@
+use accutools_core::SearchQuery;
+use common::{receipt_with_payment, total};
+
+mod common;
+
+#[test]
+fn search_by_total_and_date() {
+    let mut receipt = receipt_with_payment("0.00");
+    receipt.customer_info = String::from("Jane Doe\nNassau");
+    receipt.totals = vec![total("Total:", "1,250.00")];
+    let query = |min: &str, max: &str, from: &str| SearchQuery {
+        customer: String::from("doe"),
+        min_amount: min.to_owned(),
+        max_amount: max.to_owned(),
+        from: from.to_owned(),
+        ..SearchQuery::default()
+    };
+    assert!(query("1000", "2000", "2024-01-01").matches(&receipt).unwrap());
+    assert!(!query("", "1000", "").matches(&receipt).unwrap());
+    assert!(!query("", "", "02/01/2024").matches(&receipt).unwrap());
+}
@@ -0,0 +1,35 @@
+// Renders with the compiled in fonts
+#![cfg(feature = "embedded-resources")]
+
+use accutools_core::{gen_pdf_bytes_with_options, DocType, GenOptions, PdfResources, ReceiptInfo, Template};
+
+#[test]
+fn the_delivery_checklist_fits_above_the_signature() {
+    let receipt = ReceiptInfo {
+        date: String::from("01/31/2024"),
+        doc_number: String::from("D-1"),
+        doc_type: DocType::DeliveryTicket,
+        ..ReceiptInfo::default()
+    };
+    let resources = PdfResources::embedded().unwrap();
+    let long_item = String::from("Dumped where directed by the customer, clear of the driveway, the septic field and the overhead lines");
+    let short_items: Vec<String> = (1..=10).map(|number| format!("Check {number}")).collect();
+    // Ten rows fit with the default template
+    let mut options = GenOptions { delivery_checklist: short_items.clone(), ..GenOptions::default() };
+    assert!(gen_pdf_bytes_with_options(&receipt, &resources, &options).is_ok());
+    options.delivery_checklist.push(String::from("Site accessible"));
+    assert!(gen_pdf_bytes_with_options(&receipt, &resources, &options).is_err());
+
+    // The long item wraps, so it takes more than one row
+    options.delivery_checklist = vec![long_item];
+    assert!(gen_pdf_bytes_with_options(&receipt, &resources, &options).is_ok());
+    options.delivery_checklist.extend_from_slice(&short_items[1..]);
+    assert!(gen_pdf_bytes_with_options(&receipt, &resources, &options).is_err());
+
+    // An item table reaching below the checklist's first row leaves no room
+    options.template = Template { table_bottom: 100.0, ..Template::default() };
+    options.delivery_checklist = vec![String::from("Site accessible")];
+    assert!(gen_pdf_bytes_with_options(&receipt, &resources, &options).is_err());
+    options.delivery_checklist.clear();
+    assert!(gen_pdf_bytes_with_options(&receipt, &resources, &options).is_ok());
+}
@@ -0,0 +1,51 @@
+// Renders with the compiled in fonts
+#![cfg(feature = "embedded-resources")]
+
+use accutools_core::{gen_pdf_bytes_with_options, gen_pdf_bytes_with_text, DocType, GenOptions, Money, PdfResources, ReceiptInfo, Statement, StatementLine};
+use printpdf::lopdf::Document;
+
+fn statement(count: usize) -> ReceiptInfo {
+    let transactions = (1..=count)
+        .map(|number| StatementLine {
+            date: String::from("01/15/2024"),
+            reference: format!("INV {number}"),
+            description: String::from("Invoice"),
+            amount: "10.00".parse().unwrap(),
+            balance: None,
+            due_date: String::new(),
+        })
+        .collect();
+    return ReceiptInfo {
+        date: String::from("01/31/2024"),
+        doc_number: String::from("S-1"),
+        doc_type: DocType::Statement,
+        statement: Some(Statement {
+            opening_balance: None,
+            closing_balance: Some(Money::from_cents(1000 * count as i64)),
+            transactions,
+            aging: None,
+        }),
+        ..ReceiptInfo::default()
+    };
+}
+
+#[test]
+fn long_statements_continue_on_further_pages() {
+    let mut receipt = statement(200);
+    receipt.pre_pass().unwrap();
+    assert_eq!(receipt.statement.as_ref().unwrap().transactions[199].balance, "2000.00".parse().ok());
+    let bytes = gen_pdf_bytes_with_options(&receipt, &PdfResources::embedded().unwrap(), &GenOptions::default()).unwrap();
+    // 15 rows fit on the first page and 29 on each page after it
+    assert_eq!(Document::load_mem(&bytes).unwrap().get_pages().len(), 8);
+}
+
+#[test]
+fn notes_are_above_the_aging_summary() {
+    let mut receipt = statement(2);
+    receipt.notes = String::from("Thank you for your business. Accounts over 30 days are charged 2% a month.");
+    receipt.pre_pass().unwrap();
+    let (_, pages) = gen_pdf_bytes_with_text(&receipt, &PdfResources::embedded().unwrap(), &GenOptions::default()).unwrap();
+    let lines: Vec<&str> = pages[0].lines().collect();
+    let position = |text: &str| lines.iter().position(|line| line.contains(text)).unwrap();
+    assert!(position("Thank you") < position("Current"));
+}
@@ -0,0 +1,20 @@
+use accutools_core::PdfStore;
+use common::receipt_with_payment;
+
+mod common;
+
+#[test]
+fn every_distinct_pdf_is_stored_once() {
+    let dir = std::env::temp_dir().join(format!("accutools-store-{}", std::process::id()));
+    let store = PdfStore::open(dir.to_str().unwrap()).unwrap();
+    let receipt = receipt_with_payment("10.00");
+    let first = store.put(&receipt, b"%PDF-1.3 first").unwrap();
+    assert_eq!(store.put(&receipt, b"%PDF-1.3 first").unwrap(), first);
+    // The same content rendered with other options
+    let second = store.put(&receipt, b"%PDF-1.3 second").unwrap();
+    assert_ne!(second, first);
+    let hashes: Vec<String> = store.history("1").unwrap().into_iter().map(|stored| stored.hash).collect();
+    assert_eq!(hashes, [first, second]);
+    assert_eq!(store.get(&hashes[1]).unwrap(), b"%PDF-1.3 second");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,54 @@
+use accutools_core::{Amount, DocType, ItemLine, PrePassOptions, ReceiptInfo, Surcharge, SurchargeAmount};
+use common::{item_line, money, total};
+
+mod common;
+
+fn rows(amounts: &[Amount]) -> Vec<(&str, String)> {
+    return amounts.iter().map(|amount| (amount.name.as_str(), amount.value.to_string())).collect();
+}
+
+#[test]
+fn surcharges_are_added_once() {
+    let mut receipt = ReceiptInfo {
+        date: String::from("01/31/2024"),
+        doc_number: String::from("1"),
+        doc_type: DocType::Invoice,
+        item_lines: vec![
+            ItemLine { code: String::from("GRV"), ..item_line("100.00", true) },
+            ItemLine { code: String::from("TIRE-16"), ..item_line("50.00", true) },
+        ],
+        totals: vec![total("Subtotal:", "150.00"), total("Tax:", "7.50"), total("Total:", "157.50")],
+        amount_due: Some(money("157.50")),
+        ..ReceiptInfo::default()
+    };
+    let options = PrePassOptions {
+        surcharges: vec![
+            Surcharge {
+                name: String::from("Fuel Surcharge:"),
+                amount: SurchargeAmount::Percent(3.5),
+                item_codes: Vec::new(),
+                doc_types: Vec::new(),
+            },
+            Surcharge {
+                name: String::from("Tire Fee:"),
+                amount: SurchargeAmount::Flat(2.0),
+                item_codes: vec![String::from("TIRE")],
+                doc_types: Vec::new(),
+            },
+        ],
+        ..PrePassOptions::default()
+    };
+    let expected: Vec<(&str, String)> = [
+        ("Subtotal:", "150.00"),
+        ("Fuel Surcharge:", "5.25"),
+        ("Tire Fee:", "2.00"),
+        ("Tax:", "7.50"),
+        ("Total:", "164.75"),
+    ].map(|(name, value)| (name, value.to_owned())).into();
+    for _ in 0..2 {
+        receipt.pre_pass_with_options(&options).unwrap();
+        assert_eq!(rows(&receipt.totals), expected);
+        assert_eq!(rows(&receipt.surcharges), expected[1..3]);
+        assert_eq!(receipt.amount_due, Some(money("164.75")));
+    }
+}
@@ -0,0 +1,29 @@
+use accutools_core::{Template, TextPlacement};
+
+#[test]
+fn templates_are_laid_over_the_default() {
+    let built_in = Template::default();
+    assert_eq!(built_in.title, TextPlacement { x: 254.0, y: 750.0, size: 14.0 });
+    assert_eq!((built_in.table_top, built_in.table_bottom), (514.0, 254.0));
+    assert_eq!(built_in.columns.len(), 6);
+
+    let template = Template::parse("table_top = 520\n\n[title]\nx = 300 # centered\n\n[columns.receipt]\nlines = [450]").unwrap();
+    assert_eq!(template.title, TextPlacement { x: 300.0, ..built_in.title });
+    assert_eq!((template.table_top, template.table_bottom), (520.0, 254.0));
+    let receipt = &template.columns.iter().find(|(name, _)| name == "receipt").unwrap().1;
+    assert_eq!((receipt.lines.as_slice(), receipt.desc_chars), ([450.0].as_slice(), 0));
+    assert_eq!(template.columns.len(), 6);
+}
+
+#[test]
+fn invalid_templates_are_rejected() {
+    assert!(Template::parse("[columns.receipt]\nlines = [300, 400]").is_err());
+    assert!(Template::parse("[columns.invoice]\nlines = [300]").is_err());
+    assert!(Template::parse("[title]\nwidth = 300").is_err());
+    assert!(Template::parse("[title]\nx = \"300\"").is_err());
+    assert!(Template::parse("[columns.standard]\ndesc_chars = -1").is_err());
+    assert!(Template::parse("[title\nx = 300").is_err());
+    // Narrower than one character between the paddings
+    assert!(Template::parse("[columns.standard]\nlines = [95, 100, 339, 408, 488]").is_err());
+    assert!(Template::parse("[columns.standard]\nlines = [95, 110, 339, 408, 488]").is_ok());
+}
@@ -0,0 +1,20 @@
+use accutools_core::{Money, Rounding, TaxRate, TotalsMismatch};
+use common::{item_line, receipt_with_payment, total};
+
+mod common;
+
+#[test]
+fn totals_are_verified() {
+    let mut receipt = receipt_with_payment("0.00");
+    receipt.item_lines = vec![item_line("100.00", true), item_line("20.00", false)];
+    receipt.totals = vec![total("Subtotal:", "120.00"), total("VAT:", "10.00"), total("Total:", "130.00")];
+    let rates = [TaxRate { name: String::from("VAT"), percent: 10.0 }];
+    let computed = receipt.compute_totals(&rates, &Rounding::default()).unwrap();
+    assert_eq!(computed.total, Money::from_cents(13000));
+
+    receipt.totals[2].value = Money::from_cents(13100);
+    let error = receipt.compute_totals(&rates, &Rounding::default()).unwrap_err();
+    let mismatch = error.downcast_ref::<TotalsMismatch>().unwrap();
+    assert_eq!(mismatch.totals.len(), 1);
+    assert_eq!(mismatch.totals[0].delta, Money::from_cents(100));
+}